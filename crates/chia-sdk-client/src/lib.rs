@@ -1,11 +1,20 @@
-use std::fs;
+use std::{fs, future::Future, time::Duration};
 
-use chia_client::Peer;
+use chia_client::{Peer, PeerEvent};
+use chia_protocol::{Bytes32, CoinState, CoinStateUpdate};
 use chia_ssl::ChiaCertificate;
+use futures_util::{stream, Stream};
 use native_tls::{Identity, TlsConnector};
 use thiserror::Error;
+use tokio::{sync::broadcast, time::sleep};
 use tokio_tungstenite::{connect_async_tls_with_config, Connector};
 
+/// The maximum number of coin ids that will be sent in a single subscription request.
+pub const COIN_SUBSCRIPTION_BATCH_SIZE: usize = 5000;
+
+/// How long to wait before trying again after failing to connect or subscribe.
+pub const RECONNECT_DELAY: Duration = Duration::from_secs(1);
+
 /// An error that occurs while trying to load an SSL certificate.
 #[derive(Debug, Error)]
 pub enum SslError {
@@ -63,3 +72,270 @@ pub async fn connect_peer(
     .await?;
     Ok(Peer::new(ws))
 }
+
+/// Registers for coin updates in batches of [`COIN_SUBSCRIPTION_BATCH_SIZE`], to avoid exceeding protocol limits.
+/// The initial coin states of every batch are aggregated and returned.
+pub async fn register_for_coin_updates_chunked(
+    peer: &Peer,
+    coin_ids: Vec<Bytes32>,
+    min_height: u32,
+) -> Result<Vec<CoinState>, chia_client::Error<()>> {
+    let mut coin_states = Vec::new();
+
+    for chunk in coin_ids.chunks(COIN_SUBSCRIPTION_BATCH_SIZE) {
+        coin_states.extend(
+            peer.register_for_coin_updates(chunk.to_vec(), min_height)
+                .await?,
+        );
+    }
+
+    Ok(coin_states)
+}
+
+/// An error that occurs while streaming coin state updates.
+/// The stream tries again after [`RECONNECT_DELAY`] when it's polled after an error.
+#[derive(Debug, Error)]
+pub enum CoinStateStreamError<E> {
+    /// When connecting to a new peer fails.
+    #[error("failed to connect: {0}")]
+    Connect(E),
+
+    /// When subscribing to the puzzle hashes fails.
+    #[error("failed to subscribe: {0}")]
+    Subscribe(chia_client::Error<()>),
+}
+
+/// An item yielded by [`coin_state_stream`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CoinStateEvent {
+    /// The coin states returned by subscribing to the puzzle hashes, starting at the height of the last update.
+    /// This happens for the initial subscription and after reconnecting.
+    Subscribed(Vec<CoinState>),
+
+    /// A coin state update sent by the peer.
+    Update(CoinStateUpdate),
+}
+
+/// Streams coin state events for the given puzzle hashes, using `connect` to create a new peer
+/// and resubscribe whenever the connection is lost or updates are dropped.
+///
+/// Errors while connecting or subscribing are yielded rather than retried silently,
+/// so it's up to the caller whether to keep polling the stream or stop.
+pub fn coin_state_stream<F, Fut, E>(
+    connect: F,
+    puzzle_hashes: Vec<Bytes32>,
+) -> impl Stream<Item = Result<CoinStateEvent, CoinStateStreamError<E>>>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<Peer, E>>,
+{
+    let state = CoinStateStream {
+        connect,
+        puzzle_hashes,
+        connection: None,
+        height: 0,
+        failed: false,
+    };
+
+    stream::unfold(state, |mut state| async move {
+        let event = state.next_event().await;
+        Some((event, state))
+    })
+}
+
+struct CoinStateStream<F> {
+    connect: F,
+    puzzle_hashes: Vec<Bytes32>,
+    connection: Option<(Peer, broadcast::Receiver<PeerEvent>)>,
+    height: u32,
+    failed: bool,
+}
+
+impl<F> CoinStateStream<F> {
+    async fn next_event<Fut, E>(&mut self) -> Result<CoinStateEvent, CoinStateStreamError<E>>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<Peer, E>>,
+    {
+        loop {
+            if let Some((_, receiver)) = &mut self.connection {
+                match receiver.recv().await {
+                    Ok(PeerEvent::CoinStateUpdate(update)) => {
+                        self.height = update.height;
+                        return Ok(CoinStateEvent::Update(update));
+                    }
+                    Ok(PeerEvent::NewPeakWallet(..)) => continue,
+                    Err(_) => self.connection = None,
+                }
+            }
+
+            if self.failed {
+                sleep(RECONNECT_DELAY).await;
+            }
+
+            self.failed = true;
+
+            let peer = (self.connect)()
+                .await
+                .map_err(CoinStateStreamError::Connect)?;
+
+            // Listen for events before subscribing, so that no updates are missed.
+            let receiver = peer.receiver().resubscribe();
+
+            let coin_states = peer
+                .register_for_ph_updates(self.puzzle_hashes.clone(), self.height)
+                .await
+                .map_err(CoinStateStreamError::Subscribe)?;
+
+            self.failed = false;
+            self.connection = Some((peer, receiver));
+
+            if coin_states.is_empty() {
+                continue;
+            }
+
+            for coin_state in &coin_states {
+                let created_height = coin_state.created_height.unwrap_or(0);
+                let spent_height = coin_state.spent_height.unwrap_or(0);
+                self.height = self.height.max(created_height).max(spent_height);
+            }
+
+            return Ok(CoinStateEvent::Subscribed(coin_states));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chia_protocol::Coin;
+    use chia_puzzles::standard::StandardArgs;
+    use chia_sdk_driver::{Conditions, SpendContext};
+    use std::io::ErrorKind;
+
+    use chia_sdk_test::{secret_key, test_transaction, Simulator, SimulatorError};
+    use futures_util::{pin_mut, StreamExt};
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_register_for_coin_updates_chunked() -> anyhow::Result<()> {
+        let sim = Simulator::new().await?;
+        let peer = sim.connect().await?;
+
+        let mut coin_ids = Vec::new();
+
+        for amount in 0..20_000 {
+            coin_ids.push(sim.mint_coin(Bytes32::default(), amount).await.coin_id());
+        }
+
+        let coin_states = register_for_coin_updates_chunked(&peer, coin_ids.clone(), 0).await?;
+        assert_eq!(coin_states.len(), coin_ids.len());
+
+        for (coin_id, coin_state) in coin_ids.into_iter().zip(coin_states) {
+            assert_eq!(coin_state.coin.coin_id(), coin_id);
+            assert_eq!(coin_state.created_height, Some(0));
+        }
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_coin_state_stream_reconnect() -> anyhow::Result<()> {
+        let sim = Simulator::new().await?;
+        let peer = sim.connect().await?;
+        let ctx = &mut SpendContext::new();
+
+        let sk = secret_key()?;
+        let pk = sk.public_key();
+        let puzzle_hash = StandardArgs::curry_tree_hash(pk).into();
+
+        let coin = sim.mint_coin(puzzle_hash, 1).await;
+
+        let updates = coin_state_stream(|| sim.connect(), vec![puzzle_hash]);
+        pin_mut!(updates);
+
+        // The initial subscription yields the existing coin.
+        let event = updates.next().await.expect("expected event")?;
+        assert_eq!(
+            event,
+            CoinStateEvent::Subscribed(vec![sim.coin_state(coin.coin_id()).await.unwrap()])
+        );
+
+        ctx.spend_p2_coin(coin, pk, Conditions::new().create_coin(puzzle_hash, 1))?;
+        test_transaction(
+            &peer,
+            ctx.take_spends(),
+            &[sk.clone()],
+            sim.config().genesis_challenge,
+        )
+        .await;
+
+        let CoinStateEvent::Update(update) = updates.next().await.expect("expected event")? else {
+            panic!("expected coin state update");
+        };
+        assert_eq!(update.items.len(), 2);
+
+        let child = Coin::new(coin.coin_id(), puzzle_hash, 1);
+        sim.disconnect_all().await;
+
+        // The child is spent while the stream is disconnected.
+        let peer = sim.connect().await?;
+        ctx.spend_p2_coin(child, pk, Conditions::new().create_coin(puzzle_hash, 1))?;
+        test_transaction(
+            &peer,
+            ctx.take_spends(),
+            &[sk],
+            sim.config().genesis_challenge,
+        )
+        .await;
+
+        // The spend is picked up after resubscribing.
+        let CoinStateEvent::Subscribed(coin_states) =
+            updates.next().await.expect("expected event")?
+        else {
+            panic!("expected resubscription");
+        };
+        let child_state = sim.coin_state(child.coin_id()).await.unwrap();
+        assert!(child_state.spent_height.is_some());
+        assert!(coin_states.contains(&child_state));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_coin_state_stream_error() -> anyhow::Result<()> {
+        let sim = Simulator::new().await?;
+        let mut attempts = 0;
+
+        let updates = coin_state_stream(
+            || {
+                attempts += 1;
+                let connect = attempts > 1;
+                let sim = &sim;
+                async move {
+                    if connect {
+                        sim.connect().await
+                    } else {
+                        Err(SimulatorError::Io(ErrorKind::ConnectionRefused.into()))
+                    }
+                }
+            },
+            vec![Bytes32::default()],
+        );
+        pin_mut!(updates);
+
+        // The failure is yielded instead of being retried silently.
+        let error = updates.next().await.expect("expected event").unwrap_err();
+        assert!(matches!(error, CoinStateStreamError::Connect(_)));
+
+        // Polling again retries the connection.
+        let coin = sim.mint_coin(Bytes32::default(), 1).await;
+        let event = updates.next().await.expect("expected event")?;
+        assert_eq!(
+            event,
+            CoinStateEvent::Subscribed(vec![sim.coin_state(coin.coin_id()).await.unwrap()])
+        );
+
+        Ok(())
+    }
+}
@@ -1,18 +1,26 @@
+use std::fmt::Write;
+
 use chia_bls::PublicKey;
 use chia_protocol::{Bytes, Bytes32};
-use chia_puzzles::standard::{StandardArgs, StandardSolution};
+use chia_puzzles::{
+    offer::{NotarizedPayment, Payment, PaymentWithMemos},
+    standard::{StandardArgs, StandardSolution},
+};
 use chia_sdk_types::conditions::{
-    AssertBeforeHeightAbsolute, AssertBeforeHeightRelative, AssertBeforeSecondsAbsolute,
-    AssertBeforeSecondsRelative, AssertCoinAnnouncement, AssertHeightAbsolute,
-    AssertHeightRelative, AssertPuzzleAnnouncement, AssertSecondsAbsolute, AssertSecondsRelative,
-    Condition, CreateCoin, CreateCoinAnnouncement, CreatePuzzleAnnouncement, ReserveFee,
+    AggSig, AggSigKind, AssertBeforeHeightAbsolute, AssertBeforeHeightRelative,
+    AssertBeforeSecondsAbsolute, AssertBeforeSecondsRelative, AssertCoinAnnouncement,
+    AssertConcurrentPuzzle, AssertConcurrentSpend, AssertEphemeral, AssertHeightAbsolute,
+    AssertHeightRelative, AssertMyAmount, AssertMyCoinId, AssertMyParentId, AssertMyPuzzleHash,
+    AssertPuzzleAnnouncement, AssertSecondsAbsolute, AssertSecondsRelative, Condition, CreateCoin,
+    CreateCoinAnnouncement, CreatePuzzleAnnouncement, Remark, ReserveFee,
 };
 
-use clvm_traits::{ClvmEncoder, ToClvm, ToClvmError};
-use clvm_utils::CurriedProgram;
+use clvm_traits::{clvm_quote, ClvmEncoder, FromClvm, ToClvm, ToClvmError};
+use clvm_utils::{CurriedProgram, ToTreeHash};
 use clvmr::{
+    serde::node_to_bytes,
     sha2::{Digest, Sha256},
-    NodePtr,
+    Allocator, NodePtr,
 };
 
 use crate::{Spend, SpendContext, SpendError};
@@ -43,6 +51,23 @@ impl Conditions {
         self
     }
 
+    /// Combines the conditions from each of the given sets, in order.
+    pub fn extend_all(mut self, conditions: impl IntoIterator<Item = Conditions>) -> Self {
+        self.conditions.extend(conditions.into_iter().flatten());
+        self
+    }
+
+    /// Appends a `REMARK` condition carrying the given payload, which is ignored by consensus.
+    /// The payload is allocated in the [`SpendContext`], since conditions are stored as CLVM.
+    pub fn remark(
+        self,
+        ctx: &mut SpendContext,
+        data: impl ToClvm<NodePtr>,
+    ) -> Result<Self, SpendError> {
+        let data = ctx.alloc(&data)?;
+        Ok(self.condition(Condition::Remark(Remark::new(data))))
+    }
+
     pub fn reserve_fee(self, fee: u64) -> Self {
         self.condition(Condition::ReserveFee(ReserveFee::new(fee)))
     }
@@ -59,6 +84,20 @@ impl Conditions {
         )))
     }
 
+    /// Creates a coin with arbitrary memos, such as a hint followed by a payment reference.
+    pub fn create_coin_with_memos(
+        self,
+        puzzle_hash: Bytes32,
+        amount: u64,
+        memos: Vec<Bytes>,
+    ) -> Self {
+        self.condition(Condition::CreateCoin(CreateCoin::with_memos(
+            puzzle_hash,
+            amount,
+            memos,
+        )))
+    }
+
     pub fn create_coin_announcement(self, message: Bytes) -> Self {
         self.condition(Condition::CreateCoinAnnouncement(
             CreateCoinAnnouncement::new(message),
@@ -101,6 +140,64 @@ impl Conditions {
         self.assert_raw_puzzle_announcement(Bytes32::new(announcement_id.finalize().into()))
     }
 
+    /// Pays the royalty for an NFT trade, and announces it as a notarized payment with the launcher id as the nonce.
+    /// The NFT's royalty transfer program asserts this announcement from the puzzle hash of each trade price.
+    pub fn pay_royalty(
+        self,
+        launcher_id: Bytes32,
+        royalty_puzzle_hash: Bytes32,
+        royalty_amount: u64,
+    ) -> Self {
+        let notarized_payment = NotarizedPayment {
+            nonce: launcher_id,
+            payments: vec![Payment::WithMemos(PaymentWithMemos {
+                puzzle_hash: royalty_puzzle_hash,
+                amount: royalty_amount,
+                memos: vec![royalty_puzzle_hash.to_vec().into()],
+            })],
+        };
+
+        self.create_hinted_coin(royalty_puzzle_hash, royalty_amount, royalty_puzzle_hash)
+            .create_puzzle_announcement(notarized_payment.tree_hash().to_vec().into())
+    }
+
+    pub fn assert_concurrent_spend(self, coin_id: Bytes32) -> Self {
+        self.condition(Condition::AssertConcurrentSpend(
+            AssertConcurrentSpend::new(coin_id),
+        ))
+    }
+
+    pub fn assert_concurrent_puzzle(self, puzzle_hash: Bytes32) -> Self {
+        self.condition(Condition::AssertConcurrentPuzzle(
+            AssertConcurrentPuzzle::new(puzzle_hash),
+        ))
+    }
+
+    /// Asserts that the coin being spent was created in the same spend bundle, as used by offer fast-forward spends.
+    pub fn assert_ephemeral(self) -> Self {
+        self.condition(Condition::AssertEphemeral(AssertEphemeral::new()))
+    }
+
+    pub fn assert_my_coin_id(self, coin_id: Bytes32) -> Self {
+        self.condition(Condition::AssertMyCoinId(AssertMyCoinId::new(coin_id)))
+    }
+
+    pub fn assert_my_parent_id(self, parent_id: Bytes32) -> Self {
+        self.condition(Condition::AssertMyParentId(AssertMyParentId::new(
+            parent_id,
+        )))
+    }
+
+    pub fn assert_my_puzzle_hash(self, puzzle_hash: Bytes32) -> Self {
+        self.condition(Condition::AssertMyPuzzleHash(AssertMyPuzzleHash::new(
+            puzzle_hash,
+        )))
+    }
+
+    pub fn assert_my_amount(self, amount: u64) -> Self {
+        self.condition(Condition::AssertMyAmount(AssertMyAmount::new(amount)))
+    }
+
     pub fn assert_before_seconds_relative(self, seconds: u64) -> Self {
         self.condition(Condition::AssertBeforeSecondsRelative(
             AssertBeforeSecondsRelative::new(seconds),
@@ -149,6 +246,101 @@ impl Conditions {
         )))
     }
 
+    /// Sorts the `AGG_SIG` conditions by public key, message, and kind, leaving other conditions in place.
+    /// Only the aggregate signature matters for validity, but a stable order makes signing reproducible
+    /// when conditions are combined from multiple sources.
+    pub fn sort_agg_sigs(mut self) -> Self {
+        let mut agg_sigs: Vec<AggSig> = self
+            .conditions
+            .iter()
+            .filter_map(|condition| match condition {
+                Condition::AggSig(agg_sig) => Some(agg_sig.clone()),
+                _ => None,
+            })
+            .collect();
+
+        agg_sigs.sort_by(|a, b| {
+            a.public_key
+                .to_bytes()
+                .cmp(&b.public_key.to_bytes())
+                .then_with(|| a.message.cmp(&b.message))
+                .then_with(|| (a.kind as u8).cmp(&(b.kind as u8)))
+        });
+
+        let mut agg_sigs = agg_sigs.into_iter();
+
+        for condition in &mut self.conditions {
+            let Condition::AggSig(agg_sig) = condition else {
+                continue;
+            };
+
+            if let Some(sorted) = agg_sigs.next() {
+                *agg_sig = sorted;
+            }
+        }
+
+        self
+    }
+
+    /// Removes repeated assertions, announcements, and `AGG_SIG` conditions, keeping the first occurrence.
+    /// Conditions that have an effect each time they're output, such as `CREATE_COIN` and `RESERVE_FEE`, are kept.
+    pub fn deduped(self) -> Self {
+        let mut conditions = Vec::with_capacity(self.conditions.len());
+
+        for condition in self.conditions {
+            if is_idempotent(&condition) && conditions.contains(&condition) {
+                continue;
+            }
+            conditions.push(condition);
+        }
+
+        Self { conditions }
+    }
+
+    /// Appends the other conditions and removes any idempotent conditions that are now repeated.
+    pub fn merge(self, other: Conditions) -> Self {
+        self.extend(other).deduped()
+    }
+
+    /// Iterates over the conditions in order, without consuming them.
+    pub fn iter(&self) -> std::slice::Iter<'_, Condition> {
+        self.conditions.iter()
+    }
+
+    /// The `CREATE_COIN` conditions, in order.
+    pub fn created_coins(&self) -> Vec<CreateCoin> {
+        self.iter()
+            .filter_map(|condition| match condition {
+                Condition::CreateCoin(create_coin) => Some(create_coin.clone()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// The messages of the `CREATE_COIN_ANNOUNCEMENT` conditions, in order.
+    pub fn coin_announcements(&self) -> Vec<Bytes> {
+        self.iter()
+            .filter_map(|condition| match condition {
+                Condition::CreateCoinAnnouncement(announcement) => {
+                    Some(announcement.message.clone())
+                }
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// The messages of the `CREATE_PUZZLE_ANNOUNCEMENT` conditions, in order.
+    pub fn puzzle_announcements(&self) -> Vec<Bytes> {
+        self.iter()
+            .filter_map(|condition| match condition {
+                Condition::CreatePuzzleAnnouncement(announcement) => {
+                    Some(announcement.message.clone())
+                }
+                _ => None,
+            })
+            .collect()
+    }
+
     pub fn p2_spend(
         self,
         ctx: &mut SpendContext,
@@ -165,6 +357,163 @@ impl Conditions {
 
         Ok(Spend::new(puzzle, solution))
     }
+
+    /// Calculates the tree hash of the quoted conditions, which is the message the standard puzzle
+    /// requires an `AGG_SIG_ME` signature on when spent with [`Conditions::p2_spend`].
+    pub fn delegated_puzzle_hash(&self, ctx: &mut SpendContext) -> Result<Bytes32, SpendError> {
+        let delegated_puzzle = ctx.alloc(&clvm_quote!(self))?;
+        Ok(ctx.tree_hash(delegated_puzzle).into())
+    }
+
+    /// Renders each condition on its own line in a human readable form, such as `CREATE_COIN ph=... amount=1`.
+    /// This is useful for figuring out which condition caused a spend to fail.
+    pub fn explain(&self, allocator: &Allocator) -> String {
+        self.conditions
+            .iter()
+            .map(|condition| explain_condition(allocator, condition))
+            .collect::<Vec<String>>()
+            .join("\n")
+    }
+}
+
+/// Unknown conditions aren't considered idempotent, since their effect can't be known.
+fn is_idempotent(condition: &Condition) -> bool {
+    matches!(
+        condition,
+        Condition::AggSig(..)
+            | Condition::CreateCoinAnnouncement(..)
+            | Condition::AssertCoinAnnouncement(..)
+            | Condition::CreatePuzzleAnnouncement(..)
+            | Condition::AssertPuzzleAnnouncement(..)
+            | Condition::AssertConcurrentSpend(..)
+            | Condition::AssertConcurrentPuzzle(..)
+            | Condition::AssertMyCoinId(..)
+            | Condition::AssertMyParentId(..)
+            | Condition::AssertMyPuzzleHash(..)
+            | Condition::AssertMyAmount(..)
+            | Condition::AssertMyBirthSeconds(..)
+            | Condition::AssertMyBirthHeight(..)
+            | Condition::AssertEphemeral(..)
+            | Condition::AssertSecondsRelative(..)
+            | Condition::AssertSecondsAbsolute(..)
+            | Condition::AssertHeightRelative(..)
+            | Condition::AssertHeightAbsolute(..)
+            | Condition::AssertBeforeSecondsRelative(..)
+            | Condition::AssertBeforeSecondsAbsolute(..)
+            | Condition::AssertBeforeHeightRelative(..)
+            | Condition::AssertBeforeHeightAbsolute(..)
+    )
+}
+
+fn explain_condition(allocator: &Allocator, condition: &Condition) -> String {
+    let raw = |ptr: NodePtr| {
+        node_to_bytes(allocator, ptr).map_or_else(|_| "<invalid>".to_string(), hex::encode)
+    };
+
+    match condition {
+        Condition::Remark(remark) => format!("REMARK {}", raw(remark.rest)),
+        Condition::AggSig(agg_sig) => {
+            let name = match agg_sig.kind {
+                AggSigKind::Parent => "AGG_SIG_PARENT",
+                AggSigKind::Puzzle => "AGG_SIG_PUZZLE",
+                AggSigKind::Amount => "AGG_SIG_AMOUNT",
+                AggSigKind::PuzzleAmount => "AGG_SIG_PUZZLE_AMOUNT",
+                AggSigKind::ParentAmount => "AGG_SIG_PARENT_AMOUNT",
+                AggSigKind::ParentPuzzle => "AGG_SIG_PARENT_PUZZLE",
+                AggSigKind::Unsafe => "AGG_SIG_UNSAFE",
+                AggSigKind::Me => "AGG_SIG_ME",
+            };
+            format!(
+                "{name} pk={} msg={}",
+                hex::encode(agg_sig.public_key.to_bytes()),
+                agg_sig.message
+            )
+        }
+        Condition::CreateCoin(create_coin) => {
+            let mut text = format!(
+                "CREATE_COIN ph={} amount={}",
+                create_coin.puzzle_hash, create_coin.amount
+            );
+            if !create_coin.memos.is_empty() {
+                let memos: Vec<String> =
+                    create_coin.memos.iter().map(ToString::to_string).collect();
+                // Writing to a `String` can't fail.
+                let _ = write!(text, " memos=[{}]", memos.join(", "));
+            }
+            text
+        }
+        Condition::ReserveFee(reserve_fee) => format!("RESERVE_FEE {}", reserve_fee.amount),
+        Condition::CreateCoinAnnouncement(announcement) => {
+            format!("CREATE_COIN_ANNOUNCEMENT {}", announcement.message)
+        }
+        Condition::AssertCoinAnnouncement(announcement) => {
+            format!("ASSERT_COIN_ANNOUNCEMENT {}", announcement.announcement_id)
+        }
+        Condition::CreatePuzzleAnnouncement(announcement) => {
+            format!("CREATE_PUZZLE_ANNOUNCEMENT {}", announcement.message)
+        }
+        Condition::AssertPuzzleAnnouncement(announcement) => {
+            format!(
+                "ASSERT_PUZZLE_ANNOUNCEMENT {}",
+                announcement.announcement_id
+            )
+        }
+        Condition::AssertConcurrentSpend(condition) => {
+            format!("ASSERT_CONCURRENT_SPEND {}", condition.coin_id)
+        }
+        Condition::AssertConcurrentPuzzle(condition) => {
+            format!("ASSERT_CONCURRENT_PUZZLE {}", condition.puzzle_hash)
+        }
+        Condition::AssertMyCoinId(condition) => format!("ASSERT_MY_COIN_ID {}", condition.coin_id),
+        Condition::AssertMyParentId(condition) => {
+            format!("ASSERT_MY_PARENT_ID {}", condition.parent_id)
+        }
+        Condition::AssertMyPuzzleHash(condition) => {
+            format!("ASSERT_MY_PUZZLEHASH {}", condition.puzzle_hash)
+        }
+        Condition::AssertMyAmount(condition) => format!("ASSERT_MY_AMOUNT {}", condition.amount),
+        Condition::AssertMyBirthSeconds(condition) => {
+            format!("ASSERT_MY_BIRTH_SECONDS {}", condition.seconds)
+        }
+        Condition::AssertMyBirthHeight(condition) => {
+            format!("ASSERT_MY_BIRTH_HEIGHT {}", condition.height)
+        }
+        Condition::AssertEphemeral(_) => "ASSERT_EPHEMERAL".to_string(),
+        Condition::AssertSecondsRelative(condition) => {
+            format!("ASSERT_SECONDS_RELATIVE {}", condition.seconds)
+        }
+        Condition::AssertSecondsAbsolute(condition) => {
+            format!("ASSERT_SECONDS_ABSOLUTE {}", condition.seconds)
+        }
+        Condition::AssertHeightRelative(condition) => {
+            format!("ASSERT_HEIGHT_RELATIVE {}", condition.height)
+        }
+        Condition::AssertHeightAbsolute(condition) => {
+            format!("ASSERT_HEIGHT_ABSOLUTE {}", condition.height)
+        }
+        Condition::AssertBeforeSecondsRelative(condition) => {
+            format!("ASSERT_BEFORE_SECONDS_RELATIVE {}", condition.seconds)
+        }
+        Condition::AssertBeforeSecondsAbsolute(condition) => {
+            format!("ASSERT_BEFORE_SECONDS_ABSOLUTE {}", condition.seconds)
+        }
+        Condition::AssertBeforeHeightRelative(condition) => {
+            format!("ASSERT_BEFORE_HEIGHT_RELATIVE {}", condition.height)
+        }
+        Condition::AssertBeforeHeightAbsolute(condition) => {
+            format!("ASSERT_BEFORE_HEIGHT_ABSOLUTE {}", condition.height)
+        }
+        Condition::Softfork(softfork) => {
+            format!("SOFTFORK cost={} {}", softfork.cost, raw(softfork.rest))
+        }
+        Condition::Other(ptr) => {
+            if let Ok((opcode, args)) = <(u64, NodePtr)>::from_clvm(allocator, *ptr) {
+                format!("OTHER opcode={opcode} args={}", raw(args))
+            } else {
+                format!("OTHER {}", raw(*ptr))
+            }
+        }
+    }
 }
 
 impl AsRef<[Condition]> for Conditions {
@@ -182,6 +531,15 @@ impl IntoIterator for Conditions {
     }
 }
 
+impl<'a> IntoIterator for &'a Conditions {
+    type Item = &'a Condition;
+    type IntoIter = std::slice::Iter<'a, Condition>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
 impl ToClvm<NodePtr> for Conditions {
     fn to_clvm(
         &self,
@@ -193,7 +551,11 @@ impl ToClvm<NodePtr> for Conditions {
 
 #[cfg(test)]
 mod tests {
-    use chia_sdk_test::{secret_key, test_transaction, Simulator};
+    use chia_bls::{sign, DerivableKey};
+    use chia_protocol::{Coin, CoinSpend, SpendBundle};
+    use chia_sdk_signer::RequiredSignature;
+    use chia_sdk_test::{secret_key, test_transaction, test_transaction_raw, Simulator};
+    use chia_sdk_types::conditions::{parse_conditions, remark_payloads};
 
     use super::*;
 
@@ -221,4 +583,416 @@ mod tests {
 
         Ok(())
     }
+
+    #[tokio::test]
+    async fn test_remark() -> anyhow::Result<()> {
+        let sim = Simulator::new().await?;
+        let peer = sim.connect().await?;
+        let ctx = &mut SpendContext::new();
+
+        let sk = secret_key()?;
+        let pk = sk.public_key();
+
+        let puzzle_hash = StandardArgs::curry_tree_hash(pk).into();
+        let coin = sim.mint_coin(puzzle_hash, 1).await;
+
+        let payload = Bytes::new(vec![0, 1, 2, 0xff, 0x80, 0]);
+        let conditions = Conditions::new()
+            .remark(ctx, payload.clone())?
+            .remark(ctx, (42, "tag".to_string()))?;
+        ctx.spend_p2_coin(coin, pk, conditions)?;
+
+        test_transaction(
+            &peer,
+            ctx.take_spends(),
+            &[sk],
+            sim.config().genesis_challenge,
+        )
+        .await;
+
+        let height = sim
+            .coin_state(coin.coin_id())
+            .await
+            .and_then(|coin_state| coin_state.spent_height)
+            .expect("expected spent coin");
+        let response = peer
+            .request_puzzle_and_solution(coin.coin_id(), height)
+            .await?;
+        let coin_spend = CoinSpend::new(coin, response.puzzle, response.solution);
+
+        let payloads = remark_payloads(ctx.allocator_mut(), &coin_spend)?;
+        assert_eq!(payloads.len(), 2);
+        assert_eq!(ctx.extract::<Bytes>(payloads[0])?, payload);
+        assert_eq!(
+            ctx.extract::<(u64, String)>(payloads[1])?,
+            (42, "tag".to_string())
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_create_coin_with_memos() -> anyhow::Result<()> {
+        let ctx = &mut SpendContext::new();
+
+        let puzzle_hash = Bytes32::new([1; 32]);
+        let memos: Vec<Bytes> = vec![puzzle_hash.into(), Bytes::new(b"invoice 42".to_vec())];
+
+        let conditions = Conditions::new().create_coin_with_memos(puzzle_hash, 100, memos.clone());
+        let ptr = ctx.alloc(&conditions)?;
+
+        let parsed = parse_conditions(ctx.allocator_mut(), ptr)?;
+        assert_eq!(parsed.len(), 1);
+
+        let Condition::CreateCoin(create_coin) = &parsed[0] else {
+            panic!("expected CREATE_COIN condition");
+        };
+        assert_eq!(create_coin.puzzle_hash, puzzle_hash);
+        assert_eq!(create_coin.amount, 100);
+        assert_eq!(create_coin.memos(), memos.as_slice());
+        assert_eq!(create_coin.hint(), Some(puzzle_hash));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_typed_accessors() {
+        let puzzle_hash = Bytes32::new([1; 32]);
+
+        let conditions = Conditions::new()
+            .create_coin(puzzle_hash, 1)
+            .create_coin_announcement(Bytes::new(vec![1]))
+            .reserve_fee(1)
+            .create_hinted_coin(puzzle_hash, 2, puzzle_hash)
+            .create_puzzle_announcement(Bytes::new(vec![2]))
+            .create_coin_announcement(Bytes::new(vec![3]));
+
+        assert_eq!(
+            conditions.created_coins(),
+            vec![
+                CreateCoin::new(puzzle_hash, 1),
+                CreateCoin::with_hint(puzzle_hash, 2, puzzle_hash)
+            ]
+        );
+        assert_eq!(
+            conditions.coin_announcements(),
+            vec![Bytes::new(vec![1]), Bytes::new(vec![3])]
+        );
+        assert_eq!(conditions.puzzle_announcements(), vec![Bytes::new(vec![2])]);
+
+        assert_eq!(conditions.iter().count(), 6);
+        assert!((&conditions)
+            .into_iter()
+            .any(|condition| matches!(condition, Condition::ReserveFee(fee) if fee.amount == 1)));
+    }
+
+    #[test]
+    fn test_sort_agg_sigs() -> anyhow::Result<()> {
+        let sk = secret_key()?;
+        let first_key = sk.public_key();
+        let second_key = sk.derive_unhardened(0).public_key();
+
+        let first = Conditions::new()
+            .condition(Condition::AggSig(AggSig::new(
+                AggSigKind::Me,
+                second_key,
+                Bytes::new(vec![1]),
+            )))
+            .reserve_fee(1);
+        let second = Conditions::new()
+            .condition(Condition::AggSig(AggSig::new(
+                AggSigKind::Me,
+                first_key,
+                Bytes::new(vec![2]),
+            )))
+            .condition(Condition::AggSig(AggSig::new(
+                AggSigKind::Me,
+                first_key,
+                Bytes::new(vec![1]),
+            )));
+
+        let agg_sigs = |conditions: Conditions| -> Vec<AggSig> {
+            conditions
+                .into_iter()
+                .filter_map(|condition| match condition {
+                    Condition::AggSig(agg_sig) => Some(agg_sig),
+                    _ => None,
+                })
+                .collect()
+        };
+
+        let merged = first.clone().extend(second.clone()).sort_agg_sigs();
+        let reversed = second.extend(first).sort_agg_sigs();
+
+        // Other conditions stay where they were.
+        assert!(matches!(merged.as_ref()[1], Condition::ReserveFee(_)));
+
+        let coin = Coin::new(Bytes32::default(), Bytes32::default(), 1);
+        let messages = |agg_sigs: Vec<AggSig>| -> Vec<Vec<u8>> {
+            agg_sigs
+                .into_iter()
+                .map(|agg_sig| {
+                    RequiredSignature::from_condition(&coin, agg_sig, Bytes32::default())
+                        .final_message()
+                })
+                .collect()
+        };
+
+        let merged = agg_sigs(merged);
+        let reversed = agg_sigs(reversed);
+        assert_eq!(messages(merged.clone()), messages(reversed));
+
+        let keys: Vec<PublicKey> = merged.iter().map(|agg_sig| agg_sig.public_key).collect();
+        if first_key.to_bytes() < second_key.to_bytes() {
+            assert_eq!(keys, [first_key, first_key, second_key]);
+            assert_eq!(merged[0].message, Bytes::new(vec![1]));
+        } else {
+            assert_eq!(keys, [second_key, first_key, first_key]);
+            assert_eq!(merged[1].message, Bytes::new(vec![1]));
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_explain() -> anyhow::Result<()> {
+        let ctx = &mut SpendContext::new();
+
+        let other = ctx.alloc(&(99, (1, ())))?;
+
+        let conditions = Conditions::new()
+            .create_coin(Bytes32::new([1; 32]), 42)
+            .assert_seconds_absolute(1_700_000_000)
+            .assert_ephemeral()
+            .condition(Condition::Other(other));
+
+        assert_eq!(
+            conditions.explain(ctx.allocator()),
+            format!(
+                "CREATE_COIN ph={} amount=42\nASSERT_SECONDS_ABSOLUTE 1700000000\nASSERT_EPHEMERAL\nOTHER opcode=99 args=ff0180",
+                Bytes32::new([1; 32])
+            )
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_assert_concurrent() -> anyhow::Result<()> {
+        let sim = Simulator::new().await?;
+        let peer = sim.connect().await?;
+        let ctx = &mut SpendContext::new();
+
+        let sk = secret_key()?;
+        let pk = sk.public_key();
+        let puzzle_hash = StandardArgs::curry_tree_hash(pk).into();
+
+        let other_secret_key = sk.derive_unhardened(0);
+        let other_key = other_secret_key.public_key();
+        let other_puzzle_hash = StandardArgs::curry_tree_hash(other_key).into();
+
+        let coin = sim.mint_coin(puzzle_hash, 1).await;
+        let other_coin = sim.mint_coin(other_puzzle_hash, 1).await;
+
+        let conditions = Conditions::new()
+            .assert_concurrent_spend(other_coin.coin_id())
+            .assert_concurrent_puzzle(other_puzzle_hash);
+
+        // The bundle is rejected if the other coin isn't spent alongside it.
+        ctx.spend_p2_coin(coin, pk, conditions.clone())?;
+
+        let ack = test_transaction_raw(
+            &peer,
+            ctx.take_spends(),
+            &[sk.clone()],
+            sim.config().genesis_challenge,
+        )
+        .await?;
+        assert_eq!(ack.status, 3);
+
+        ctx.spend_p2_coin(coin, pk, conditions)?;
+        ctx.spend_p2_coin(other_coin, other_key, Conditions::new())?;
+
+        test_transaction(
+            &peer,
+            ctx.take_spends(),
+            &[sk, other_secret_key],
+            sim.config().genesis_challenge,
+        )
+        .await;
+
+        let coin_state = sim.coin_state(coin.coin_id()).await.expect("expected coin");
+        assert!(coin_state.spent_height.is_some());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_assert_ephemeral() -> anyhow::Result<()> {
+        let sim = Simulator::new().await?;
+        let peer = sim.connect().await?;
+        let ctx = &mut SpendContext::new();
+
+        let sk = secret_key()?;
+        let pk = sk.public_key();
+        let puzzle_hash = StandardArgs::curry_tree_hash(pk).into();
+
+        let coin = sim.mint_coin(puzzle_hash, 1).await;
+
+        // A coin that already exists on chain isn't ephemeral.
+        ctx.spend_p2_coin(coin, pk, Conditions::new().assert_ephemeral())?;
+
+        let ack = test_transaction_raw(
+            &peer,
+            ctx.take_spends(),
+            &[sk.clone()],
+            sim.config().genesis_challenge,
+        )
+        .await?;
+        assert_eq!(ack.status, 3);
+
+        // A coin created and spent in the same bundle is.
+        ctx.spend_p2_coin(coin, pk, Conditions::new().create_coin(puzzle_hash, 1))?;
+        let child = Coin::new(coin.coin_id(), puzzle_hash, 1);
+        ctx.spend_p2_coin(child, pk, Conditions::new().assert_ephemeral())?;
+
+        test_transaction(
+            &peer,
+            ctx.take_spends(),
+            &[sk],
+            sim.config().genesis_challenge,
+        )
+        .await;
+
+        let coin_state = sim
+            .coin_state(child.coin_id())
+            .await
+            .expect("expected child coin");
+        assert!(coin_state.spent_height.is_some());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_assert_my_coin_info() -> anyhow::Result<()> {
+        let sim = Simulator::new().await?;
+        let peer = sim.connect().await?;
+        let ctx = &mut SpendContext::new();
+
+        let sk = secret_key()?;
+        let pk = sk.public_key();
+        let puzzle_hash = StandardArgs::curry_tree_hash(pk).into();
+
+        let coin = sim.mint_coin(puzzle_hash, 1).await;
+
+        let mismatched = [
+            Conditions::new().assert_my_coin_id(Bytes32::default()),
+            Conditions::new().assert_my_parent_id(Bytes32::default()),
+            Conditions::new().assert_my_puzzle_hash(Bytes32::default()),
+            Conditions::new().assert_my_amount(2),
+        ];
+
+        for conditions in mismatched {
+            ctx.spend_p2_coin(coin, pk, conditions)?;
+
+            let ack = test_transaction_raw(
+                &peer,
+                ctx.take_spends(),
+                &[sk.clone()],
+                sim.config().genesis_challenge,
+            )
+            .await?;
+            assert_eq!(ack.status, 3);
+        }
+
+        let conditions = Conditions::new()
+            .assert_my_coin_id(coin.coin_id())
+            .assert_my_parent_id(coin.parent_coin_info)
+            .assert_my_puzzle_hash(coin.puzzle_hash)
+            .assert_my_amount(coin.amount);
+        ctx.spend_p2_coin(coin, pk, conditions)?;
+
+        test_transaction(
+            &peer,
+            ctx.take_spends(),
+            &[sk],
+            sim.config().genesis_challenge,
+        )
+        .await;
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_delegated_puzzle_hash() -> anyhow::Result<()> {
+        let sim = Simulator::new().await?;
+        let peer = sim.connect().await?;
+        let ctx = &mut SpendContext::new();
+
+        let sk = secret_key()?;
+        let pk = sk.public_key();
+        let puzzle_hash = StandardArgs::curry_tree_hash(pk).into();
+
+        let coin = sim.mint_coin(puzzle_hash, 1).await;
+
+        let conditions = Conditions::new().create_coin(puzzle_hash, 1);
+        let delegated_puzzle_hash = conditions.delegated_puzzle_hash(ctx)?;
+        ctx.spend_p2_coin(coin, pk, conditions)?;
+
+        // Sign the message without looking at the coin spends.
+        let mut message = delegated_puzzle_hash.to_vec();
+        message.extend_from_slice(&coin.coin_id());
+        message.extend_from_slice(&sim.config().genesis_challenge);
+        let signature = sign(&sk, message);
+
+        let spend_bundle = SpendBundle::new(ctx.take_spends(), signature);
+        let ack = peer.send_transaction(spend_bundle).await?;
+        assert_eq!(ack.status, 1);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_deduped() {
+        let puzzle_hash = Bytes32::new([1; 32]);
+        let coin_id = Bytes32::new([2; 32]);
+
+        let nft_conditions = Conditions::new()
+            .create_coin(puzzle_hash, 1)
+            .assert_puzzle_announcement(puzzle_hash, [1, 2, 3])
+            .reserve_fee(100);
+        let did_conditions = Conditions::new()
+            .create_coin(puzzle_hash, 1)
+            .assert_puzzle_announcement(puzzle_hash, [1, 2, 3])
+            .assert_concurrent_spend(coin_id)
+            .reserve_fee(100);
+
+        let merged = nft_conditions.merge(did_conditions);
+
+        assert_eq!(
+            merged,
+            Conditions::new()
+                .create_coin(puzzle_hash, 1)
+                .assert_puzzle_announcement(puzzle_hash, [1, 2, 3])
+                .reserve_fee(100)
+                .create_coin(puzzle_hash, 1)
+                .assert_concurrent_spend(coin_id)
+                .reserve_fee(100)
+        );
+
+        let pk = PublicKey::default();
+        let conditions = Conditions::new()
+            .condition(Condition::AggSig(AggSig::new(
+                AggSigKind::Me,
+                pk,
+                vec![1].into(),
+            )))
+            .condition(Condition::AggSig(AggSig::new(
+                AggSigKind::Me,
+                pk,
+                vec![1].into(),
+            )))
+            .deduped();
+        assert_eq!(conditions.as_ref().len(), 1);
+    }
 }
@@ -1,3 +1,6 @@
+use chia_consensus::gen::validation_error::ValidationErr;
+use chia_protocol::Bytes48;
+use chia_sdk_signer::SignerError;
 use chia_sdk_types::conditions::ConditionError;
 use clvm_traits::{FromClvmError, ToClvmError};
 use clvmr::reduction::EvalErr;
@@ -22,6 +25,18 @@ pub enum DriverError {
     #[error("clvm eval error: {0}")]
     Eval(#[from] EvalErr),
 
+    #[error("signer error: {0}")]
+    Signer(#[from] SignerError),
+
+    #[error("missing secret key for required signature by public key {0}")]
+    MissingKey(Bytes48),
+
+    #[error("validation error: {0:?}")]
+    Validation(ValidationErr),
+
+    #[error("cost {cost} exceeds the maximum of {max}")]
+    CostExceeded { cost: u64, max: u64 },
+
     #[error("custom driver error: {0}")]
     Custom(String),
 
@@ -46,8 +61,43 @@ pub enum DriverError {
     #[error("mismatched singleton output (maybe no spend revealed the new singleton state)")]
     MismatchedOutput,
 
+    #[error("coin spend is not a child of the previous singleton spend")]
+    BrokenLineage,
+
+    #[error("singleton spend must create exactly one odd child unless melted, found {0}")]
+    InvalidSingletonOutput(usize),
+
+    #[error("all CATs in a spend must have the same asset id")]
+    MismatchedAssetId,
+
+    #[error("TAIL program does not match the asset id")]
+    MismatchedTail,
+
+    #[error("cannot melt more than the coin amount")]
+    MeltAmountTooLarge,
+
+    #[error("launcher index {index} is out of range for a total of {total}")]
+    InvalidLauncherIndex { index: usize, total: usize },
+
     #[error(
         "missing puzzle (required to build innermost puzzle - usually fixed by using .with_puzzle)"
     )]
     MissingPuzzle,
+
+    #[error("{context}: {source}")]
+    Context {
+        context: String,
+        source: Box<DriverError>,
+    },
+}
+
+impl DriverError {
+    /// Wraps the error with a description of what was being done when it occurred.
+    #[must_use]
+    pub fn context(self, context: impl Into<String>) -> Self {
+        Self::Context {
+            context: context.into(),
+            source: Box::new(self),
+        }
+    }
 }
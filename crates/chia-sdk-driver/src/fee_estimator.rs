@@ -0,0 +1,121 @@
+/// Recommends fees using a simple fee per cost model, which scales with how full the mempool is.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FeeEstimator {
+    min_fee_per_cost: f64,
+    max_fee_per_cost: f64,
+}
+
+impl Default for FeeEstimator {
+    /// An empty mempool accepts spend bundles without a fee, and 5 mojos per cost is enough to get into a full one.
+    fn default() -> Self {
+        Self::new(0.0, 5.0)
+    }
+}
+
+impl FeeEstimator {
+    /// Creates an estimator with the fee per cost recommended when the mempool is empty and when it's full.
+    pub fn new(min_fee_per_cost: f64, max_fee_per_cost: f64) -> Self {
+        Self {
+            min_fee_per_cost,
+            max_fee_per_cost,
+        }
+    }
+
+    pub fn min_fee_per_cost(&self) -> f64 {
+        self.min_fee_per_cost
+    }
+
+    pub fn max_fee_per_cost(&self) -> f64 {
+        self.max_fee_per_cost
+    }
+
+    /// Updates the model with the fees and costs of the items currently in the mempool.
+    /// Items without a cost are ignored, and the model is unchanged if there are none left.
+    pub fn observe_mempool(&mut self, items: &[(u64, u64)]) {
+        let mut rates = items
+            .iter()
+            .filter(|(_, cost)| *cost > 0)
+            .map(|(fee, cost)| fee_per_cost(*fee, *cost));
+
+        let Some(first) = rates.next() else {
+            return;
+        };
+
+        let (min, max) = rates.fold((first, first), |(min, max), rate| {
+            (min.min(rate), max.max(rate))
+        });
+
+        self.min_fee_per_cost = min;
+        self.max_fee_per_cost = max;
+    }
+
+    /// Recommends a fee for a spend bundle with the given cost.
+    /// The mempool pressure ranges from 0 (empty) to 1 (full), and is clamped to that range.
+    #[allow(
+        clippy::cast_precision_loss,
+        clippy::cast_possible_truncation,
+        clippy::cast_sign_loss
+    )]
+    pub fn recommend(&self, cost: u64, mempool_pressure: f64) -> u64 {
+        let pressure = if mempool_pressure.is_nan() {
+            0.0
+        } else {
+            mempool_pressure.clamp(0.0, 1.0)
+        };
+
+        let rate =
+            self.min_fee_per_cost + (self.max_fee_per_cost - self.min_fee_per_cost) * pressure;
+
+        // Float to integer casts saturate, so a huge fee can't wrap around.
+        (cost as f64 * rate.max(0.0)).ceil() as u64
+    }
+}
+
+#[allow(clippy::cast_precision_loss)]
+fn fee_per_cost(fee: u64, cost: u64) -> f64 {
+    fee as f64 / cost as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_recommend_zero_cost() {
+        let estimator = FeeEstimator::default();
+
+        for pressure in [0.0, 0.5, 1.0, 100.0] {
+            assert_eq!(estimator.recommend(0, pressure), 0);
+        }
+    }
+
+    #[test]
+    fn test_recommend_pressure() {
+        let estimator = FeeEstimator::new(1.0, 5.0);
+        let cost = 10_000_000;
+
+        assert_eq!(estimator.recommend(cost, 0.0), cost);
+        assert_eq!(estimator.recommend(cost, 0.5), cost * 3);
+        assert_eq!(estimator.recommend(cost, 1.0), cost * 5);
+
+        // Pressure outside of the range is clamped.
+        assert_eq!(estimator.recommend(cost, 10.0), cost * 5);
+        assert_eq!(estimator.recommend(cost, -1.0), cost);
+        assert_eq!(estimator.recommend(cost, f64::NAN), cost);
+
+        // The fee saturates rather than overflowing.
+        assert_eq!(estimator.recommend(u64::MAX, 1.0), u64::MAX);
+    }
+
+    #[test]
+    fn test_observe_mempool() {
+        let mut estimator = FeeEstimator::default();
+
+        estimator.observe_mempool(&[(0, 0)]);
+        assert_eq!(estimator, FeeEstimator::default());
+
+        estimator.observe_mempool(&[(200, 100), (50, 100), (700, 100), (1, 0)]);
+        assert_eq!(estimator, FeeEstimator::new(0.5, 7.0));
+        assert_eq!(estimator.recommend(100, 1.0), 700);
+    }
+}
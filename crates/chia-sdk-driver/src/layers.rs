@@ -2,6 +2,7 @@ mod cat_layer;
 mod did_layer;
 mod nft_ownership_layer;
 mod nft_state_layer;
+mod p2_singleton_layer;
 mod singleton_layer;
 mod transparent_layer;
 
@@ -9,5 +10,6 @@ pub use cat_layer::*;
 pub use did_layer::*;
 pub use nft_ownership_layer::*;
 pub use nft_state_layer::*;
+pub use p2_singleton_layer::*;
 pub use singleton_layer::*;
 pub use transparent_layer::*;
@@ -1,6 +1,6 @@
 use chia_protocol::{Bytes32, Coin, CoinSpend, Program};
 use chia_puzzles::cat::{CatArgs, CatSolution, CAT_PUZZLE_HASH};
-use clvm_traits::{FromClvm, FromNodePtr, ToNodePtr};
+use clvm_traits::{FromClvm, FromNodePtr};
 use clvm_utils::{CurriedProgram, ToTreeHash, TreeHash};
 use clvmr::{Allocator, NodePtr};
 
@@ -84,16 +84,18 @@ where
     }
 
     fn construct_puzzle(&self, ctx: &mut SpendContext) -> Result<NodePtr, DriverError> {
-        CurriedProgram {
-            program: ctx.cat_puzzle().map_err(DriverError::Spend)?,
+        let program = ctx.cat_puzzle().map_err(DriverError::Spend)?;
+        let inner_puzzle = self.inner_puzzle.construct_puzzle(ctx)?;
+
+        ctx.alloc(&CurriedProgram {
+            program,
             args: CatArgs {
                 mod_hash: CAT_PUZZLE_HASH.into(),
                 asset_id: self.asset_id,
-                inner_puzzle: self.inner_puzzle.construct_puzzle(ctx)?,
+                inner_puzzle,
             },
-        }
-        .to_node_ptr(ctx.allocator_mut())
-        .map_err(DriverError::ToClvm)
+        })
+        .map_err(DriverError::Spend)
     }
 
     fn construct_solution(
@@ -101,19 +103,20 @@ where
         ctx: &mut SpendContext,
         solution: Self::Solution,
     ) -> Result<NodePtr, DriverError> {
-        CatSolution {
-            inner_puzzle_solution: self
-                .inner_puzzle
-                .construct_solution(ctx, solution.inner_puzzle_solution)?,
+        let inner_puzzle_solution = self
+            .inner_puzzle
+            .construct_solution(ctx, solution.inner_puzzle_solution)?;
+
+        ctx.alloc(&CatSolution {
+            inner_puzzle_solution,
             lineage_proof: solution.lineage_proof,
             prev_coin_id: solution.prev_coin_id,
             this_coin_info: solution.this_coin_info,
             next_coin_proof: solution.next_coin_proof,
             prev_subtotal: solution.prev_subtotal,
             extra_delta: solution.extra_delta,
-        }
-        .to_node_ptr(ctx.allocator_mut())
-        .map_err(DriverError::ToClvm)
+        })
+        .map_err(DriverError::Spend)
     }
 }
 
@@ -3,7 +3,6 @@ use chia_puzzles::{
     did::{DidArgs, DidSolution, DID_INNER_PUZZLE_HASH},
     singleton::SingletonStruct,
 };
-use clvm_traits::ToNodePtr;
 use clvm_traits::{FromClvm, ToClvm};
 use clvm_utils::{CurriedProgram, ToTreeHash, TreeHash};
 use clvmr::{Allocator, NodePtr};
@@ -96,23 +95,21 @@ where
     }
 
     fn construct_puzzle(&self, ctx: &mut SpendContext) -> Result<NodePtr, DriverError> {
-        let metadata_ptr = self
-            .metadata
-            .to_node_ptr(ctx.allocator_mut())
-            .map_err(DriverError::ToClvm)?;
+        let metadata_ptr = ctx.alloc(&self.metadata).map_err(DriverError::Spend)?;
+        let program = ctx.did_inner_puzzle().map_err(DriverError::Spend)?;
+        let inner_puzzle = self.inner_puzzle.construct_puzzle(ctx)?;
 
-        CurriedProgram {
-            program: ctx.did_inner_puzzle().map_err(DriverError::Spend)?,
+        ctx.alloc(&CurriedProgram {
+            program,
             args: DidArgs {
                 recovery_did_list_hash: self.recovery_did_list_hash,
                 num_verifications_required: self.num_verifications_required,
                 singleton_struct: SingletonStruct::new(self.launcher_id),
                 metadata: metadata_ptr,
-                inner_puzzle: self.inner_puzzle.construct_puzzle(ctx)?,
+                inner_puzzle,
             },
-        }
-        .to_node_ptr(ctx.allocator_mut())
-        .map_err(DriverError::ToClvm)
+        })
+        .map_err(DriverError::Spend)
     }
 
     fn construct_solution(
@@ -120,12 +117,12 @@ where
         ctx: &mut SpendContext,
         solution: Self::Solution,
     ) -> Result<NodePtr, DriverError> {
-        DidSolution::InnerSpend(
-            self.inner_puzzle
-                .construct_solution(ctx, solution.inner_solution)?,
-        )
-        .to_node_ptr(ctx.allocator_mut())
-        .map_err(DriverError::ToClvm)
+        let inner_solution = self
+            .inner_puzzle
+            .construct_solution(ctx, solution.inner_solution)?;
+
+        ctx.alloc(&DidSolution::InnerSpend(inner_solution))
+            .map_err(DriverError::Spend)
     }
 }
 
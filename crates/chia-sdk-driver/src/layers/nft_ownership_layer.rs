@@ -6,8 +6,8 @@ use chia_puzzles::{
     },
     singleton::SingletonStruct,
 };
-use chia_sdk_types::conditions::{run_puzzle, NewNftOwner};
-use clvm_traits::{FromClvm, ToNodePtr};
+use chia_sdk_types::conditions::{run_puzzle, NewNftOwner, NftTradePrice};
+use clvm_traits::FromClvm;
 use clvm_utils::{CurriedProgram, ToTreeHash, TreeHash};
 use clvmr::{Allocator, NodePtr};
 
@@ -135,28 +135,31 @@ where
     }
 
     fn construct_puzzle(&self, ctx: &mut SpendContext) -> Result<NodePtr, DriverError> {
-        let transfer_program = CurriedProgram {
-            program: ctx.nft_royalty_transfer().map_err(DriverError::Spend)?,
-            args: NftRoyaltyTransferPuzzleArgs {
-                singleton_struct: SingletonStruct::new(self.launcher_id),
-                royalty_puzzle_hash: self.royalty_puzzle_hash,
-                trade_price_percentage: self.royalty_percentage,
-            },
-        }
-        .to_node_ptr(ctx.allocator_mut())
-        .map_err(DriverError::ToClvm)?;
-
-        CurriedProgram {
-            program: ctx.nft_ownership_layer().map_err(DriverError::Spend)?,
+        let transfer_program = ctx.nft_royalty_transfer().map_err(DriverError::Spend)?;
+        let transfer_program = ctx
+            .alloc(&CurriedProgram {
+                program: transfer_program,
+                args: NftRoyaltyTransferPuzzleArgs {
+                    singleton_struct: SingletonStruct::new(self.launcher_id),
+                    royalty_puzzle_hash: self.royalty_puzzle_hash,
+                    trade_price_percentage: self.royalty_percentage,
+                },
+            })
+            .map_err(DriverError::Spend)?;
+
+        let program = ctx.nft_ownership_layer().map_err(DriverError::Spend)?;
+        let inner_puzzle = self.inner_puzzle.construct_puzzle(ctx)?;
+
+        ctx.alloc(&CurriedProgram {
+            program,
             args: NftOwnershipLayerArgs {
                 mod_hash: NFT_OWNERSHIP_LAYER_PUZZLE_HASH.into(),
                 current_owner: self.current_owner,
                 transfer_program,
-                inner_puzzle: self.inner_puzzle.construct_puzzle(ctx)?,
+                inner_puzzle,
             },
-        }
-        .to_node_ptr(ctx.allocator_mut())
-        .map_err(DriverError::ToClvm)
+        })
+        .map_err(DriverError::Spend)
     }
 
     fn construct_solution(
@@ -164,13 +167,12 @@ where
         ctx: &mut SpendContext,
         solution: Self::Solution,
     ) -> Result<NodePtr, DriverError> {
-        NftOwnershipLayerSolution {
-            inner_solution: self
-                .inner_puzzle
-                .construct_solution(ctx, solution.inner_solution)?,
-        }
-        .to_node_ptr(ctx.allocator_mut())
-        .map_err(DriverError::ToClvm)
+        let inner_solution = self
+            .inner_puzzle
+            .construct_solution(ctx, solution.inner_solution)?;
+
+        ctx.alloc(&NftOwnershipLayerSolution { inner_solution })
+            .map_err(DriverError::Spend)
     }
 }
 
@@ -197,6 +199,49 @@ impl<IP> NftOwnershipLayer<IP> {
         inner_layer_puzzle: NodePtr,
         inner_layer_solution: NodePtr,
     ) -> Result<Option<Option<Bytes32>>, DriverError> {
+        Ok(Self::new_nft_owner_from_conditions(
+            allocator,
+            inner_layer_puzzle,
+            inner_layer_solution,
+        )?
+        .map(|condition| condition.did_id))
+    }
+
+    /// Parses the trade prices from the new owner condition of a spend of this layer.
+    /// Unlike [`PuzzleLayer::from_parent_spend`], this works with any transfer program.
+    /// Returns [`None`] if the puzzle isn't this layer, or if there's no new owner condition.
+    pub fn trade_prices_from_spend(
+        allocator: &mut Allocator,
+        layer_puzzle: NodePtr,
+        layer_solution: NodePtr,
+    ) -> Result<Option<Vec<NftTradePrice>>, DriverError> {
+        let Some(puzzle) = Puzzle::parse(allocator, layer_puzzle).as_curried() else {
+            return Ok(None);
+        };
+
+        if puzzle.mod_hash != NFT_OWNERSHIP_LAYER_PUZZLE_HASH {
+            return Ok(None);
+        }
+
+        let args = NftOwnershipLayerArgs::<NodePtr, NodePtr>::from_clvm(allocator, puzzle.args)
+            .map_err(DriverError::FromClvm)?;
+        let solution = NftOwnershipLayerSolution::<NodePtr>::from_clvm(allocator, layer_solution)
+            .map_err(DriverError::FromClvm)?;
+
+        Ok(Self::new_nft_owner_from_conditions(
+            allocator,
+            args.inner_puzzle,
+            solution.inner_solution,
+        )?
+        .map(|condition| condition.trade_prices))
+    }
+
+    /// Finds the new owner condition output by the inner puzzle, including its trade prices.
+    pub fn new_nft_owner_from_conditions(
+        allocator: &mut Allocator,
+        inner_layer_puzzle: NodePtr,
+        inner_layer_solution: NodePtr,
+    ) -> Result<Option<NewNftOwner>, DriverError> {
         let output = run_puzzle(allocator, inner_layer_puzzle, inner_layer_solution)
             .map_err(DriverError::Eval)?;
 
@@ -207,7 +252,7 @@ impl<IP> NftOwnershipLayer<IP> {
             let condition = NewNftOwner::from_clvm(allocator, condition);
 
             if let Ok(condition) = condition {
-                return Ok(Some(condition.did_id));
+                return Ok(Some(condition));
             }
         }
 
@@ -1,7 +1,7 @@
 use chia_protocol::Bytes32;
 use chia_puzzles::nft::{NftStateLayerArgs, NftStateLayerSolution, NFT_STATE_LAYER_PUZZLE_HASH};
 use chia_sdk_types::conditions::run_puzzle;
-use clvm_traits::{apply_constants, FromClvm, ToClvm, ToNodePtr};
+use clvm_traits::{apply_constants, FromClvm, ToClvm};
 use clvm_utils::{CurriedProgram, ToTreeHash, TreeHash};
 use clvmr::{Allocator, NodePtr};
 
@@ -103,22 +103,20 @@ where
     }
 
     fn construct_puzzle(&self, ctx: &mut SpendContext) -> Result<NodePtr, DriverError> {
-        let metadata_ptr = self
-            .metadata
-            .to_node_ptr(ctx.allocator_mut())
-            .map_err(DriverError::ToClvm)?;
+        let metadata_ptr = ctx.alloc(&self.metadata).map_err(DriverError::Spend)?;
+        let program = ctx.nft_state_layer().map_err(DriverError::Spend)?;
+        let inner_puzzle = self.inner_puzzle.construct_puzzle(ctx)?;
 
-        CurriedProgram {
-            program: ctx.nft_state_layer().map_err(DriverError::Spend)?,
+        ctx.alloc(&CurriedProgram {
+            program,
             args: NftStateLayerArgs {
                 mod_hash: NFT_STATE_LAYER_PUZZLE_HASH.into(),
                 metadata: metadata_ptr,
                 metadata_updater_puzzle_hash: self.metadata_updater_puzzle_hash,
-                inner_puzzle: self.inner_puzzle.construct_puzzle(ctx)?,
+                inner_puzzle,
             },
-        }
-        .to_node_ptr(ctx.allocator_mut())
-        .map_err(DriverError::ToClvm)
+        })
+        .map_err(DriverError::Spend)
     }
 
     fn construct_solution(
@@ -126,13 +124,12 @@ where
         ctx: &mut SpendContext,
         solution: Self::Solution,
     ) -> Result<NodePtr, DriverError> {
-        NftStateLayerSolution {
-            inner_solution: self
-                .inner_puzzle
-                .construct_solution(ctx, solution.inner_solution)?,
-        }
-        .to_node_ptr(ctx.allocator_mut())
-        .map_err(DriverError::ToClvm)
+        let inner_solution = self
+            .inner_puzzle
+            .construct_solution(ctx, solution.inner_solution)?;
+
+        ctx.alloc(&NftStateLayerSolution { inner_solution })
+            .map_err(DriverError::Spend)
     }
 }
 
@@ -0,0 +1,236 @@
+use chia_protocol::{Bytes32, Coin, CoinSpend, Program};
+use chia_puzzles::singleton::{SINGLETON_LAUNCHER_PUZZLE_HASH, SINGLETON_TOP_LAYER_PUZZLE_HASH};
+use clvm_traits::{FromClvm, FromNodePtr, ToClvm};
+use clvm_utils::{CurriedProgram, ToTreeHash, TreeHash};
+use clvmr::{Allocator, NodePtr};
+use hex_literal::hex;
+
+use crate::{Conditions, DriverError, Puzzle, PuzzleLayer, SpendContext};
+
+/// The `p2_singleton` puzzle reveal, which locks a coin so that it can only be spent alongside a singleton.
+pub const P2_SINGLETON_PUZZLE: [u8; 403] = hex!(
+    "
+    ff02ffff01ff04ffff04ff18ffff04ffff0bffff02ff2effff04ff02ffff04ff
+    05ffff04ff2fffff04ffff02ff3effff04ff02ffff04ffff04ff05ffff04ff0b
+    ff178080ff80808080ff808080808080ff5f80ff808080ffff04ffff04ff2cff
+    ff01ff248080ffff04ffff04ff10ffff04ff5fff808080ff80808080ffff04ff
+    ff01ffffff463fff02ff3c04ffff01ff0102ffff02ffff03ff05ffff01ff02ff
+    16ffff04ff02ffff04ff0dffff04ffff0bff3affff0bff12ff3c80ffff0bff3a
+    ffff0bff3affff0bff12ff2a80ff0980ffff0bff3aff0bffff0bff12ff808080
+    8080ff8080808080ffff010b80ff0180ffff0bff3affff0bff12ff1480ffff0b
+    ff3affff0bff3affff0bff12ff2a80ff0580ffff0bff3affff02ff16ffff04ff
+    02ffff04ff07ffff04ffff0bff12ff1280ff8080808080ffff0bff12ff808080
+    8080ff02ffff03ffff07ff0580ffff01ff0bffff0102ffff02ff3effff04ff02
+    ffff04ff09ff80808080ffff02ff3effff04ff02ffff04ff0dff8080808080ff
+    ff01ff0bffff0101ff058080ff0180ff018080
+    "
+);
+
+/// The tree hash of [`P2_SINGLETON_PUZZLE`].
+pub const P2_SINGLETON_PUZZLE_HASH: TreeHash = TreeHash::new(hex!(
+    "40f828d8dd55603f4ff9fbf6b73271e904e69406982f4fbefae2c8dcceaf9834"
+));
+
+/// The curried arguments of the `p2_singleton` puzzle.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ToClvm, FromClvm)]
+#[clvm(curry)]
+pub struct P2SingletonArgs {
+    pub singleton_mod_hash: Bytes32,
+    pub launcher_id: Bytes32,
+    pub launcher_puzzle_hash: Bytes32,
+}
+
+impl P2SingletonArgs {
+    pub fn new(launcher_id: Bytes32) -> Self {
+        Self {
+            singleton_mod_hash: SINGLETON_TOP_LAYER_PUZZLE_HASH.into(),
+            launcher_id,
+            launcher_puzzle_hash: SINGLETON_LAUNCHER_PUZZLE_HASH.into(),
+        }
+    }
+}
+
+/// The solution to the `p2_singleton` puzzle.
+/// The singleton's current inner puzzle hash is used to compute its full puzzle hash.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ToClvm, FromClvm)]
+#[clvm(list)]
+pub struct P2SingletonSolution {
+    pub singleton_inner_puzzle_hash: Bytes32,
+    pub my_id: Bytes32,
+}
+
+/// A coin that is owned by a singleton, and can only be spent in the same block as it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct P2SingletonLayer {
+    pub launcher_id: Bytes32,
+}
+
+impl P2SingletonLayer {
+    pub fn new(launcher_id: Bytes32) -> Self {
+        Self { launcher_id }
+    }
+
+    /// Spends the coin, given the current inner puzzle hash of the owning singleton.
+    /// Returns the conditions that the singleton must output in the same block to release the coin.
+    pub fn spend(
+        &self,
+        ctx: &mut SpendContext,
+        coin: Coin,
+        singleton_inner_puzzle_hash: Bytes32,
+    ) -> Result<Conditions, DriverError> {
+        let coin_id = coin.coin_id();
+
+        let puzzle_ptr = self.construct_puzzle(ctx)?;
+        let puzzle_reveal =
+            Program::from_node_ptr(ctx.allocator(), puzzle_ptr).map_err(DriverError::FromClvm)?;
+
+        let solution_ptr = self.construct_solution(
+            ctx,
+            P2SingletonSolution {
+                singleton_inner_puzzle_hash,
+                my_id: coin_id,
+            },
+        )?;
+        let solution =
+            Program::from_node_ptr(ctx.allocator(), solution_ptr).map_err(DriverError::FromClvm)?;
+
+        ctx.insert_coin_spend(CoinSpend::new(coin, puzzle_reveal, solution))
+            .map_err(DriverError::Spend)?;
+
+        Ok(Conditions::new()
+            .create_puzzle_announcement(coin_id.to_vec().into())
+            .assert_coin_announcement(coin_id, b"$"))
+    }
+}
+
+impl PuzzleLayer for P2SingletonLayer {
+    type Solution = P2SingletonSolution;
+
+    fn from_parent_spend(
+        _allocator: &mut Allocator,
+        _layer_puzzle: NodePtr,
+        _layer_solution: NodePtr,
+    ) -> Result<Option<Self>, DriverError> {
+        // The `p2_singleton` puzzle doesn't create any coins.
+        Ok(None)
+    }
+
+    fn from_puzzle(
+        allocator: &mut Allocator,
+        layer_puzzle: NodePtr,
+    ) -> Result<Option<Self>, DriverError> {
+        let puzzle = Puzzle::parse(allocator, layer_puzzle);
+
+        let Some(puzzle) = puzzle.as_curried() else {
+            return Ok(None);
+        };
+
+        if puzzle.mod_hash != P2_SINGLETON_PUZZLE_HASH {
+            return Ok(None);
+        }
+
+        let args =
+            P2SingletonArgs::from_clvm(allocator, puzzle.args).map_err(DriverError::FromClvm)?;
+
+        if args != P2SingletonArgs::new(args.launcher_id) {
+            return Err(DriverError::InvalidSingletonStruct);
+        }
+
+        Ok(Some(Self {
+            launcher_id: args.launcher_id,
+        }))
+    }
+
+    fn construct_puzzle(&self, ctx: &mut SpendContext) -> Result<NodePtr, DriverError> {
+        let program = ctx
+            .puzzle(P2_SINGLETON_PUZZLE_HASH, &P2_SINGLETON_PUZZLE)
+            .map_err(DriverError::Spend)?;
+
+        ctx.alloc(&CurriedProgram {
+            program,
+            args: P2SingletonArgs::new(self.launcher_id),
+        })
+        .map_err(DriverError::Spend)
+    }
+
+    fn construct_solution(
+        &self,
+        ctx: &mut SpendContext,
+        solution: Self::Solution,
+    ) -> Result<NodePtr, DriverError> {
+        ctx.alloc(&solution).map_err(DriverError::Spend)
+    }
+}
+
+impl ToTreeHash for P2SingletonLayer {
+    fn tree_hash(&self) -> TreeHash {
+        CurriedProgram {
+            program: P2_SINGLETON_PUZZLE_HASH,
+            args: P2SingletonArgs::new(self.launcher_id),
+        }
+        .tree_hash()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chia_puzzles::standard::StandardArgs;
+    use chia_sdk_test::{secret_key, test_transaction, Simulator};
+
+    use crate::Launcher;
+
+    use super::*;
+
+    #[test]
+    fn test_p2_singleton_puzzle_hash() -> anyhow::Result<()> {
+        let mut ctx = SpendContext::new();
+        let puzzle = ctx.puzzle(P2_SINGLETON_PUZZLE_HASH, &P2_SINGLETON_PUZZLE)?;
+        assert_eq!(ctx.tree_hash(puzzle), P2_SINGLETON_PUZZLE_HASH);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_p2_singleton() -> anyhow::Result<()> {
+        let sim = Simulator::new().await?;
+        let peer = sim.connect().await?;
+        let ctx = &mut SpendContext::new();
+
+        let sk = secret_key()?;
+        let pk = sk.public_key();
+
+        let puzzle_hash = StandardArgs::curry_tree_hash(pk).into();
+        let coin = sim.mint_coin(puzzle_hash, 1).await;
+
+        let (create_did, did, did_proof) =
+            Launcher::new(coin.coin_id(), 1).create_simple_did(ctx, pk)?;
+        ctx.spend_p2_coin(coin, pk, create_did)?;
+
+        let layer = P2SingletonLayer::new(did.launcher_id);
+        let p2_puzzle_hash = layer.tree_hash().into();
+        let p2_coin = sim.mint_coin(p2_puzzle_hash, 1).await;
+
+        let puzzle = layer.construct_puzzle(ctx)?;
+        let parsed = P2SingletonLayer::from_puzzle(ctx.allocator_mut(), puzzle)?;
+        assert_eq!(parsed, Some(layer));
+
+        // The released value is left over as a fee, since the DID can only recreate itself here.
+        let release = layer.spend(ctx, p2_coin, did.singleton_inner_puzzle_hash().into())?;
+        ctx.spend_standard_did(&did, did_proof, pk, release)?;
+
+        test_transaction(
+            &peer,
+            ctx.take_spends(),
+            &[sk],
+            sim.config().genesis_challenge,
+        )
+        .await;
+
+        let coin_state = sim
+            .coin_state(p2_coin.coin_id())
+            .await
+            .expect("expected p2 singleton coin");
+        assert!(coin_state.spent_height.is_some());
+
+        Ok(())
+    }
+}
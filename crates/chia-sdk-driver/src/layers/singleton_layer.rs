@@ -6,11 +6,12 @@ use chia_puzzles::{
     },
     LineageProof, Proof,
 };
+use chia_sdk_types::conditions::{singleton_output, singleton_spend_output, SingletonOutput};
 use clvm_traits::{FromClvm, FromNodePtr, ToClvm, ToNodePtr};
 use clvm_utils::{tree_hash, CurriedProgram, ToTreeHash, TreeHash};
 use clvmr::{Allocator, NodePtr};
 
-use crate::{DriverError, OuterPuzzleLayer, Puzzle, PuzzleLayer, SpendContext};
+use crate::{odd_child, DriverError, OuterPuzzleLayer, Puzzle, PuzzleLayer, SpendContext};
 
 #[derive(Debug)]
 pub struct SingletonLayer<IP> {
@@ -37,26 +38,10 @@ where
         layer_puzzle: NodePtr,
         layer_solution: NodePtr,
     ) -> Result<Option<Self>, DriverError> {
-        let parent_puzzle = Puzzle::parse(allocator, layer_puzzle);
-
-        let Some(parent_puzzle) = parent_puzzle.as_curried() else {
+        let Some(parent_args) = parse_singleton_args(allocator, layer_puzzle)? else {
             return Ok(None);
         };
 
-        if parent_puzzle.mod_hash != SINGLETON_TOP_LAYER_PUZZLE_HASH {
-            return Ok(None);
-        }
-
-        let parent_args = SingletonArgs::<NodePtr>::from_clvm(allocator, parent_puzzle.args)
-            .map_err(DriverError::FromClvm)?;
-
-        if parent_args.singleton_struct.mod_hash != SINGLETON_TOP_LAYER_PUZZLE_HASH.into()
-            || parent_args.singleton_struct.launcher_puzzle_hash
-                != SINGLETON_LAUNCHER_PUZZLE_HASH.into()
-        {
-            return Err(DriverError::InvalidSingletonStruct);
-        }
-
         let solution = SingletonSolution::<NodePtr>::from_clvm(allocator, layer_solution)
             .map_err(DriverError::FromClvm)?;
 
@@ -73,25 +58,10 @@ where
         allocator: &mut Allocator,
         layer_puzzle: NodePtr,
     ) -> Result<Option<Self>, DriverError> {
-        let puzzle = Puzzle::parse(allocator, layer_puzzle);
-
-        let Some(puzzle) = puzzle.as_curried() else {
+        let Some(args) = parse_singleton_args(allocator, layer_puzzle)? else {
             return Ok(None);
         };
 
-        if puzzle.mod_hash != SINGLETON_TOP_LAYER_PUZZLE_HASH {
-            return Ok(None);
-        }
-
-        let args = SingletonArgs::<NodePtr>::from_clvm(allocator, puzzle.args)
-            .map_err(DriverError::FromClvm)?;
-
-        if args.singleton_struct.mod_hash != SINGLETON_TOP_LAYER_PUZZLE_HASH.into()
-            || args.singleton_struct.launcher_puzzle_hash != SINGLETON_LAUNCHER_PUZZLE_HASH.into()
-        {
-            return Err(DriverError::InvalidSingletonStruct);
-        }
-
         match IP::from_puzzle(allocator, args.inner_puzzle)? {
             None => Ok(None),
             Some(inner_puzzle) => Ok(Some(SingletonLayer::<IP> {
@@ -102,19 +72,21 @@ where
     }
 
     fn construct_puzzle(&self, ctx: &mut SpendContext) -> Result<NodePtr, DriverError> {
-        CurriedProgram {
-            program: ctx.singleton_top_layer().map_err(DriverError::Spend)?,
+        let program = ctx.singleton_top_layer().map_err(DriverError::Spend)?;
+        let inner_puzzle = self.inner_puzzle.construct_puzzle(ctx)?;
+
+        ctx.alloc(&CurriedProgram {
+            program,
             args: SingletonArgs {
                 singleton_struct: SingletonStruct {
                     mod_hash: SINGLETON_TOP_LAYER_PUZZLE_HASH.into(),
                     launcher_puzzle_hash: SINGLETON_LAUNCHER_PUZZLE_HASH.into(),
                     launcher_id: self.launcher_id,
                 },
-                inner_puzzle: self.inner_puzzle.construct_puzzle(ctx)?,
+                inner_puzzle,
             },
-        }
-        .to_node_ptr(ctx.allocator_mut())
-        .map_err(DriverError::ToClvm)
+        })
+        .map_err(DriverError::Spend)
     }
 
     fn construct_solution(
@@ -122,18 +94,78 @@ where
         ctx: &mut SpendContext,
         solution: Self::Solution,
     ) -> Result<NodePtr, DriverError> {
-        SingletonSolution {
+        let inner_solution = self
+            .inner_puzzle
+            .construct_solution(ctx, solution.inner_solution)?;
+
+        ctx.alloc(&SingletonSolution {
             lineage_proof: solution.lineage_proof,
             amount: solution.amount,
-            inner_solution: self
-                .inner_puzzle
-                .construct_solution(ctx, solution.inner_solution)?,
+            inner_solution,
+        })
+        .map_err(DriverError::Spend)
+    }
+}
+
+/// Checks that the output of a singleton's inner puzzle creates exactly one odd child,
+/// unless the singleton is being melted.
+pub fn validate_singleton_output(
+    allocator: &mut Allocator,
+    inner_puzzle: NodePtr,
+    inner_solution: NodePtr,
+) -> Result<(), DriverError> {
+    check_output(singleton_output(allocator, inner_puzzle, inner_solution)?)
+}
+
+/// Checks that a singleton coin spend creates exactly one odd child, unless it's melted.
+/// Spends of coins which aren't singletons are ignored.
+pub fn validate_singleton_spend(
+    allocator: &mut Allocator,
+    coin_spend: &CoinSpend,
+) -> Result<(), DriverError> {
+    match singleton_spend_output(allocator, coin_spend)? {
+        Some(output) => check_output(output),
+        None => Ok(()),
+    }
+}
+
+fn check_output(output: SingletonOutput) -> Result<(), DriverError> {
+    match output {
+        SingletonOutput::OddChildren(odd_children) if !output.is_valid() => {
+            Err(DriverError::InvalidSingletonOutput(odd_children))
         }
-        .to_node_ptr(ctx.allocator_mut())
-        .map_err(DriverError::ToClvm)
+        _ => Ok(()),
     }
 }
 
+/// Checks whether a coin spend melts a singleton, rather than creating a child singleton.
+/// Returns `false` if the coin spend is not a singleton spend.
+pub fn is_melt_spend(
+    allocator: &mut Allocator,
+    coin_spend: &CoinSpend,
+) -> Result<bool, DriverError> {
+    let puzzle = coin_spend
+        .puzzle_reveal
+        .to_node_ptr(allocator)
+        .map_err(DriverError::ToClvm)?;
+    let solution = coin_spend
+        .solution
+        .to_node_ptr(allocator)
+        .map_err(DriverError::ToClvm)?;
+
+    let Some(args) = parse_singleton_args(allocator, puzzle)? else {
+        return Ok(false);
+    };
+
+    let solution = SingletonSolution::<NodePtr>::from_clvm(allocator, solution)
+        .map_err(DriverError::FromClvm)?;
+
+    Ok(
+        singleton_output(allocator, args.inner_puzzle, solution.inner_solution)?
+            == SingletonOutput::Melt,
+    )
+}
+
 impl<IP> OuterPuzzleLayer for SingletonLayer<IP>
 where
     IP: PuzzleLayer,
@@ -147,10 +179,26 @@ where
         solution: Self::Solution,
     ) -> Result<CoinSpend, DriverError> {
         let puzzle_ptr = self.construct_puzzle(ctx)?;
+        let solution_ptr = self.construct_solution(ctx, solution)?;
+
+        let puzzle = CurriedProgram::<NodePtr, SingletonArgs<NodePtr>>::from_clvm(
+            ctx.allocator(),
+            puzzle_ptr,
+        )
+        .map_err(DriverError::FromClvm)?;
+        let singleton_solution =
+            SingletonSolution::<NodePtr>::from_clvm(ctx.allocator(), solution_ptr)
+                .map_err(DriverError::FromClvm)?;
+
+        // A spend that doesn't melt the singleton or create exactly one odd child would be rejected.
+        validate_singleton_output(
+            ctx.allocator_mut(),
+            puzzle.args.inner_puzzle,
+            singleton_solution.inner_solution,
+        )?;
+
         let puzzle_reveal =
             Program::from_node_ptr(ctx.allocator(), puzzle_ptr).map_err(DriverError::FromClvm)?;
-
-        let solution_ptr = self.construct_solution(ctx, solution)?;
         let solution_reveal =
             Program::from_node_ptr(ctx.allocator(), solution_ptr).map_err(DriverError::FromClvm)?;
 
@@ -207,30 +255,454 @@ where
         parent_coin: Coin,
         parent_puzzle: NodePtr,
     ) -> Result<Option<LineageProof>, DriverError> {
-        let parent_puzzle = Puzzle::parse(allocator, parent_puzzle);
+        let Some(parent_args) = parse_singleton_args(allocator, parent_puzzle)? else {
+            return Ok(None);
+        };
+
+        Ok(Some(LineageProof {
+            parent_parent_coin_id: parent_coin.parent_coin_info,
+            parent_inner_puzzle_hash: tree_hash(allocator, parent_args.inner_puzzle).into(),
+            parent_amount: parent_coin.amount,
+        }))
+    }
+}
+
+impl<IP> SingletonLayer<IP>
+where
+    IP: PuzzleLayer + ToTreeHash,
+{
+    /// Parses the child layer, its lineage proof, and the child coin from a parent spend in one pass.
+    /// Returns [`None`] if the parent spend doesn't create exactly one odd child.
+    pub fn parse_child(
+        allocator: &mut Allocator,
+        parent_coin: Coin,
+        parent_puzzle: NodePtr,
+        parent_solution: NodePtr,
+    ) -> Result<Option<(Self, LineageProof, Coin)>, DriverError> {
+        let Some(parent_args) = parse_singleton_args(allocator, parent_puzzle)? else {
+            return Ok(None);
+        };
 
-        let Some(parent_puzzle) = parent_puzzle.as_curried() else {
+        let lineage_proof = LineageProof {
+            parent_parent_coin_id: parent_coin.parent_coin_info,
+            parent_inner_puzzle_hash: tree_hash(allocator, parent_args.inner_puzzle).into(),
+            parent_amount: parent_coin.amount,
+        };
+
+        let solution = SingletonSolution::<NodePtr>::from_clvm(allocator, parent_solution)
+            .map_err(DriverError::FromClvm)?;
+
+        let Some(inner_puzzle) =
+            IP::from_parent_spend(allocator, parent_args.inner_puzzle, solution.inner_solution)?
+        else {
             return Ok(None);
         };
 
-        if parent_puzzle.mod_hash != SINGLETON_TOP_LAYER_PUZZLE_HASH {
+        let layer = SingletonLayer::<IP> {
+            launcher_id: parent_args.singleton_struct.launcher_id,
+            inner_puzzle,
+        };
+
+        // The singleton may recreate itself with a different odd amount.
+        let Some(child_coin) = odd_child(allocator, parent_coin, parent_puzzle, parent_solution)?
+        else {
             return Ok(None);
+        };
+
+        Ok(Some((layer, lineage_proof, child_coin)))
+    }
+
+    /// Replays the spend history of a singleton in order, following the child at each step.
+    /// Returns the latest layer, coin, and lineage proof, or [`None`] if there are no singleton spends.
+    pub fn replay(
+        allocator: &mut Allocator,
+        spends: &[CoinSpend],
+    ) -> Result<Option<(Self, Coin, LineageProof)>, DriverError> {
+        let mut tip: Option<(Self, Coin, LineageProof)> = None;
+
+        for coin_spend in spends {
+            if let Some((_, coin, _)) = &tip {
+                if coin_spend.coin != *coin {
+                    return Err(DriverError::BrokenLineage);
+                }
+            }
+
+            let puzzle = coin_spend.puzzle_reveal.to_node_ptr(allocator)?;
+            let solution = coin_spend.solution.to_node_ptr(allocator)?;
+
+            let Some(child) = Self::parse_child(allocator, coin_spend.coin, puzzle, solution)?
+            else {
+                return if tip.is_none() {
+                    Ok(None)
+                } else {
+                    Err(DriverError::BrokenLineage)
+                };
+            };
+
+            let (layer, lineage_proof, coin) = child;
+            tip = Some((layer, coin, lineage_proof));
         }
 
-        let parent_args = SingletonArgs::<NodePtr>::from_clvm(allocator, parent_puzzle.args)
-            .map_err(DriverError::FromClvm)?;
+        Ok(tip)
+    }
+}
+
+/// Parses the curried arguments of a singleton puzzle, returning [`None`] if it isn't a singleton.
+fn parse_singleton_args(
+    allocator: &Allocator,
+    puzzle: NodePtr,
+) -> Result<Option<SingletonArgs<NodePtr>>, DriverError> {
+    let Some(puzzle) = Puzzle::parse(allocator, puzzle).as_curried() else {
+        return Ok(None);
+    };
+
+    if puzzle.mod_hash != SINGLETON_TOP_LAYER_PUZZLE_HASH {
+        return Ok(None);
+    }
+
+    let args = SingletonArgs::<NodePtr>::from_clvm(allocator, puzzle.args)
+        .map_err(DriverError::FromClvm)?;
+
+    if args.singleton_struct.mod_hash != SINGLETON_TOP_LAYER_PUZZLE_HASH.into()
+        || args.singleton_struct.launcher_puzzle_hash != SINGLETON_LAUNCHER_PUZZLE_HASH.into()
+    {
+        return Err(DriverError::InvalidSingletonStruct);
+    }
+
+    Ok(Some(args))
+}
+
+#[cfg(test)]
+mod tests {
+    use chia_bls::PublicKey;
+    use chia_puzzles::standard::StandardArgs;
+    use chia_sdk_test::{secret_key, test_transaction, Simulator};
+    use chia_sdk_types::conditions::{Condition, MeltSingleton};
+
+    use chia_puzzles::nft::{NftOwnershipLayerSolution, NftStateLayerSolution};
+
+    use crate::{
+        nft_mint, Conditions, Did, DidLayer, DidLayerSolution, Launcher, Spend, TransparentLayer,
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_multiple_odd_children() -> anyhow::Result<()> {
+        let ctx = &mut SpendContext::new();
+
+        let pk = PublicKey::default();
+        let puzzle_hash = StandardArgs::curry_tree_hash(pk).into();
+        let parent = Coin::new(Bytes32::default(), puzzle_hash, 1);
 
-        if parent_args.singleton_struct.mod_hash != SINGLETON_TOP_LAYER_PUZZLE_HASH.into()
-            || parent_args.singleton_struct.launcher_puzzle_hash
-                != SINGLETON_LAUNCHER_PUZZLE_HASH.into()
-        {
-            return Err(DriverError::InvalidSingletonStruct);
+        let (_, did, did_proof) = Launcher::new(parent.coin_id(), 1).create_simple_did(ctx, pk)?;
+
+        let inner_spend = Conditions::new()
+            .create_coin(puzzle_hash, 1)
+            .create_coin(puzzle_hash, 1)
+            .p2_spend(ctx, pk)?;
+
+        assert!(matches!(
+            did.spend(ctx, did_proof, inner_spend),
+            Err(DriverError::InvalidSingletonOutput(2))
+        ));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_melt_did() -> anyhow::Result<()> {
+        let sim = Simulator::new().await?;
+        let peer = sim.connect().await?;
+        let ctx = &mut SpendContext::new();
+
+        let sk = secret_key()?;
+        let pk = sk.public_key();
+
+        let puzzle_hash = StandardArgs::curry_tree_hash(pk).into();
+        let coin = sim.mint_coin(puzzle_hash, 1).await;
+
+        let (create_did, did, did_proof) =
+            Launcher::new(coin.coin_id(), 1).create_simple_did(ctx, pk)?;
+
+        ctx.spend_p2_coin(coin, pk, create_did)?;
+
+        let melt = ctx.alloc(&MeltSingleton::new())?;
+        let inner_spend = Conditions::new()
+            .condition(Condition::Other(melt))
+            .p2_spend(ctx, pk)?;
+
+        let layers = did.get_layered_object(Some(inner_spend.puzzle()));
+        let puzzle = layers.construct_puzzle(ctx)?;
+        let solution = layers.construct_solution(
+            ctx,
+            SingletonLayerSolution {
+                lineage_proof: did_proof,
+                amount: did.coin.amount,
+                inner_solution: DidLayerSolution {
+                    inner_solution: inner_spend.solution(),
+                },
+            },
+        )?;
+        ctx.spend(did.coin, Spend::new(puzzle, solution))?;
+
+        let coin_spends = ctx.take_spends();
+
+        for coin_spend in &coin_spends {
+            let is_melt = is_melt_spend(ctx.allocator_mut(), coin_spend)?;
+            assert_eq!(is_melt, coin_spend.coin == did.coin);
         }
 
-        Ok(Some(LineageProof {
-            parent_parent_coin_id: parent_coin.parent_coin_info,
-            parent_inner_puzzle_hash: tree_hash(allocator, parent_args.inner_puzzle).into(),
-            parent_amount: parent_coin.amount,
-        }))
+        // The melt is distinguishable from a spend that couldn't be parsed.
+        let melt_spend = coin_spends.last().expect("missing melt spend");
+        assert!(Did::<()>::from_parent_spend(ctx.allocator_mut(), melt_spend)?.is_none());
+
+        test_transaction(&peer, coin_spends, &[sk], sim.config().genesis_challenge).await;
+
+        let coin_state = sim
+            .coin_state(did.coin.coin_id())
+            .await
+            .expect("expected did coin");
+        assert!(coin_state.spent_height.is_some());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_melt_nft() -> anyhow::Result<()> {
+        let sim = Simulator::new().await?;
+        let peer = sim.connect().await?;
+        let ctx = &mut SpendContext::new();
+
+        let sk = secret_key()?;
+        let pk = sk.public_key();
+
+        let puzzle_hash = StandardArgs::curry_tree_hash(pk).into();
+        let coin = sim.mint_coin(puzzle_hash, 1).await;
+
+        let (mint_nft, nft, nft_proof) =
+            Launcher::new(coin.coin_id(), 1).mint_nft(ctx, nft_mint(puzzle_hash, None))?;
+
+        ctx.spend_p2_coin(coin, pk, mint_nft)?;
+
+        // The NFT state layer raises on any odd CREATE_COIN other than the child, so it can't be melted.
+        let melt = ctx.alloc(&MeltSingleton::new())?;
+        let inner_spend = Conditions::new()
+            .create_coin(puzzle_hash, 1)
+            .condition(Condition::Other(melt))
+            .p2_spend(ctx, pk)?;
+
+        let solution = |inner_solution| SingletonLayerSolution {
+            lineage_proof: nft_proof,
+            amount: nft.coin.amount,
+            inner_solution: NftStateLayerSolution {
+                inner_solution: NftOwnershipLayerSolution { inner_solution },
+            },
+        };
+
+        let layers = nft.get_layered_object(Some(inner_spend.puzzle()));
+        assert!(matches!(
+            layers.solve(ctx, nft.coin, solution(inner_spend.solution())),
+            Err(DriverError::Conditions(_))
+        ));
+
+        // The unchecked spend still can't be parsed as a melt.
+        let puzzle = layers.construct_puzzle(ctx)?;
+        let melt_solution = layers.construct_solution(ctx, solution(inner_spend.solution()))?;
+        let melt_spend = CoinSpend::new(
+            nft.coin,
+            Program::from_node_ptr(ctx.allocator(), puzzle)?,
+            Program::from_node_ptr(ctx.allocator(), melt_solution)?,
+        );
+        assert!(matches!(
+            is_melt_spend(ctx.allocator_mut(), &melt_spend),
+            Err(DriverError::Conditions(_))
+        ));
+
+        let inner_spend = Conditions::new()
+            .create_hinted_coin(nft.singleton_inner_puzzle_hash().into(), 1, puzzle_hash)
+            .p2_spend(ctx, pk)?;
+
+        let layers = nft.get_layered_object(Some(inner_spend.puzzle()));
+        let coin_spend = layers.solve(ctx, nft.coin, solution(inner_spend.solution()))?;
+        ctx.insert_coin_spend(coin_spend)?;
+
+        let coin_spends = ctx.take_spends();
+
+        for coin_spend in &coin_spends {
+            assert!(!is_melt_spend(ctx.allocator_mut(), coin_spend)?);
+        }
+
+        test_transaction(&peer, coin_spends, &[sk], sim.config().genesis_challenge).await;
+
+        let coin_state = sim
+            .coin_state(nft.coin.coin_id())
+            .await
+            .expect("expected nft coin");
+        assert!(coin_state.spent_height.is_some());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_solve_with_child() -> anyhow::Result<()> {
+        let ctx = &mut SpendContext::new();
+
+        let pk = PublicKey::default();
+        let puzzle_hash = StandardArgs::curry_tree_hash(pk).into();
+        let parent = Coin::new(Bytes32::default(), puzzle_hash, 1);
+
+        let (_, did, did_proof) = Launcher::new(parent.coin_id(), 1).create_simple_did(ctx, pk)?;
+
+        let inner_spend = Conditions::new()
+            .create_hinted_coin(did.singleton_inner_puzzle_hash().into(), 1, puzzle_hash)
+            .p2_spend(ctx, pk)?;
+
+        let layers = did.get_layered_object(Some(inner_spend.puzzle()));
+        let (coin_spend, child) = layers.solve_with_child(
+            ctx,
+            did.coin,
+            SingletonLayerSolution {
+                lineage_proof: did_proof,
+                amount: did.coin.amount,
+                inner_solution: DidLayerSolution {
+                    inner_solution: inner_spend.solution(),
+                },
+            },
+        )?;
+
+        let expected = Did::<()>::from_parent_spend(ctx.allocator_mut(), &coin_spend)?
+            .expect("expected child did");
+        assert_eq!(child, Some(expected.coin));
+        assert_eq!(
+            child.map(|coin| coin.puzzle_hash),
+            Some(did.coin.puzzle_hash)
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_child() -> anyhow::Result<()> {
+        let ctx = &mut SpendContext::new();
+
+        let pk = PublicKey::default();
+        let puzzle_hash = StandardArgs::curry_tree_hash(pk).into();
+        let parent = Coin::new(Bytes32::default(), puzzle_hash, 1);
+
+        let (_, did, did_proof) = Launcher::new(parent.coin_id(), 1).create_simple_did(ctx, pk)?;
+        let (child_did, child_proof) =
+            ctx.spend_standard_did(&did, did_proof, pk, Conditions::new())?;
+
+        let coin_spend = ctx
+            .take_spends()
+            .into_iter()
+            .find(|coin_spend| coin_spend.coin == did.coin)
+            .expect("expected did spend");
+        let puzzle = coin_spend.puzzle_reveal.to_node_ptr(ctx.allocator_mut())?;
+        let solution = coin_spend.solution.to_node_ptr(ctx.allocator_mut())?;
+
+        let (layer, lineage_proof, child_coin) = SingletonLayer::<
+            DidLayer<(), TransparentLayer<true>>,
+        >::parse_child(
+            ctx.allocator_mut(), did.coin, puzzle, solution
+        )?
+        .expect("expected child did");
+
+        assert_eq!(layer.launcher_id, did.launcher_id);
+        assert_eq!(child_coin, child_did.coin);
+        assert_eq!(Proof::Lineage(lineage_proof), child_proof);
+        assert_eq!(
+            Some(lineage_proof),
+            SingletonLayer::<DidLayer<(), TransparentLayer<true>>>::lineage_proof_from_parent_spend(
+                ctx.allocator(),
+                did.coin,
+                puzzle,
+            )?
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_child_with_new_amount() -> anyhow::Result<()> {
+        let ctx = &mut SpendContext::new();
+
+        let pk = PublicKey::default();
+        let puzzle_hash = StandardArgs::curry_tree_hash(pk).into();
+        let parent = Coin::new(Bytes32::default(), puzzle_hash, 1);
+
+        let (_, did, did_proof) = Launcher::new(parent.coin_id(), 1).create_simple_did(ctx, pk)?;
+
+        let inner_spend = Conditions::new()
+            .create_hinted_coin(did.singleton_inner_puzzle_hash().into(), 3, puzzle_hash)
+            .p2_spend(ctx, pk)?;
+        let (coin_spend, _, _) = did.spend(ctx, did_proof, inner_spend)?;
+
+        let puzzle = coin_spend.puzzle_reveal.to_node_ptr(ctx.allocator_mut())?;
+        let solution = coin_spend.solution.to_node_ptr(ctx.allocator_mut())?;
+
+        let (layer, lineage_proof, child_coin) = SingletonLayer::<
+            DidLayer<(), TransparentLayer<true>>,
+        >::parse_child(
+            ctx.allocator_mut(), did.coin, puzzle, solution
+        )?
+        .expect("expected child did");
+
+        assert_eq!(
+            child_coin,
+            Coin::new(did.coin.coin_id(), layer.tree_hash().into(), 3)
+        );
+        assert_eq!(lineage_proof.parent_amount, did.coin.amount);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_replay() -> anyhow::Result<()> {
+        type DidSingleton = SingletonLayer<DidLayer<(), TransparentLayer<true>>>;
+
+        let ctx = &mut SpendContext::new();
+
+        let pk = PublicKey::default();
+        let puzzle_hash = StandardArgs::curry_tree_hash(pk).into();
+        let parent = Coin::new(Bytes32::default(), puzzle_hash, 1);
+
+        let (_, did, did_proof) = Launcher::new(parent.coin_id(), 1).create_simple_did(ctx, pk)?;
+        let (did, did_proof) = ctx.spend_standard_did(&did, did_proof, pk, Conditions::new())?;
+        let (did, did_proof) = ctx.spend_standard_did(&did, did_proof, pk, Conditions::new())?;
+        let (tip, tip_proof) = ctx.spend_standard_did(&did, did_proof, pk, Conditions::new())?;
+
+        // Only the DID spends, not the launcher.
+        let spends: Vec<CoinSpend> = ctx
+            .take_spends()
+            .into_iter()
+            .filter(|coin_spend| coin_spend.coin.puzzle_hash == tip.coin.puzzle_hash)
+            .collect();
+        assert_eq!(spends.len(), 4);
+
+        let (layer, coin, lineage_proof) =
+            DidSingleton::replay(ctx.allocator_mut(), &spends)?.expect("expected did");
+        assert_eq!(layer.launcher_id, tip.launcher_id);
+        assert_eq!(coin, tip.coin);
+        assert_eq!(Proof::Lineage(lineage_proof), tip_proof);
+
+        assert!(DidSingleton::replay(ctx.allocator_mut(), &[])?.is_none());
+
+        let broken = [spends[0].clone(), spends[2].clone()];
+        assert!(matches!(
+            DidSingleton::replay(ctx.allocator_mut(), &broken),
+            Err(DriverError::BrokenLineage)
+        ));
+
+        // A coin with the same parent and puzzle hash but a different amount isn't the child.
+        let mut wrong_amount = spends[1].clone();
+        wrong_amount.coin.amount = 3;
+        assert!(matches!(
+            DidSingleton::replay(ctx.allocator_mut(), &[spends[0].clone(), wrong_amount]),
+            Err(DriverError::BrokenLineage)
+        ));
+
+        Ok(())
     }
 }
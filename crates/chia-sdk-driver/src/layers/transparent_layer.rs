@@ -64,15 +64,12 @@ impl<const HINT_OVERRIDE: bool> PuzzleLayer for TransparentLayer<HINT_OVERRIDE>
                     // e.g., DID created NFT
                     continue;
                 }
-                new_puzzle_hash = Some(
-                    if HINT_OVERRIDE && !cc.memos.is_empty() && cc.memos[0].len() == 32 {
-                        // standard puzzle will hint the inner puzzle hash
-                        // this is useful e.g., when re-creatign a DID (created puz hash != actual transparent layer puz hash)
-                        Bytes32::new(cc.memos[0].to_vec().try_into().unwrap())
-                    } else {
-                        cc.puzzle_hash
-                    },
-                );
+                new_puzzle_hash = Some(match cc.hint() {
+                    // standard puzzle will hint the inner puzzle hash
+                    // this is useful e.g., when re-creatign a DID (created puz hash != actual transparent layer puz hash)
+                    Some(hint) if HINT_OVERRIDE => hint,
+                    _ => cc.puzzle_hash,
+                });
             }
         }
 
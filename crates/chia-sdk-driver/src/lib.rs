@@ -1,5 +1,6 @@
 mod conditions;
 mod driver_error;
+mod fee_estimator;
 mod layers;
 mod primitives;
 mod puzzle;
@@ -11,6 +12,7 @@ mod spend_error;
 
 pub use conditions::*;
 pub use driver_error::*;
+pub use fee_estimator::*;
 pub use layers::*;
 pub use primitives::*;
 pub use puzzle::*;
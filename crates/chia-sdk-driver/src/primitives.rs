@@ -1,16 +1,27 @@
 mod cat;
+mod clawback;
 mod debug;
 mod did;
 mod did_launcher;
+mod did_recovery;
 mod intermediate_launcher;
+mod json;
 mod launcher;
+mod multisig;
 mod nft;
 mod nft_launcher;
+mod nft_metadata;
+mod streaming_payment;
 
 pub use cat::*;
+pub use clawback::*;
 pub use debug::*;
 pub use did::*;
+pub use did_recovery::*;
 pub use intermediate_launcher::*;
 pub use launcher::*;
+pub use multisig::*;
 pub use nft::*;
 pub use nft_launcher::*;
+pub use nft_metadata::*;
+pub use streaming_payment::*;
@@ -1,14 +1,18 @@
+use chia_bls::PublicKey;
 use chia_protocol::{Bytes32, Coin, CoinSpend, Program};
 use chia_puzzles::{
-    cat::{CatSolution, CoinProof},
+    cat::{CatSolution, CoinProof, CAT_PUZZLE_HASH},
     LineageProof, Proof,
 };
-use chia_sdk_types::conditions::{run_puzzle, Condition};
+use chia_sdk_types::conditions::{run_puzzle, Condition, RunTail};
 use clvm_traits::{FromClvm, FromNodePtr, ToNodePtr};
 use clvm_utils::TreeHash;
 use clvmr::{Allocator, NodePtr};
 
-use crate::{CatLayer, DriverError, PuzzleLayer, Spend, SpendContext, TransparentLayer};
+use crate::{
+    CatLayer, CatSpend, Conditions, DriverError, Puzzle, PuzzleLayer, Spend, SpendContext,
+    SpendError, TransparentLayer,
+};
 
 #[derive(Debug, Clone, Copy)]
 pub struct Cat {
@@ -168,4 +172,328 @@ impl Cat {
             }),
         ))
     }
+
+    /// Spends CATs owned by standard p2 puzzles together in a single ring.
+    /// Each inner spend is built from the synthetic key and the conditions it should output.
+    pub fn spend_all_standard(
+        ctx: &mut SpendContext,
+        inputs: Vec<(Cat, LineageProof, PublicKey, Conditions)>,
+    ) -> Result<(), DriverError> {
+        let Some(asset_id) = inputs.first().map(|(cat, ..)| cat.asset_id) else {
+            return Ok(());
+        };
+
+        let mut cat_spend = CatSpend::new(asset_id);
+
+        for (cat, lineage_proof, synthetic_key, conditions) in inputs {
+            if cat.asset_id != asset_id {
+                return Err(DriverError::MismatchedAssetId);
+            }
+
+            let inner_spend = conditions
+                .p2_spend(ctx, synthetic_key)
+                .map_err(|error| DriverError::from(error).context("spending CATs"))?;
+            cat_spend = cat_spend.spend(cat.coin, inner_spend, lineage_proof, 0);
+        }
+
+        cat_spend
+            .finish(ctx)
+            .map_err(|error| DriverError::from(error).context("spending CATs"))?;
+
+        Ok(())
+    }
+
+    /// Creates the inner conditions required to melt part of the CAT's supply by running the TAIL.
+    /// The remaining amount is recreated with the same p2 puzzle hash.
+    ///
+    /// Returns the conditions and the extra delta that must be used in the CAT spend.
+    pub fn melt(
+        &self,
+        ctx: &mut SpendContext,
+        tail_reveal: NodePtr,
+        tail_solution: NodePtr,
+        amount: u64,
+    ) -> Result<(Conditions, i64), DriverError> {
+        if ctx.tree_hash(tail_reveal) != self.asset_id.into() {
+            return Err(DriverError::MismatchedTail);
+        }
+
+        if amount > self.coin.amount {
+            return Err(DriverError::MeltAmountTooLarge);
+        }
+
+        let extra_delta = -i64::try_from(amount).map_err(SpendError::from)?;
+        let run_tail = ctx.alloc(&RunTail::new(tail_reveal, tail_solution))?;
+        let mut conditions = Conditions::new().condition(Condition::Other(run_tail));
+
+        let remaining = self.coin.amount - amount;
+
+        if remaining > 0 {
+            conditions = conditions.create_hinted_coin(
+                self.p2_puzzle_hash.into(),
+                remaining,
+                self.p2_puzzle_hash.into(),
+            );
+        }
+
+        Ok((conditions, extra_delta))
+    }
+}
+
+/// Parses the extra delta from the solution of a CAT spend, which is positive for issuance and negative for melting.
+/// Returns [`None`] if the coin spend isn't a CAT.
+pub fn cat_extra_delta(
+    allocator: &mut Allocator,
+    coin_spend: &CoinSpend,
+) -> Result<Option<i64>, DriverError> {
+    let puzzle = coin_spend
+        .puzzle_reveal
+        .to_node_ptr(allocator)
+        .map_err(DriverError::ToClvm)?;
+
+    let Some(puzzle) = Puzzle::parse(allocator, puzzle).as_curried() else {
+        return Ok(None);
+    };
+
+    if puzzle.mod_hash != CAT_PUZZLE_HASH {
+        return Ok(None);
+    }
+
+    let solution = coin_spend
+        .solution
+        .to_node_ptr(allocator)
+        .map_err(DriverError::ToClvm)?;
+    let solution =
+        CatSolution::<NodePtr>::from_clvm(allocator, solution).map_err(DriverError::FromClvm)?;
+
+    Ok(Some(solution.extra_delta))
+}
+
+#[cfg(test)]
+mod tests {
+    use chia_puzzles::{
+        cat::{CatArgs, EverythingWithSignatureTailArgs, GenesisByCoinIdTailArgs},
+        standard::StandardArgs,
+    };
+    use chia_sdk_test::{secret_key, test_transaction, Simulator};
+    use clvm_utils::CurriedProgram;
+
+    use crate::{issue_cat_from_key, CatSpend};
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_cat_melt() -> anyhow::Result<()> {
+        let sim = Simulator::new().await?;
+        let peer = sim.connect().await?;
+        let ctx = &mut SpendContext::new();
+
+        let sk = secret_key()?;
+        let pk = sk.public_key();
+
+        let puzzle_hash = StandardArgs::curry_tree_hash(pk).into();
+        let coin = sim.mint_coin(puzzle_hash, 1000).await;
+
+        let conditions = Conditions::new().create_hinted_coin(puzzle_hash, 1000, puzzle_hash);
+        let (issue_cat, issuance) = issue_cat_from_key(ctx, coin.coin_id(), pk, 1000, conditions)?;
+
+        ctx.spend_p2_coin(coin, pk, issue_cat)?;
+
+        let cat_puzzle_hash =
+            CatArgs::curry_tree_hash(issuance.asset_id, puzzle_hash.into()).into();
+        let cat = Cat::new(
+            Coin::new(issuance.eve_coin.coin_id(), cat_puzzle_hash, 1000),
+            issuance.asset_id,
+            puzzle_hash.into(),
+            None,
+        );
+
+        let tail = ctx.everything_with_signature_tail_puzzle()?;
+        let tail_reveal = ctx.alloc(&CurriedProgram {
+            program: tail,
+            args: EverythingWithSignatureTailArgs::new(pk),
+        })?;
+
+        let (melt, extra_delta) = cat.melt(ctx, tail_reveal, NodePtr::NIL, 400)?;
+        assert_eq!(extra_delta, -400);
+
+        CatSpend::new(cat.asset_id)
+            .spend(
+                cat.coin,
+                melt.p2_spend(ctx, pk)?,
+                issuance.lineage_proof,
+                extra_delta,
+            )
+            .finish(ctx)?;
+
+        test_transaction(
+            &peer,
+            ctx.take_spends(),
+            &[sk],
+            sim.config().genesis_challenge,
+        )
+        .await;
+
+        let melted_coin = Coin::new(cat.coin.coin_id(), cat_puzzle_hash, 600);
+        assert!(sim.coin_state(melted_coin.coin_id()).await.is_some());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_cat_spend_all_standard() -> anyhow::Result<()> {
+        let sim = Simulator::new().await?;
+        let peer = sim.connect().await?;
+        let ctx = &mut SpendContext::new();
+
+        let sk = secret_key()?;
+        let pk = sk.public_key();
+
+        let puzzle_hash = StandardArgs::curry_tree_hash(pk).into();
+        let coin = sim.mint_coin(puzzle_hash, 1000).await;
+
+        let conditions = Conditions::new().create_hinted_coin(puzzle_hash, 1000, puzzle_hash);
+        let (issue_cat, issuance) = issue_cat_from_key(ctx, coin.coin_id(), pk, 1000, conditions)?;
+
+        ctx.spend_p2_coin(coin, pk, issue_cat)?;
+
+        let cat_puzzle_hash =
+            CatArgs::curry_tree_hash(issuance.asset_id, puzzle_hash.into()).into();
+        let cat = Cat::new(
+            Coin::new(issuance.eve_coin.coin_id(), cat_puzzle_hash, 1000),
+            issuance.asset_id,
+            puzzle_hash.into(),
+            None,
+        );
+
+        let first_puzzle_hash = Bytes32::new([1; 32]);
+        let second_puzzle_hash = Bytes32::new([2; 32]);
+
+        Cat::spend_all_standard(
+            ctx,
+            vec![(
+                cat,
+                issuance.lineage_proof,
+                pk,
+                Conditions::new()
+                    .create_hinted_coin(first_puzzle_hash, 300, first_puzzle_hash)
+                    .create_hinted_coin(second_puzzle_hash, 200, second_puzzle_hash)
+                    .create_hinted_coin(puzzle_hash, 500, puzzle_hash),
+            )],
+        )?;
+
+        test_transaction(
+            &peer,
+            ctx.take_spends(),
+            &[sk],
+            sim.config().genesis_challenge,
+        )
+        .await;
+
+        for (p2_puzzle_hash, amount) in [
+            (first_puzzle_hash, 300),
+            (second_puzzle_hash, 200),
+            (puzzle_hash, 500),
+        ] {
+            let puzzle_hash =
+                CatArgs::curry_tree_hash(issuance.asset_id, p2_puzzle_hash.into()).into();
+            let child = Coin::new(cat.coin.coin_id(), puzzle_hash, amount);
+            assert!(sim.coin_state(child.coin_id()).await.is_some());
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_cat_extra_delta() -> anyhow::Result<()> {
+        let ctx = &mut SpendContext::new();
+
+        let pk = PublicKey::default();
+        let puzzle_hash = StandardArgs::curry_tree_hash(pk);
+        let asset_id = Bytes32::new([1; 32]);
+        let cat_puzzle_hash = CatArgs::curry_tree_hash(asset_id, puzzle_hash).into();
+        let lineage_proof = LineageProof {
+            parent_parent_coin_id: Bytes32::default(),
+            parent_inner_puzzle_hash: puzzle_hash.into(),
+            parent_amount: 1000,
+        };
+
+        // Issuance, a normal transfer, and a melt respectively.
+        for extra_delta in [500, 0, -400] {
+            let coin = Coin::new(Bytes32::default(), cat_puzzle_hash, 1000);
+            let output = u64::try_from(1000 + extra_delta)?;
+            let inner_spend = Conditions::new()
+                .create_coin(puzzle_hash.into(), output)
+                .p2_spend(ctx, pk)?;
+
+            CatSpend::new(asset_id)
+                .spend(coin, inner_spend, lineage_proof, extra_delta)
+                .finish(ctx)?;
+
+            let coin_spend = ctx.take_spends().remove(0);
+            assert_eq!(
+                cat_extra_delta(ctx.allocator_mut(), &coin_spend)?,
+                Some(extra_delta)
+            );
+        }
+
+        let coin = Coin::new(Bytes32::default(), puzzle_hash.into(), 1000);
+        ctx.spend_p2_coin(coin, pk, Conditions::new())?;
+        let coin_spend = ctx.take_spends().remove(0);
+        assert_eq!(cat_extra_delta(ctx.allocator_mut(), &coin_spend)?, None);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_cat_spend_all_standard_mismatched_asset_id() {
+        let ctx = &mut SpendContext::new();
+
+        let pk = PublicKey::default();
+        let puzzle_hash = StandardArgs::curry_tree_hash(pk);
+        let lineage_proof = LineageProof {
+            parent_parent_coin_id: Bytes32::default(),
+            parent_inner_puzzle_hash: puzzle_hash.into(),
+            parent_amount: 1,
+        };
+
+        let inputs = [Bytes32::new([1; 32]), Bytes32::new([2; 32])]
+            .into_iter()
+            .map(|asset_id| {
+                let coin = Coin::new(asset_id, Bytes32::default(), 1);
+                let cat = Cat::new(coin, asset_id, puzzle_hash, None);
+                (cat, lineage_proof, pk, Conditions::new())
+            })
+            .collect();
+
+        assert!(matches!(
+            Cat::spend_all_standard(ctx, inputs),
+            Err(DriverError::MismatchedAssetId)
+        ));
+    }
+
+    #[test]
+    fn test_cat_melt_mismatched_tail() -> anyhow::Result<()> {
+        let ctx = &mut SpendContext::new();
+
+        let tail = ctx.genesis_by_coin_id_tail_puzzle()?;
+        let tail_reveal = ctx.alloc(&CurriedProgram {
+            program: tail,
+            args: GenesisByCoinIdTailArgs::new(Bytes32::default()),
+        })?;
+
+        let cat = Cat::new(
+            Coin::new(Bytes32::default(), Bytes32::default(), 1000),
+            Bytes32::new([1; 32]),
+            Bytes32::default().into(),
+            None,
+        );
+
+        assert!(matches!(
+            cat.melt(ctx, tail_reveal, NodePtr::NIL, 400),
+            Err(DriverError::MismatchedTail)
+        ));
+
+        Ok(())
+    }
 }
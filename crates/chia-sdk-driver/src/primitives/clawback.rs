@@ -0,0 +1,304 @@
+use chia_bls::PublicKey;
+use chia_protocol::{Bytes32, Coin};
+use clvm_traits::{FromClvm, ToClvm};
+use clvm_utils::{CurriedProgram, ToTreeHash, TreeHash};
+use hex_literal::hex;
+
+use crate::{Conditions, Spend, SpendContext, SpendError};
+
+/// The clawback puzzle reveal, which can be found in `puzzles/clawback.clsp.hex`.
+pub const CLAWBACK_PUZZLE: [u8; 231] = hex!(
+    "
+    ff02ffff01ff02ff0affff04ff02ffff04ff05ffff04ff0bffff04ff17ffff04
+    ffff02ff0effff04ff02ffff04ff2fff80808080ffff04ffff02ff2fff5f80ff
+    8080808080808080ffff04ffff01ff50ffff02ffff03ffff09ff2fff0580ffff
+    015fffff01ff02ffff03ffff09ff2fff0b80ffff01ff04ffff04ff04ffff04ff
+    17ff808080ff5f80ffff01ff088080ff018080ff0180ff02ffff03ffff07ff05
+    80ffff01ff0bffff0102ffff02ff0effff04ff02ffff04ff09ff80808080ffff
+    02ff0effff04ff02ffff04ff0dff8080808080ffff01ff0bffff0101ff058080
+    ff0180ff018080
+    "
+);
+
+/// The tree hash of [`CLAWBACK_PUZZLE`].
+pub const CLAWBACK_PUZZLE_HASH: TreeHash = TreeHash::new(hex!(
+    "ebf96faa374d9d84fa288fff1cf60922e40e9b6391c1b1590122fd5e1ff1b36a"
+));
+
+/// The curried arguments of the clawback puzzle.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ToClvm, FromClvm)]
+#[clvm(curry)]
+pub struct ClawbackArgs {
+    pub sender_puzzle_hash: Bytes32,
+    pub recipient_puzzle_hash: Bytes32,
+    pub seconds: u64,
+}
+
+/// The solution to the clawback puzzle, which reveals and runs the sender or recipient's inner puzzle.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ToClvm, FromClvm)]
+#[clvm(list)]
+pub struct ClawbackSolution<P, S> {
+    pub inner_puzzle: P,
+    pub inner_solution: S,
+}
+
+/// A coin sent to a recipient, which can be clawed back by the sender until it's claimed.
+/// The recipient can only claim the coin once the timelock has passed since it was created.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[must_use]
+pub struct Clawback {
+    pub sender_puzzle_hash: Bytes32,
+    pub recipient_puzzle_hash: Bytes32,
+    pub seconds: u64,
+}
+
+impl Clawback {
+    pub fn new(sender_puzzle_hash: Bytes32, recipient_puzzle_hash: Bytes32, seconds: u64) -> Self {
+        Self {
+            sender_puzzle_hash,
+            recipient_puzzle_hash,
+            seconds,
+        }
+    }
+
+    /// The puzzle hash of the clawback coin.
+    pub fn puzzle_hash(&self) -> TreeHash {
+        CurriedProgram {
+            program: CLAWBACK_PUZZLE_HASH,
+            args: self.args(),
+        }
+        .tree_hash()
+    }
+
+    /// Creates the clawback coin from the parent coin, hinted to the recipient.
+    /// The conditions must be output by the parent coin's spend (for example with [`SpendContext::spend_p2_coin`]).
+    pub fn create(&self, parent_coin_id: Bytes32, amount: u64) -> (Conditions, Coin) {
+        let puzzle_hash = self.puzzle_hash().into();
+
+        (
+            Conditions::new().create_hinted_coin(puzzle_hash, amount, self.recipient_puzzle_hash),
+            Coin::new(parent_coin_id, puzzle_hash, amount),
+        )
+    }
+
+    /// Spends the clawback coin with the sender's standard p2 puzzle, at any time before it's claimed.
+    /// Returns the conditions output by the clawback coin.
+    pub fn claw_back(
+        &self,
+        ctx: &mut SpendContext,
+        coin: Coin,
+        sender_key: PublicKey,
+        conditions: Conditions,
+    ) -> Result<Conditions, SpendError> {
+        self.spend_path(ctx, coin, sender_key, conditions)
+    }
+
+    /// Spends the clawback coin with the recipient's standard p2 puzzle, once the timelock has passed.
+    /// Returns the conditions output by the clawback coin, including the timelock assertion.
+    pub fn claim(
+        &self,
+        ctx: &mut SpendContext,
+        coin: Coin,
+        recipient_key: PublicKey,
+        conditions: Conditions,
+    ) -> Result<Conditions, SpendError> {
+        // The puzzle enforces the timelock on this path, but the recipient signs over it as well.
+        let conditions = conditions.assert_seconds_relative(self.seconds);
+        self.spend_path(ctx, coin, recipient_key, conditions)
+    }
+
+    fn args(&self) -> ClawbackArgs {
+        ClawbackArgs {
+            sender_puzzle_hash: self.sender_puzzle_hash,
+            recipient_puzzle_hash: self.recipient_puzzle_hash,
+            seconds: self.seconds,
+        }
+    }
+
+    fn spend_path(
+        &self,
+        ctx: &mut SpendContext,
+        coin: Coin,
+        synthetic_key: PublicKey,
+        conditions: Conditions,
+    ) -> Result<Conditions, SpendError> {
+        let p2_spend = conditions.clone().p2_spend(ctx, synthetic_key)?;
+        let clawback_puzzle = ctx.puzzle(CLAWBACK_PUZZLE_HASH, &CLAWBACK_PUZZLE)?;
+
+        let puzzle = ctx.alloc(&CurriedProgram {
+            program: clawback_puzzle,
+            args: self.args(),
+        })?;
+
+        let solution = ctx.alloc(&ClawbackSolution {
+            inner_puzzle: p2_spend.puzzle(),
+            inner_solution: p2_spend.solution(),
+        })?;
+
+        ctx.spend(coin, Spend::new(puzzle, solution))?;
+
+        Ok(conditions)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chia_bls::DerivableKey;
+    use chia_puzzles::standard::StandardArgs;
+    use chia_sdk_test::{secret_key, test_transaction, test_transaction_raw, Simulator};
+
+    use super::*;
+
+    #[test]
+    fn test_clawback_puzzle_hash() -> anyhow::Result<()> {
+        let mut ctx = SpendContext::new();
+        let puzzle = ctx.puzzle(CLAWBACK_PUZZLE_HASH, &CLAWBACK_PUZZLE)?;
+        assert_eq!(ctx.tree_hash(puzzle), CLAWBACK_PUZZLE_HASH);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_clawback() -> anyhow::Result<()> {
+        let sim = Simulator::new().await?;
+        let peer = sim.connect().await?;
+        let ctx = &mut SpendContext::new();
+
+        let sender_secret_key = secret_key()?.derive_unhardened(0);
+        let sender_key = sender_secret_key.public_key();
+        let sender_puzzle_hash = StandardArgs::curry_tree_hash(sender_key).into();
+
+        let recipient_secret_key = secret_key()?.derive_unhardened(1);
+        let recipient_key = recipient_secret_key.public_key();
+        let recipient_puzzle_hash = StandardArgs::curry_tree_hash(recipient_key).into();
+
+        let coin = sim.mint_coin(sender_puzzle_hash, 1).await;
+
+        let clawback = Clawback::new(sender_puzzle_hash, recipient_puzzle_hash, 100);
+        let (create_clawback, clawback_coin) = clawback.create(coin.coin_id(), 1);
+        ctx.spend_p2_coin(coin, sender_key, create_clawback)?;
+
+        test_transaction(
+            &peer,
+            ctx.take_spends(),
+            &[sender_secret_key.clone()],
+            sim.config().genesis_challenge,
+        )
+        .await;
+
+        // The recipient can't claim the coin before the timelock has passed.
+        let claimed = clawback.claim(
+            ctx,
+            clawback_coin,
+            recipient_key,
+            Conditions::new().create_coin(recipient_puzzle_hash, 1),
+        )?;
+        assert_eq!(
+            claimed,
+            Conditions::new()
+                .create_coin(recipient_puzzle_hash, 1)
+                .assert_seconds_relative(100)
+        );
+
+        let ack = test_transaction_raw(
+            &peer,
+            ctx.take_spends(),
+            &[recipient_secret_key.clone()],
+            sim.config().genesis_challenge,
+        )
+        .await?;
+        assert_eq!(ack.status, 3);
+
+        // But the sender can claw it back, without a timelock.
+        let clawed_back = clawback.claw_back(
+            ctx,
+            clawback_coin,
+            sender_key,
+            Conditions::new().create_coin(sender_puzzle_hash, 1),
+        )?;
+        assert_eq!(
+            clawed_back,
+            Conditions::new().create_coin(sender_puzzle_hash, 1)
+        );
+
+        test_transaction(
+            &peer,
+            ctx.take_spends(),
+            &[sender_secret_key],
+            sim.config().genesis_challenge,
+        )
+        .await;
+
+        let coin_state = sim
+            .coin_state(clawback_coin.coin_id())
+            .await
+            .expect("expected clawback coin");
+        assert!(coin_state.spent_height.is_some());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_clawback_claim() -> anyhow::Result<()> {
+        let sim = Simulator::new().await?;
+        let peer = sim.connect().await?;
+        let ctx = &mut SpendContext::new();
+
+        let sender_secret_key = secret_key()?.derive_unhardened(0);
+        let sender_key = sender_secret_key.public_key();
+        let sender_puzzle_hash = StandardArgs::curry_tree_hash(sender_key).into();
+
+        let recipient_secret_key = secret_key()?.derive_unhardened(1);
+        let recipient_key = recipient_secret_key.public_key();
+        let recipient_puzzle_hash = StandardArgs::curry_tree_hash(recipient_key).into();
+
+        let coin = sim.mint_coin(sender_puzzle_hash, 1).await;
+
+        let clawback = Clawback::new(sender_puzzle_hash, recipient_puzzle_hash, 100);
+        let (create_clawback, clawback_coin) = clawback.create(coin.coin_id(), 1);
+        ctx.spend_p2_coin(coin, sender_key, create_clawback)?;
+
+        test_transaction(
+            &peer,
+            ctx.take_spends(),
+            &[sender_secret_key.clone()],
+            sim.config().genesis_challenge,
+        )
+        .await;
+
+        sim.pass_time(100).await;
+
+        let _ = clawback.claim(
+            ctx,
+            clawback_coin,
+            recipient_key,
+            Conditions::new().create_coin(recipient_puzzle_hash, 1),
+        )?;
+
+        test_transaction(
+            &peer,
+            ctx.take_spends(),
+            &[recipient_secret_key],
+            sim.config().genesis_challenge,
+        )
+        .await;
+
+        // The sender can no longer claw back the coin once it has been claimed.
+        let _ = clawback.claw_back(
+            ctx,
+            clawback_coin,
+            sender_key,
+            Conditions::new().create_coin(sender_puzzle_hash, 1),
+        )?;
+
+        let ack = test_transaction_raw(
+            &peer,
+            ctx.take_spends(),
+            &[sender_secret_key],
+            sim.config().genesis_challenge,
+        )
+        .await?;
+        assert_eq!(ack.status, 3);
+
+        Ok(())
+    }
+}
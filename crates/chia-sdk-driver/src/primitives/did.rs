@@ -1,12 +1,13 @@
+use chia_bls::PublicKey;
 use chia_protocol::{Bytes32, Coin, CoinSpend, Program};
-use chia_puzzles::{LineageProof, Proof};
-use clvm_traits::{FromClvm, FromNodePtr, ToClvm, ToNodePtr};
+use chia_puzzles::{standard::StandardArgs, LineageProof, Proof};
+use clvm_traits::{FromClvm, ToClvm, ToNodePtr};
 use clvm_utils::{ToTreeHash, TreeHash};
 use clvmr::{Allocator, NodePtr};
 
 use crate::{
-    DidLayer, DidLayerSolution, DriverError, PuzzleLayer, SingletonLayer, SingletonLayerSolution,
-    Spend, SpendContext, TransparentLayer,
+    Conditions, DidLayer, DidLayerSolution, DriverError, OuterPuzzleLayer, PuzzleLayer,
+    SingletonLayer, SingletonLayerSolution, Spend, SpendContext, TransparentLayer,
 };
 
 #[derive(Debug, Clone, Copy)]
@@ -23,6 +24,7 @@ pub struct Did<M = NodePtr> {
 
     // innermost (owner) layer
     pub p2_puzzle_hash: TreeHash,
+    /// The revealed p2 puzzle, if known.
     pub p2_puzzle: Option<NodePtr>,
 }
 
@@ -69,12 +71,28 @@ where
     where
         M: ToTreeHash,
     {
-        let puzzle_ptr = cs
-            .puzzle_reveal
-            .to_node_ptr(allocator)
-            .map_err(DriverError::ToClvm)?;
-        let solution_ptr = cs
-            .solution
+        Ok(Self::from_puzzle_and_solution(
+            allocator,
+            cs.coin.coin_id(),
+            &cs.puzzle_reveal,
+            &cs.solution,
+        )?
+        .map(|(did, _)| did))
+    }
+
+    /// Parses the child DID from the puzzle and solution of its parent, such as those returned by a peer.
+    /// Returns [`None`] if the parent isn't a DID.
+    pub fn from_puzzle_and_solution(
+        allocator: &mut Allocator,
+        parent_coin_id: Bytes32,
+        puzzle: &Program,
+        solution: &Program,
+    ) -> Result<Option<(Self, Coin)>, DriverError>
+    where
+        M: ToTreeHash,
+    {
+        let puzzle_ptr = puzzle.to_node_ptr(allocator).map_err(DriverError::ToClvm)?;
+        let solution_ptr = solution
             .to_node_ptr(allocator)
             .map_err(DriverError::ToClvm)?;
 
@@ -84,18 +102,24 @@ where
             solution_ptr,
         )?;
 
-        match res {
-            None => Ok(None),
-            Some(res) => Ok(Some(Did {
-                coin: Coin::new(cs.coin.coin_id(), res.tree_hash().into(), 1),
+        let Some(res) = res else {
+            return Ok(None);
+        };
+
+        let coin = Coin::new(parent_coin_id, res.tree_hash().into(), 1);
+
+        Ok(Some((
+            Did {
+                coin,
                 launcher_id: res.launcher_id,
                 recovery_did_list_hash: res.inner_puzzle.recovery_did_list_hash,
                 num_verifications_required: res.inner_puzzle.num_verifications_required,
                 metadata: res.inner_puzzle.metadata,
                 p2_puzzle_hash: res.inner_puzzle.inner_puzzle.puzzle_hash,
                 p2_puzzle: res.inner_puzzle.inner_puzzle.puzzle,
-            })),
-        }
+            },
+            coin,
+        )))
     }
 
     pub fn from_puzzle(
@@ -156,12 +180,9 @@ where
     {
         let thing = self.get_layered_object(Some(inner_spend.puzzle()));
 
-        let puzzle_ptr = thing.construct_puzzle(ctx)?;
-        let puzzle =
-            Program::from_node_ptr(ctx.allocator(), puzzle_ptr).map_err(DriverError::FromClvm)?;
-
-        let solution_ptr = thing.construct_solution(
+        let cs = thing.solve(
             ctx,
+            self.coin,
             SingletonLayerSolution {
                 lineage_proof,
                 amount: self.coin.amount,
@@ -170,14 +191,6 @@ where
                 },
             },
         )?;
-        let solution =
-            Program::from_node_ptr(ctx.allocator(), solution_ptr).map_err(DriverError::FromClvm)?;
-
-        let cs = CoinSpend {
-            coin: self.coin,
-            puzzle_reveal: puzzle,
-            solution,
-        };
         let lineage_proof = thing.lineage_proof_for_child(self.coin.parent_coin_info, 1);
 
         Ok((
@@ -186,6 +199,92 @@ where
             Proof::Lineage(lineage_proof),
         ))
     }
+
+    /// Spends the DID with the standard p2 puzzle, recreating it with new metadata.
+    /// The child can't be parsed from this spend alone, since the parent's puzzle only reveals the old metadata.
+    pub fn update_metadata<N>(
+        &self,
+        ctx: &mut SpendContext,
+        lineage_proof: Proof,
+        new_metadata: N,
+        synthetic_key: PublicKey,
+    ) -> Result<(Did<N>, Proof), DriverError>
+    where
+        M: Clone + ToTreeHash,
+        N: ToClvm<NodePtr> + FromClvm<NodePtr> + Clone + ToTreeHash,
+    {
+        self.recreate(
+            ctx,
+            lineage_proof,
+            new_metadata,
+            StandardArgs::curry_tree_hash(synthetic_key),
+            synthetic_key,
+        )
+    }
+
+    /// Spends the DID with the old key's standard p2 puzzle, recreating it under the new key's standard p2 puzzle.
+    /// This is used to rotate the controlling key, for example if it has been compromised.
+    pub fn rotate_key(
+        &self,
+        ctx: &mut SpendContext,
+        lineage_proof: Proof,
+        new_synthetic_key: PublicKey,
+        old_synthetic_key: PublicKey,
+    ) -> Result<(Did<M>, Proof), DriverError>
+    where
+        M: Clone + ToTreeHash,
+    {
+        self.recreate(
+            ctx,
+            lineage_proof,
+            self.metadata.clone(),
+            StandardArgs::curry_tree_hash(new_synthetic_key),
+            old_synthetic_key,
+        )
+    }
+
+    /// Spends the DID with the signing key's standard p2 puzzle, recreating it with the new metadata and p2 puzzle hash.
+    fn recreate<N>(
+        &self,
+        ctx: &mut SpendContext,
+        lineage_proof: Proof,
+        new_metadata: N,
+        new_p2_puzzle_hash: TreeHash,
+        signing_key: PublicKey,
+    ) -> Result<(Did<N>, Proof), DriverError>
+    where
+        M: Clone + ToTreeHash,
+        N: ToClvm<NodePtr> + FromClvm<NodePtr> + Clone + ToTreeHash,
+    {
+        let new_did = Did::new(
+            self.coin,
+            self.launcher_id,
+            self.recovery_did_list_hash,
+            self.num_verifications_required,
+            new_metadata,
+            new_p2_puzzle_hash,
+            None,
+        );
+
+        let p2_spend = Conditions::new()
+            .create_hinted_coin(
+                // DID layer does not automatically wrap CREATE_COINs
+                new_did
+                    .compute_new_did_layer_puzzle_hash(new_p2_puzzle_hash)
+                    .into(),
+                self.coin.amount,
+                new_p2_puzzle_hash.into(),
+            )
+            .p2_spend(ctx, signing_key)?;
+
+        let (did_spend, _, new_proof) = self.spend(ctx, lineage_proof, p2_spend)?;
+        ctx.insert_coin_spend(did_spend)?;
+
+        let puzzle_hash = new_did.get_layered_object(None).tree_hash();
+        let coin = Coin::new(self.coin.coin_id(), puzzle_hash.into(), self.coin.amount);
+
+        Ok((new_did.with_coin(coin), new_proof))
+    }
 }
 
 impl<M> Did<M>
@@ -223,13 +322,181 @@ where
 
 #[cfg(test)]
 mod tests {
-    use chia_puzzles::standard::StandardArgs;
-    use chia_sdk_test::{secret_key, test_transaction, Simulator};
+    use chia_bls::DerivableKey;
+    use chia_sdk_test::{secret_key, test_transaction, test_transaction_raw, Simulator};
 
-    use crate::{Conditions, Launcher};
+    use crate::Launcher;
 
     use super::*;
 
+    #[tokio::test]
+    async fn test_did_from_puzzle_and_solution() -> anyhow::Result<()> {
+        let sim = Simulator::new().await?;
+        let peer = sim.connect().await?;
+        let ctx = &mut SpendContext::new();
+
+        let sk = secret_key()?;
+        let pk = sk.public_key();
+
+        let puzzle_hash = StandardArgs::curry_tree_hash(pk).into();
+        let coin = sim.mint_coin(puzzle_hash, 1).await;
+
+        let (create_did, did, did_proof) =
+            Launcher::new(coin.coin_id(), 1).create_simple_did(ctx, pk)?;
+        ctx.spend_p2_coin(coin, pk, create_did)?;
+
+        let (new_did, _) = ctx.spend_standard_did(&did, did_proof, pk, Conditions::new())?;
+
+        test_transaction(
+            &peer,
+            ctx.take_spends(),
+            &[sk],
+            sim.config().genesis_challenge,
+        )
+        .await;
+
+        let height = sim
+            .coin_state(did.coin.coin_id())
+            .await
+            .and_then(|coin_state| coin_state.spent_height)
+            .expect("expected spent did");
+        let response = peer
+            .request_puzzle_and_solution(did.coin.coin_id(), height)
+            .await?;
+
+        let (child, child_coin) = Did::<()>::from_puzzle_and_solution(
+            ctx.allocator_mut(),
+            response.coin_name,
+            &response.puzzle,
+            &response.solution,
+        )?
+        .expect("expected child did");
+        assert_eq!(child_coin, new_did.coin);
+        assert_eq!(child.launcher_id, did.launcher_id);
+
+        // The parent of the DID isn't a DID itself.
+        let response = peer
+            .request_puzzle_and_solution(coin.coin_id(), height)
+            .await?;
+        assert!(Did::<()>::from_puzzle_and_solution(
+            ctx.allocator_mut(),
+            response.coin_name,
+            &response.puzzle,
+            &response.solution,
+        )?
+        .is_none());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_update_did_metadata() -> anyhow::Result<()> {
+        let sim = Simulator::new().await?;
+        let peer = sim.connect().await?;
+        let ctx = &mut SpendContext::new();
+
+        let sk = secret_key()?;
+        let pk = sk.public_key();
+
+        let puzzle_hash = StandardArgs::curry_tree_hash(pk).into();
+        let coin = sim.mint_coin(puzzle_hash, 1).await;
+
+        let (create_did, did, did_proof) =
+            Launcher::new(coin.coin_id(), 1).create_simple_did(ctx, pk)?;
+        ctx.spend_p2_coin(coin, pk, create_did)?;
+
+        let metadata = "profile".to_string();
+        let (did, did_proof) = did.update_metadata(ctx, did_proof, metadata.clone(), pk)?;
+        assert_eq!(did.metadata, metadata);
+
+        // Spending the updated DID reveals its metadata, so its child can be parsed.
+        let (new_did, _) = ctx.spend_standard_did(&did, did_proof, pk, Conditions::new())?;
+
+        let coin_spends = ctx.take_spends();
+        let did_spend = coin_spends
+            .iter()
+            .find(|cs| cs.coin == did.coin)
+            .expect("expected did spend")
+            .clone();
+
+        let child = Did::<String>::from_parent_spend(ctx.allocator_mut(), &did_spend)?
+            .expect("expected child did");
+        assert_eq!(child.metadata, metadata);
+        assert_eq!(child.coin, new_did.coin);
+
+        test_transaction(&peer, coin_spends, &[sk], sim.config().genesis_challenge).await;
+
+        let coin_state = sim
+            .coin_state(new_did.coin.coin_id())
+            .await
+            .expect("expected did coin");
+        assert_eq!(coin_state.coin, new_did.coin);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_rotate_did_key() -> anyhow::Result<()> {
+        let sim = Simulator::new().await?;
+        let peer = sim.connect().await?;
+        let ctx = &mut SpendContext::new();
+
+        let sk = secret_key()?;
+        let pk = sk.public_key();
+        let new_secret_key = sk.derive_unhardened(0);
+        let new_public_key = new_secret_key.public_key();
+
+        let puzzle_hash = StandardArgs::curry_tree_hash(pk).into();
+        let coin = sim.mint_coin(puzzle_hash, 1).await;
+
+        let (create_did, did, did_proof) =
+            Launcher::new(coin.coin_id(), 1).create_simple_did(ctx, pk)?;
+        ctx.spend_p2_coin(coin, pk, create_did)?;
+
+        let (did, did_proof) = did.rotate_key(ctx, did_proof, new_public_key, pk)?;
+        assert_eq!(
+            did.p2_puzzle_hash,
+            StandardArgs::curry_tree_hash(new_public_key)
+        );
+
+        test_transaction(
+            &peer,
+            ctx.take_spends(),
+            &[sk.clone()],
+            sim.config().genesis_challenge,
+        )
+        .await;
+
+        // The old key no longer controls the DID.
+        ctx.spend_standard_did(&did, did_proof, pk, Conditions::new())?;
+        let ack = test_transaction_raw(
+            &peer,
+            ctx.take_spends(),
+            &[sk],
+            sim.config().genesis_challenge,
+        )
+        .await?;
+        assert_eq!(ack.status, 3);
+
+        let (new_did, _) =
+            ctx.spend_standard_did(&did, did_proof, new_public_key, Conditions::new())?;
+        test_transaction(
+            &peer,
+            ctx.take_spends(),
+            &[new_secret_key],
+            sim.config().genesis_challenge,
+        )
+        .await;
+
+        let coin_state = sim
+            .coin_state(new_did.coin.coin_id())
+            .await
+            .expect("expected did coin");
+        assert_eq!(coin_state.coin, new_did.coin);
+
+        Ok(())
+    }
+
     #[tokio::test]
     async fn test_did_recreation() -> anyhow::Result<()> {
         let sim = Simulator::new().await?;
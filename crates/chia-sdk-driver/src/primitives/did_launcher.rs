@@ -35,7 +35,7 @@ impl Launcher {
 
         let inner_puzzle_hash = did.singleton_inner_puzzle_hash().into();
 
-        let (launch_singleton, eve_coin) = self.spend(ctx, inner_puzzle_hash, ())?;
+        let (launch_singleton, eve_coin) = self.spend_with_metadata(ctx, inner_puzzle_hash)?;
 
         let proof = Proof::Eve(EveProof {
             parent_coin_info: launcher_coin.parent_coin_info,
@@ -56,6 +56,52 @@ impl Launcher {
     where
         M: ToClvm<NodePtr> + FromClvm<NodePtr> + Clone + ToTreeHash,
         Self: Sized,
+    {
+        self.create_did_inner(
+            ctx,
+            recovery_did_list_hash,
+            num_verifications_required,
+            metadata,
+            synthetic_key,
+        )
+        .map_err(|error| error.context("creating DID"))
+    }
+
+    /// Creates a DID controlled by an arbitrary inner puzzle, such as a multisig, rather than the standard puzzle.
+    /// Unlike [`Launcher::create_did`], the eve DID is not spent, since only the caller knows how to solve its inner puzzle.
+    pub fn create_did_with_inner<M>(
+        self,
+        ctx: &mut SpendContext,
+        inner_puzzle: NodePtr,
+        inner_puzzle_hash: Bytes32,
+        recovery_did_list_hash: Bytes32,
+        num_verifications_required: u64,
+        metadata: M,
+    ) -> Result<(Conditions, Did<M>, Proof), DriverError>
+    where
+        M: ToClvm<NodePtr> + FromClvm<NodePtr> + Clone + ToTreeHash,
+    {
+        self.create_eve_did(
+            ctx,
+            inner_puzzle_hash,
+            Some(inner_puzzle),
+            recovery_did_list_hash,
+            num_verifications_required,
+            metadata,
+        )
+        .map_err(|error| DriverError::Spend(error).context("creating DID"))
+    }
+
+    fn create_did_inner<M>(
+        self,
+        ctx: &mut SpendContext,
+        recovery_did_list_hash: Bytes32,
+        num_verifications_required: u64,
+        metadata: M,
+        synthetic_key: PublicKey,
+    ) -> Result<(Conditions, Did<M>, Proof), DriverError>
+    where
+        M: ToClvm<NodePtr> + FromClvm<NodePtr> + Clone + ToTreeHash,
     {
         let inner_puzzle = CurriedProgram {
             program: ctx.standard_puzzle()?,
@@ -93,10 +139,11 @@ impl Launcher {
 
 #[cfg(test)]
 mod tests {
-    use crate::{Launcher, SpendContext};
+    use crate::{Conditions, Launcher, Spend, SpendContext};
 
     use chia_puzzles::standard::StandardArgs;
     use chia_sdk_test::{secret_key, test_transaction, Simulator};
+    use clvm_utils::tree_hash_atom;
 
     #[tokio::test]
     async fn test_create_did() -> anyhow::Result<()> {
@@ -132,4 +179,64 @@ mod tests {
 
         Ok(())
     }
+
+    #[tokio::test]
+    async fn test_create_did_with_inner() -> anyhow::Result<()> {
+        let sim = Simulator::new().await?;
+        let peer = sim.connect().await?;
+        let ctx = &mut SpendContext::new();
+
+        let sk = secret_key()?;
+        let pk = sk.public_key();
+
+        let puzzle_hash = StandardArgs::curry_tree_hash(pk).into();
+        let coin = sim.mint_coin(puzzle_hash, 1).await;
+
+        // This inner puzzle outputs its solution as conditions.
+        let inner_puzzle = ctx.alloc(&1)?;
+        let inner_puzzle_hash = ctx.tree_hash(inner_puzzle).into();
+
+        let (launch_singleton, did, did_proof) = Launcher::new(coin.coin_id(), 1)
+            .create_did_with_inner(
+                ctx,
+                inner_puzzle,
+                inner_puzzle_hash,
+                tree_hash_atom(&[]).into(),
+                1,
+                (),
+            )?;
+        assert_eq!(did.p2_puzzle, Some(inner_puzzle));
+        assert_eq!(did.p2_puzzle_hash, inner_puzzle_hash.into());
+
+        ctx.spend_p2_coin(coin, pk, launch_singleton)?;
+
+        let conditions = Conditions::new().create_hinted_coin(
+            did.compute_new_did_layer_puzzle_hash(inner_puzzle_hash.into())
+                .into(),
+            did.coin.amount,
+            inner_puzzle_hash,
+        );
+        let inner_solution = ctx.alloc(&conditions)?;
+
+        let (did_spend, child, _) =
+            did.spend(ctx, did_proof, Spend::new(inner_puzzle, inner_solution))?;
+        ctx.insert_coin_spend(did_spend)?;
+
+        test_transaction(
+            &peer,
+            ctx.take_spends(),
+            &[sk],
+            sim.config().genesis_challenge,
+        )
+        .await;
+
+        let coin_state = sim
+            .coin_state(child.coin.coin_id())
+            .await
+            .expect("expected did coin");
+        assert_eq!(coin_state.spent_height, None);
+        assert_eq!(child.p2_puzzle_hash, inner_puzzle_hash.into());
+
+        Ok(())
+    }
 }
@@ -0,0 +1,146 @@
+use chia_bls::PublicKey;
+use chia_protocol::Bytes32;
+use clvm_traits::{apply_constants, FromClvm, ToClvm};
+use clvm_utils::{ToTreeHash, TreeHash};
+
+/// The list of DIDs that can attest to the recovery of a DID.
+/// Only the tree hash of the list is curried into the DID puzzle, so it must be revealed when recovering.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct RecoveryList {
+    pub recovery_did_ids: Vec<Bytes32>,
+}
+
+impl RecoveryList {
+    pub fn new(recovery_did_ids: Vec<Bytes32>) -> Self {
+        Self { recovery_did_ids }
+    }
+
+    /// The `recovery_did_list_hash` that the DID puzzle is curried with.
+    pub fn hash(&self) -> Bytes32 {
+        self.recovery_did_ids.tree_hash().into()
+    }
+
+    /// Creates the DID inner puzzle solution for recovering the DID to a new inner puzzle hash.
+    /// The DID puzzle can't skip entries in the list, so there must be an attestation
+    /// for every recovery DID, in the same order.
+    pub fn recovery_solution(
+        &self,
+        my_amount: u64,
+        new_inner_puzzle_hash: Bytes32,
+        attestations: Vec<RecoveryAttestation>,
+        pubkey: PublicKey,
+        my_id: Bytes32,
+    ) -> DidRecoverySolution {
+        DidRecoverySolution {
+            my_amount,
+            new_inner_puzzle_hash,
+            attestations,
+            pubkey,
+            recovery_list_reveal: self.recovery_did_ids.clone(),
+            my_id,
+        }
+    }
+}
+
+impl ToTreeHash for RecoveryList {
+    fn tree_hash(&self) -> TreeHash {
+        self.recovery_did_ids.tree_hash()
+    }
+}
+
+/// The coin info of a recovery DID that attested to a recovery.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ToClvm, FromClvm)]
+#[clvm(list)]
+pub struct RecoveryAttestation {
+    pub parent_coin_id: Bytes32,
+    pub inner_puzzle_hash: Bytes32,
+    pub amount: u64,
+}
+
+impl RecoveryAttestation {
+    pub fn new(parent_coin_id: Bytes32, inner_puzzle_hash: Bytes32, amount: u64) -> Self {
+        Self {
+            parent_coin_id,
+            inner_puzzle_hash,
+            amount,
+        }
+    }
+}
+
+/// The solution to the DID inner puzzle in recovery mode.
+#[derive(ToClvm, FromClvm)]
+#[apply_constants]
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[clvm(list)]
+pub struct DidRecoverySolution {
+    #[clvm(constant = 0)]
+    pub mode: u8,
+    pub my_amount: u64,
+    pub new_inner_puzzle_hash: Bytes32,
+    pub attestations: Vec<RecoveryAttestation>,
+    pub pubkey: PublicKey,
+    pub recovery_list_reveal: Vec<Bytes32>,
+    pub my_id: Bytes32,
+}
+
+#[cfg(test)]
+mod tests {
+    use chia_protocol::Coin;
+    use chia_puzzles::standard::StandardArgs;
+    use clvm_utils::tree_hash_atom;
+
+    use crate::{Launcher, PuzzleLayer, SpendContext};
+
+    use super::*;
+
+    #[test]
+    fn test_recovery_list() -> anyhow::Result<()> {
+        let ctx = &mut SpendContext::new();
+
+        let pk = PublicKey::default();
+        let puzzle_hash = StandardArgs::curry_tree_hash(pk).into();
+        let parent = Coin::new(Bytes32::default(), puzzle_hash, 1);
+
+        assert_eq!(RecoveryList::default().hash(), tree_hash_atom(&[]).into());
+
+        let recovery_list = RecoveryList::new(vec![Bytes32::new([1; 32]), Bytes32::new([2; 32])]);
+        let ptr = ctx.alloc(&recovery_list.recovery_did_ids)?;
+        assert_eq!(recovery_list.hash(), ctx.tree_hash(ptr).into());
+
+        let (_, did, _) =
+            Launcher::new(parent.coin_id(), 1).create_did(ctx, recovery_list.hash(), 1, (), pk)?;
+        assert_eq!(did.recovery_did_list_hash, recovery_list.hash());
+
+        // The DID puzzle accepts the revealed list in recovery mode.
+        let p2_puzzle = ctx.standard_puzzle()?;
+        let did_puzzle = did
+            .get_layered_object(Some(p2_puzzle))
+            .inner_puzzle
+            .construct_puzzle(ctx)?;
+
+        let attestation = RecoveryAttestation::new(Bytes32::new([3; 32]), Bytes32::new([4; 32]), 1);
+        let solution = recovery_list.recovery_solution(
+            did.coin.amount,
+            Bytes32::new([5; 32]),
+            vec![attestation, attestation],
+            pk,
+            did.coin.coin_id(),
+        );
+        let solution_ptr = ctx.alloc(&solution)?;
+        assert!(ctx.run(did_puzzle, solution_ptr).is_ok());
+
+        // A different list doesn't match the curried hash.
+        let other_list = RecoveryList::new(vec![Bytes32::new([1; 32])]);
+        let solution = other_list.recovery_solution(
+            did.coin.amount,
+            Bytes32::new([5; 32]),
+            vec![attestation],
+            pk,
+            did.coin.coin_id(),
+        );
+        let solution_ptr = ctx.alloc(&solution)?;
+        assert!(ctx.run(did_puzzle, solution_ptr).is_err());
+
+        Ok(())
+    }
+}
@@ -8,7 +8,7 @@ use clvmr::{
     Allocator,
 };
 
-use crate::{Conditions, SpendContext, SpendError};
+use crate::{Conditions, DriverError, SpendContext};
 
 use super::Launcher;
 
@@ -59,7 +59,15 @@ impl IntermediateLauncher {
     }
 
     /// Spends the intermediate coin to create the launcher coin.
-    pub fn create(self, ctx: &mut SpendContext) -> Result<Launcher, SpendError> {
+    /// Fails if the mint number is out of range, since the launcher id could collide with another mint.
+    pub fn create(self, ctx: &mut SpendContext) -> Result<Launcher, DriverError> {
+        if self.mint_number >= self.mint_total {
+            return Err(DriverError::InvalidLauncherIndex {
+                index: self.mint_number,
+                total: self.mint_total,
+            });
+        }
+
         let mut parent = Conditions::new();
 
         let intermediate_puzzle = ctx.nft_intermediate_launcher()?;
@@ -78,7 +86,7 @@ impl IntermediateLauncher {
             self.intermediate_coin,
             puzzle_reveal,
             solution,
-        ));
+        ))?;
 
         let mut index_message = Sha256::new();
         index_message.update(usize_to_bytes(self.mint_number));
@@ -99,3 +107,38 @@ fn usize_to_bytes(value: usize) -> Vec<u8> {
     let atom = allocator.new_number(value.into()).unwrap();
     allocator.atom(atom).as_ref().to_vec()
 }
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::*;
+
+    #[test]
+    fn test_invalid_launcher_index() {
+        let ctx = &mut SpendContext::new();
+
+        for (index, total) in [(0, 0), (1, 1), (5, 3)] {
+            assert!(matches!(
+                IntermediateLauncher::new(Bytes32::default(), index, total).create(ctx),
+                Err(DriverError::InvalidLauncherIndex { index: i, total: t }) if i == index && t == total
+            ));
+        }
+
+        assert!(ctx.spends().is_empty());
+    }
+
+    #[test]
+    fn test_distinct_launcher_ids() -> anyhow::Result<()> {
+        let ctx = &mut SpendContext::new();
+
+        let mut launcher_ids = HashSet::new();
+
+        for index in 0..10 {
+            let launcher = IntermediateLauncher::new(Bytes32::default(), index, 10).create(ctx)?;
+            assert!(launcher_ids.insert(launcher.coin().coin_id()));
+        }
+
+        Ok(())
+    }
+}
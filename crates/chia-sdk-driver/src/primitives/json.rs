@@ -0,0 +1,231 @@
+//! JSON representations of [`Nft`] and [`Did`].
+//!
+//! The p2 puzzle reveal is excluded, since it's a [`NodePtr`](clvmr::NodePtr) that's only meaningful
+//! with the allocator it came from. Only the p2 puzzle hash is serialized, so deserialized values
+//! have no p2 puzzle until it's parsed again.
+
+use chia_protocol::{Bytes32, Coin};
+use clvm_utils::TreeHash;
+use hex::{decode, encode};
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::{Did, Nft};
+
+#[derive(Serialize, Deserialize)]
+struct JsonCoin {
+    parent_coin_info: String,
+    puzzle_hash: String,
+    amount: u64,
+}
+
+#[derive(Serialize, Deserialize)]
+struct JsonNft<M> {
+    coin: JsonCoin,
+    launcher_id: String,
+    metadata: M,
+    current_owner: Option<String>,
+    royalty_puzzle_hash: String,
+    royalty_percentage: u16,
+    p2_puzzle_hash: String,
+}
+
+#[derive(Serialize, Deserialize)]
+struct JsonDid<M> {
+    coin: JsonCoin,
+    launcher_id: String,
+    recovery_did_list_hash: String,
+    num_verifications_required: u64,
+    metadata: M,
+    p2_puzzle_hash: String,
+}
+
+fn encode_bytes32(value: impl Into<Bytes32>) -> String {
+    format!("0x{}", encode(value.into()))
+}
+
+fn decode_bytes32<E>(value: &str) -> Result<Bytes32, E>
+where
+    E: de::Error,
+{
+    let bytes = decode(value.strip_prefix("0x").unwrap_or(value)).map_err(E::custom)?;
+    let bytes: [u8; 32] = bytes
+        .try_into()
+        .map_err(|_| E::custom("expected 32 bytes"))?;
+    Ok(Bytes32::new(bytes))
+}
+
+impl From<&Coin> for JsonCoin {
+    fn from(coin: &Coin) -> Self {
+        Self {
+            parent_coin_info: encode_bytes32(coin.parent_coin_info),
+            puzzle_hash: encode_bytes32(coin.puzzle_hash),
+            amount: coin.amount,
+        }
+    }
+}
+
+impl JsonCoin {
+    fn decode<E>(&self) -> Result<Coin, E>
+    where
+        E: de::Error,
+    {
+        Ok(Coin::new(
+            decode_bytes32(&self.parent_coin_info)?,
+            decode_bytes32(&self.puzzle_hash)?,
+            self.amount,
+        ))
+    }
+}
+
+impl<M> Serialize for Nft<M>
+where
+    M: Serialize,
+{
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        JsonNft {
+            coin: JsonCoin::from(&self.coin),
+            launcher_id: encode_bytes32(self.launcher_id),
+            metadata: &self.metadata,
+            current_owner: self.current_owner.map(encode_bytes32),
+            royalty_puzzle_hash: encode_bytes32(self.royalty_puzzle_hash),
+            royalty_percentage: self.royalty_percentage,
+            p2_puzzle_hash: encode_bytes32(self.p2_puzzle_hash),
+        }
+        .serialize(serializer)
+    }
+}
+
+impl<'de, M> Deserialize<'de> for Nft<M>
+where
+    M: Deserialize<'de>,
+{
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let json = JsonNft::<M>::deserialize(deserializer)?;
+
+        Ok(Self {
+            coin: json.coin.decode()?,
+            launcher_id: decode_bytes32(&json.launcher_id)?,
+            metadata: json.metadata,
+            current_owner: json
+                .current_owner
+                .as_deref()
+                .map(decode_bytes32)
+                .transpose()?,
+            royalty_puzzle_hash: decode_bytes32(&json.royalty_puzzle_hash)?,
+            royalty_percentage: json.royalty_percentage,
+            p2_puzzle_hash: TreeHash::from(decode_bytes32::<D::Error>(&json.p2_puzzle_hash)?),
+            p2_puzzle: None,
+        })
+    }
+}
+
+impl<M> Serialize for Did<M>
+where
+    M: Serialize,
+{
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        JsonDid {
+            coin: JsonCoin::from(&self.coin),
+            launcher_id: encode_bytes32(self.launcher_id),
+            recovery_did_list_hash: encode_bytes32(self.recovery_did_list_hash),
+            num_verifications_required: self.num_verifications_required,
+            metadata: &self.metadata,
+            p2_puzzle_hash: encode_bytes32(self.p2_puzzle_hash),
+        }
+        .serialize(serializer)
+    }
+}
+
+impl<'de, M> Deserialize<'de> for Did<M>
+where
+    M: Deserialize<'de>,
+{
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let json = JsonDid::<M>::deserialize(deserializer)?;
+
+        Ok(Self {
+            coin: json.coin.decode()?,
+            launcher_id: decode_bytes32(&json.launcher_id)?,
+            recovery_did_list_hash: decode_bytes32(&json.recovery_did_list_hash)?,
+            num_verifications_required: json.num_verifications_required,
+            metadata: json.metadata,
+            p2_puzzle_hash: TreeHash::from(decode_bytes32::<D::Error>(&json.p2_puzzle_hash)?),
+            p2_puzzle: None,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chia_bls::PublicKey;
+    use chia_puzzles::standard::StandardArgs;
+    use chia_sdk_types::conditions::NewNftOwner;
+
+    use crate::{Launcher, NftMint, SpendContext};
+
+    use super::*;
+
+    #[test]
+    fn test_nft_did_json() -> anyhow::Result<()> {
+        let ctx = &mut SpendContext::new();
+
+        let pk = PublicKey::default();
+        let puzzle_hash = StandardArgs::curry_tree_hash(pk).into();
+        let parent = Coin::new(Bytes32::default(), puzzle_hash, 2);
+
+        let (_, did, _) = Launcher::new(parent.coin_id(), 1).create_simple_did(ctx, pk)?;
+
+        let (_, nft, _) = Launcher::new(did.coin.coin_id(), 1).mint_nft(
+            ctx,
+            NftMint {
+                metadata: "metadata".to_string(),
+                royalty_puzzle_hash: Bytes32::new([1; 32]),
+                royalty_percentage: 300,
+                puzzle_hash,
+                owner: NewNftOwner {
+                    did_id: Some(did.launcher_id),
+                    trade_prices: Vec::new(),
+                    did_inner_puzzle_hash: Some(did.singleton_inner_puzzle_hash().into()),
+                },
+            },
+        )?;
+
+        let json = serde_json::to_string(&nft)?;
+        let parsed = serde_json::from_str::<Nft<String>>(&json)?;
+        assert_eq!(serde_json::to_string(&parsed)?, json);
+        assert_eq!(parsed.coin, nft.coin);
+        assert_eq!(parsed.p2_puzzle_hash, nft.p2_puzzle_hash);
+        assert!(parsed.p2_puzzle.is_none());
+
+        let json = serde_json::to_value(&nft)?;
+        assert_eq!(
+            json["launcher_id"],
+            format!("0x{}", encode(nft.launcher_id))
+        );
+        assert_eq!(
+            json["current_owner"],
+            format!("0x{}", encode(did.launcher_id))
+        );
+
+        let json = serde_json::to_string(&did)?;
+        let parsed = serde_json::from_str::<Did<()>>(&json)?;
+        assert_eq!(serde_json::to_string(&parsed)?, json);
+        assert_eq!(parsed.coin, did.coin);
+        assert_eq!(parsed.p2_puzzle_hash, did.p2_puzzle_hash);
+        assert!(parsed.p2_puzzle.is_none());
+
+        Ok(())
+    }
+}
@@ -1,8 +1,11 @@
 #![allow(clippy::missing_const_for_fn)]
 
-use chia_protocol::{Bytes32, Coin, CoinSpend, Program};
-use chia_puzzles::singleton::{
-    LauncherSolution, SingletonArgs, SINGLETON_LAUNCHER_PUZZLE, SINGLETON_LAUNCHER_PUZZLE_HASH,
+use chia_protocol::{Bytes, Bytes32, Coin, CoinSpend, Program};
+use chia_puzzles::{
+    singleton::{
+        LauncherSolution, SingletonArgs, SINGLETON_LAUNCHER_PUZZLE, SINGLETON_LAUNCHER_PUZZLE_HASH,
+    },
+    EveProof, Proof,
 };
 use clvm_traits::ToClvm;
 use clvmr::NodePtr;
@@ -17,12 +20,24 @@ use crate::{Conditions, SpendContext, SpendError};
 pub struct Launcher {
     coin: Coin,
     conditions: Conditions,
+    metadata: Vec<(Bytes, Bytes)>,
 }
 
 impl Launcher {
     /// Creates a new [`Launcher`] with the specified launcher coin and parent spend conditions.
     pub fn from_coin(coin: Coin, conditions: Conditions) -> Self {
-        Self { coin, conditions }
+        Self {
+            coin,
+            conditions,
+            metadata: Vec::new(),
+        }
+    }
+
+    /// Commits the key value pairs to the launcher solution when minting, such as with [`Launcher::mint_eve_nft`].
+    /// They are included in the launcher announcement, which the returned conditions assert.
+    pub fn with_metadata(mut self, metadata: Vec<(Bytes, Bytes)>) -> Self {
+        self.metadata = metadata;
+        self
     }
 
     /// The parent coin specified when constructing the [`Launcher`] will create the launcher coin.
@@ -133,7 +148,7 @@ impl Launcher {
             self.coin,
             Program::from(SINGLETON_LAUNCHER_PUZZLE.to_vec()),
             solution,
-        ));
+        ))?;
 
         let singleton_coin =
             Coin::new(self.coin.coin_id(), singleton_puzzle_hash, self.coin.amount);
@@ -144,4 +159,101 @@ impl Launcher {
             singleton_coin,
         ))
     }
+
+    /// Spends the launcher coin to create the eve singleton, with the key value pairs from [`Launcher::with_metadata`].
+    pub(crate) fn spend_with_metadata(
+        mut self,
+        ctx: &mut SpendContext,
+        singleton_inner_puzzle_hash: Bytes32,
+    ) -> Result<(Conditions, Coin), SpendError> {
+        let metadata = std::mem::take(&mut self.metadata);
+        self.spend(ctx, singleton_inner_puzzle_hash, metadata)
+    }
+
+    /// Spends the launcher coin to create an eve singleton with an arbitrary inner puzzle.
+    /// Returns the eve coin along with the proof needed to spend it.
+    pub fn mint_eve_singleton(
+        self,
+        ctx: &mut SpendContext,
+        inner_puzzle_hash: Bytes32,
+    ) -> Result<(Conditions, Coin, Proof), SpendError> {
+        let launcher_coin = self.coin();
+
+        let (launch_singleton, eve_coin) = self.spend_with_metadata(ctx, inner_puzzle_hash)?;
+
+        let proof = Proof::Eve(EveProof {
+            parent_coin_info: launcher_coin.parent_coin_info,
+            amount: launcher_coin.amount,
+        });
+
+        Ok((launch_singleton, eve_coin, proof))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chia_puzzles::standard::StandardArgs;
+    use chia_sdk_test::{secret_key, test_transaction, Simulator};
+    use chia_sdk_types::conditions::CreateCoin;
+    use clvm_utils::ToTreeHash;
+
+    use crate::{OuterPuzzleLayer, SingletonLayer, SingletonLayerSolution, TransparentLayer};
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_mint_eve_singleton() -> anyhow::Result<()> {
+        let sim = Simulator::new().await?;
+        let peer = sim.connect().await?;
+        let ctx = &mut SpendContext::new();
+
+        let sk = secret_key()?;
+        let pk = sk.public_key();
+
+        let puzzle_hash = StandardArgs::curry_tree_hash(pk).into();
+        let coin = sim.mint_coin(puzzle_hash, 1).await;
+
+        // The inner puzzle `1` returns its solution as the output conditions.
+        let inner_puzzle = ctx.alloc(&1)?;
+        let inner_puzzle_hash = ctx.tree_hash(inner_puzzle);
+
+        let launcher = Launcher::new(coin.coin_id(), 1);
+        let launcher_id = launcher.coin().coin_id();
+        let (launch_singleton, eve_coin, proof) =
+            launcher.mint_eve_singleton(ctx, inner_puzzle_hash.into())?;
+
+        ctx.spend_p2_coin(coin, pk, launch_singleton)?;
+
+        let layer = SingletonLayer {
+            launcher_id,
+            inner_puzzle: TransparentLayer::<false>::new(inner_puzzle_hash, Some(inner_puzzle)),
+        };
+        assert_eq!(eve_coin.puzzle_hash, layer.tree_hash().into());
+
+        let inner_solution = ctx.alloc(&[CreateCoin::new(inner_puzzle_hash.into(), 1)])?;
+        let eve_spend = layer.solve(
+            ctx,
+            eve_coin,
+            SingletonLayerSolution {
+                lineage_proof: proof,
+                amount: eve_coin.amount,
+                inner_solution,
+            },
+        )?;
+        ctx.insert_coin_spend(eve_spend)?;
+
+        test_transaction(
+            &peer,
+            ctx.take_spends(),
+            &[sk],
+            sim.config().genesis_challenge,
+        )
+        .await;
+
+        let child = Coin::new(eve_coin.coin_id(), eve_coin.puzzle_hash, 1);
+        assert!(sim.coin_state(eve_coin.coin_id()).await.is_some());
+        assert!(sim.coin_state(child.coin_id()).await.is_some());
+
+        Ok(())
+    }
 }
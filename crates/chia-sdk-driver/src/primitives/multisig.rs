@@ -0,0 +1,205 @@
+use chia_bls::PublicKey;
+use chia_protocol::Coin;
+use clvm_traits::{clvm_quote, FromClvm, ToClvm};
+use clvm_utils::{CurriedProgram, ToTreeHash, TreeHash};
+use clvmr::NodePtr;
+use hex_literal::hex;
+
+use crate::{Conditions, Spend, SpendContext, SpendError};
+
+/// The multisig puzzle reveal, which can be found in `puzzles/multisig.clsp.hex`.
+pub const MULTISIG_PUZZLE: [u8; 341] = hex!(
+    "
+    ff02ffff01ff02ff0effff04ff02ffff04ff05ffff04ff0bffff04ff17ffff04
+    ffff02ff0affff04ff02ffff04ff2fff80808080ffff04ffff02ff2fff5f80ff
+    8080808080808080ffff04ffff01ff32ffff02ffff03ffff07ff0580ffff01ff
+    0bffff0102ffff02ff0affff04ff02ffff04ff09ff80808080ffff02ff0affff
+    04ff02ffff04ff0dff8080808080ffff01ff0bffff0101ff058080ff0180ff02
+    ffff03ff05ffff01ff02ffff03ff27ffff01ff02ff0effff04ff02ffff04ff0d
+    ffff04ffff11ff0bffff010180ffff04ff37ffff04ff2fffff04ffff04ffff04
+    ff04ffff04ff09ffff04ff2fff80808080ff5f80ff8080808080808080ffff01
+    ff02ff0effff04ff02ffff04ff0dffff04ff0bffff04ff37ffff04ff2fffff04
+    ff5fff808080808080808080ff0180ffff01ff02ffff03ffff15ff0bff8080ff
+    ff01ff0880ffff015f80ff018080ff0180ff018080
+    "
+);
+
+/// The tree hash of [`MULTISIG_PUZZLE`].
+pub const MULTISIG_PUZZLE_HASH: TreeHash = TreeHash::new(hex!(
+    "b5e8667cbbe007acd599f95bb85ed3b75a4aeaf3d8ae059585598ca8e924d690"
+));
+
+/// The curried arguments of the multisig puzzle.
+#[derive(Debug, Clone, PartialEq, Eq, ToClvm, FromClvm)]
+#[clvm(curry)]
+pub struct MultisigArgs {
+    pub public_keys: Vec<PublicKey>,
+    pub threshold: usize,
+}
+
+/// The solution to the multisig puzzle.
+/// Each selector indicates whether the public key at the same index is signing the delegated puzzle.
+#[derive(Debug, Clone, PartialEq, Eq, ToClvm, FromClvm)]
+#[clvm(list)]
+pub struct MultisigSolution<P, S> {
+    pub selectors: Vec<bool>,
+    pub delegated_puzzle: P,
+    pub delegated_solution: S,
+}
+
+/// A p2 puzzle which requires `threshold` of the public keys to sign the delegated puzzle with `AGG_SIG_ME`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[must_use]
+pub struct Multisig {
+    pub public_keys: Vec<PublicKey>,
+    pub threshold: usize,
+}
+
+impl Multisig {
+    pub fn new(public_keys: Vec<PublicKey>, threshold: usize) -> Self {
+        Self {
+            public_keys,
+            threshold,
+        }
+    }
+
+    /// The puzzle hash of coins locked by the multisig.
+    pub fn puzzle_hash(&self) -> TreeHash {
+        CurriedProgram {
+            program: MULTISIG_PUZZLE_HASH,
+            args: self.args(),
+        }
+        .tree_hash()
+    }
+
+    /// Creates an inner spend which outputs the conditions, signed by each of the given public keys.
+    /// Every signer must sign the resulting `AGG_SIG_ME` condition for the spend to be valid.
+    pub fn inner_spend(
+        &self,
+        ctx: &mut SpendContext,
+        signers: &[PublicKey],
+        conditions: Conditions,
+    ) -> Result<Spend, SpendError> {
+        let multisig_puzzle = ctx.puzzle(MULTISIG_PUZZLE_HASH, &MULTISIG_PUZZLE)?;
+
+        let puzzle = ctx.alloc(&CurriedProgram {
+            program: multisig_puzzle,
+            args: self.args(),
+        })?;
+
+        let solution = ctx.alloc(&MultisigSolution {
+            selectors: self
+                .public_keys
+                .iter()
+                .map(|public_key| signers.contains(public_key))
+                .collect(),
+            delegated_puzzle: clvm_quote!(conditions),
+            delegated_solution: NodePtr::NIL,
+        })?;
+
+        Ok(Spend::new(puzzle, solution))
+    }
+
+    /// Spends a coin locked by the multisig, outputting the conditions.
+    pub fn spend(
+        &self,
+        ctx: &mut SpendContext,
+        coin: Coin,
+        signers: &[PublicKey],
+        conditions: Conditions,
+    ) -> Result<(), SpendError> {
+        let spend = self.inner_spend(ctx, signers, conditions)?;
+        ctx.spend(coin, spend)
+    }
+
+    fn args(&self) -> MultisigArgs {
+        MultisigArgs {
+            public_keys: self.public_keys.clone(),
+            threshold: self.threshold,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chia_bls::{DerivableKey, SecretKey, Signature};
+    use chia_protocol::SpendBundle;
+    use chia_sdk_signer::RequiredSignature;
+    use chia_sdk_test::{secret_key, Simulator};
+    use clvmr::Allocator;
+
+    use super::*;
+
+    #[test]
+    fn test_multisig_puzzle_hash() -> anyhow::Result<()> {
+        let mut ctx = SpendContext::new();
+        let puzzle = ctx.puzzle(MULTISIG_PUZZLE_HASH, &MULTISIG_PUZZLE)?;
+        assert_eq!(ctx.tree_hash(puzzle), MULTISIG_PUZZLE_HASH);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_multisig() -> anyhow::Result<()> {
+        let sim = Simulator::new().await?;
+        let peer = sim.connect().await?;
+        let ctx = &mut SpendContext::new();
+
+        let secret_keys: Vec<SecretKey> = (0..3)
+            .map(|index| Ok(secret_key()?.derive_unhardened(index)))
+            .collect::<anyhow::Result<_>>()?;
+        let public_keys: Vec<PublicKey> = secret_keys.iter().map(SecretKey::public_key).collect();
+
+        let multisig = Multisig::new(public_keys.clone(), 2);
+        let puzzle_hash = multisig.puzzle_hash().into();
+        let coin = sim.mint_coin(puzzle_hash, 1).await;
+
+        // A single signer isn't enough to satisfy the threshold.
+        multisig.spend(
+            ctx,
+            coin,
+            &public_keys[..1],
+            Conditions::new().create_coin(puzzle_hash, 1),
+        )?;
+
+        let ack = peer
+            .send_transaction(SpendBundle::new(ctx.take_spends(), Signature::default()))
+            .await?;
+        assert_eq!(ack.status, 3);
+
+        multisig.spend(
+            ctx,
+            coin,
+            &public_keys[..2],
+            Conditions::new().create_coin(puzzle_hash, 1),
+        )?;
+        let coin_spends = ctx.take_spends();
+
+        let required_signatures = RequiredSignature::from_coin_spends(
+            &mut Allocator::new(),
+            &coin_spends,
+            sim.config().genesis_challenge,
+        )?;
+        assert_eq!(required_signatures.len(), 2);
+
+        // Each party signs separately, and the signatures are combined before broadcast.
+        let mut signature =
+            RequiredSignature::sign_partial(&required_signatures, &secret_keys[..1]);
+
+        let ack = peer
+            .send_transaction(SpendBundle::new(coin_spends.clone(), signature.clone()))
+            .await?;
+        assert_eq!(ack.status, 3);
+
+        signature += &RequiredSignature::sign_partial(&required_signatures, &secret_keys[1..2]);
+
+        let ack = peer
+            .send_transaction(SpendBundle::new(coin_spends, signature))
+            .await?;
+        assert_eq!(ack.status, 1);
+
+        let child = Coin::new(coin.coin_id(), puzzle_hash, 1);
+        assert!(sim.coin_state(child.coin_id()).await.is_some());
+
+        Ok(())
+    }
+}
@@ -1,12 +1,17 @@
 use chia_bls::PublicKey;
 use chia_protocol::{Bytes32, Coin, CoinSpend, Program};
 use chia_puzzles::{
-    nft::{NftOwnershipLayerSolution, NftStateLayerSolution},
+    nft::{
+        NftOwnershipLayerArgs, NftOwnershipLayerSolution, NftRoyaltyTransferPuzzleArgs,
+        NftStateLayerArgs, NftStateLayerSolution, NFT_OWNERSHIP_LAYER_PUZZLE_HASH,
+        NFT_STATE_LAYER_PUZZLE_HASH,
+    },
+    singleton::{SingletonArgs, SINGLETON_TOP_LAYER_PUZZLE_HASH},
     standard::DEFAULT_HIDDEN_PUZZLE_HASH,
     LineageProof, Proof,
 };
-use chia_sdk_types::conditions::{Condition, CreateCoin, NewNftOwner};
-use clvm_traits::{clvm_list, FromClvm, FromNodePtr, ToClvm, ToNodePtr};
+use chia_sdk_types::conditions::{Condition, CreateCoin, NewNftOwner, NftTradePrice};
+use clvm_traits::{clvm_list, FromClvm, ToClvm, ToNodePtr};
 use clvm_utils::{tree_hash, ToTreeHash, TreeHash};
 use clvmr::{
     sha2::{Digest, Sha256},
@@ -14,8 +19,9 @@ use clvmr::{
 };
 
 use crate::{
-    Conditions, DriverError, NftOwnershipLayer, NftStateLayer, PuzzleLayer, SingletonLayer,
-    SingletonLayerSolution, Spend, SpendContext, TransparentLayer,
+    Conditions, Did, DriverError, NftMint, NftOwnershipLayer, NftStateLayer, OuterPuzzleLayer,
+    Puzzle, PuzzleLayer, SingletonLayer, SingletonLayerSolution, Spend, SpendContext,
+    TransparentLayer,
 };
 
 #[derive(Debug, Clone, Copy)]
@@ -35,6 +41,7 @@ pub struct Nft<M = NodePtr> {
 
     // innermost (owner) layer
     pub p2_puzzle_hash: TreeHash,
+    /// The revealed p2 puzzle, if known.
     pub p2_puzzle: Option<NodePtr>,
 }
 
@@ -77,6 +84,48 @@ where
         self
     }
 
+    /// Extracts the trade prices from the new owner condition of an NFT spend.
+    /// Returns [`None`] if the spend isn't an NFT, or if it doesn't have a new owner condition.
+    /// The transfer program isn't checked, since the trade prices are output by the inner puzzle.
+    pub fn parse_trade_prices(
+        allocator: &mut Allocator,
+        cs: &CoinSpend,
+    ) -> Result<Option<Vec<NftTradePrice>>, DriverError> {
+        let puzzle = cs
+            .puzzle_reveal
+            .to_node_ptr(allocator)
+            .map_err(DriverError::ToClvm)?;
+        let solution = cs
+            .solution
+            .to_node_ptr(allocator)
+            .map_err(DriverError::ToClvm)?;
+
+        // The ownership layer is left unparsed, so that it doesn't need to use the standard transfer program.
+        let Some(layers) = SingletonLayer::<NftStateLayer<NodePtr, TransparentLayer>>::from_puzzle(
+            allocator, puzzle,
+        )?
+        else {
+            return Ok(None);
+        };
+
+        let ownership_puzzle = layers
+            .inner_puzzle
+            .inner_puzzle
+            .puzzle
+            .ok_or(DriverError::MissingPuzzle)?;
+
+        let solution = SingletonLayerSolution::<NftStateLayerSolution<NodePtr>>::from_clvm(
+            allocator, solution,
+        )
+        .map_err(DriverError::FromClvm)?;
+
+        NftOwnershipLayer::<TransparentLayer>::trade_prices_from_spend(
+            allocator,
+            ownership_puzzle,
+            solution.inner_solution.inner_solution,
+        )
+    }
+
     pub fn from_parent_spend(
         allocator: &mut Allocator,
         cs: &CoinSpend,
@@ -84,12 +133,28 @@ where
     where
         M: ToTreeHash,
     {
-        let puzzle_ptr = cs
-            .puzzle_reveal
-            .to_node_ptr(allocator)
-            .map_err(DriverError::ToClvm)?;
-        let solution_ptr = cs
-            .solution
+        Ok(Self::from_puzzle_and_solution(
+            allocator,
+            cs.coin.coin_id(),
+            &cs.puzzle_reveal,
+            &cs.solution,
+        )?
+        .map(|(nft, _)| nft))
+    }
+
+    /// Parses the child NFT from the puzzle and solution of its parent, such as those returned by a peer.
+    /// Returns [`None`] if the parent isn't an NFT.
+    pub fn from_puzzle_and_solution(
+        allocator: &mut Allocator,
+        parent_coin_id: Bytes32,
+        puzzle: &Program,
+        solution: &Program,
+    ) -> Result<Option<(Self, Coin)>, DriverError>
+    where
+        M: ToTreeHash,
+    {
+        let puzzle_ptr = puzzle.to_node_ptr(allocator).map_err(DriverError::ToClvm)?;
+        let solution_ptr = solution
             .to_node_ptr(allocator)
             .map_err(DriverError::ToClvm)?;
 
@@ -99,10 +164,15 @@ where
             solution_ptr,
         )?;
 
-        match res {
-            None => Ok(None),
-            Some(res) => Ok(Some(Nft {
-                coin: Coin::new(cs.coin.coin_id(), res.tree_hash().into(), 1),
+        let Some(res) = res else {
+            return Ok(None);
+        };
+
+        let coin = Coin::new(parent_coin_id, res.tree_hash().into(), 1);
+
+        Ok(Some((
+            Nft {
+                coin,
                 launcher_id: res.launcher_id,
                 metadata: res.inner_puzzle.metadata,
                 current_owner: res.inner_puzzle.inner_puzzle.current_owner,
@@ -110,8 +180,9 @@ where
                 royalty_percentage: res.inner_puzzle.inner_puzzle.royalty_percentage,
                 p2_puzzle_hash: res.inner_puzzle.inner_puzzle.inner_puzzle.puzzle_hash,
                 p2_puzzle: res.inner_puzzle.inner_puzzle.inner_puzzle.puzzle,
-            })),
-        }
+            },
+            coin,
+        )))
     }
 
     pub fn from_puzzle(
@@ -179,12 +250,9 @@ where
     {
         let thing = self.get_layered_object(Some(inner_spend.puzzle()));
 
-        let puzzle_ptr = thing.construct_puzzle(ctx)?;
-        let puzzle =
-            Program::from_node_ptr(ctx.allocator(), puzzle_ptr).map_err(DriverError::FromClvm)?;
-
-        let solution_ptr = thing.construct_solution(
+        let cs = thing.solve(
             ctx,
+            self.coin,
             SingletonLayerSolution {
                 lineage_proof,
                 amount: self.coin.amount,
@@ -195,14 +263,6 @@ where
                 },
             },
         )?;
-        let solution =
-            Program::from_node_ptr(ctx.allocator(), solution_ptr).map_err(DriverError::FromClvm)?;
-
-        let cs = CoinSpend {
-            coin: self.coin,
-            puzzle_reveal: puzzle,
-            solution,
-        };
         let lineage_proof = thing.lineage_proof_for_child(self.coin.parent_coin_info, 1);
         Ok((
             cs.clone(),
@@ -263,13 +323,81 @@ where
             .p2_spend(ctx, owner_synthetic_key)
             .map_err(DriverError::Spend)?;
 
-        let did_conditions = Conditions::new().assert_raw_puzzle_announcement(
-            did_puzzle_assertion(self.coin.puzzle_hash, new_did_owner),
-        );
+        // The new DID owner also announces the launcher id, which the transfer program asserts if the owner changes.
+        let did_conditions = Conditions::new()
+            .assert_raw_puzzle_announcement(did_puzzle_assertion(
+                self.coin.puzzle_hash,
+                new_did_owner,
+            ))
+            .create_puzzle_announcement(self.launcher_id.to_vec().into());
 
         let (cs, new_nft, lineage_proof) = self.spend(ctx, lineage_proof, inner_spend)?;
         Ok((cs, did_conditions, new_nft, lineage_proof))
     }
+
+    /// Assigns the NFT to a DID without changing its owner puzzle hash.
+    /// The DID must be spent in the same transaction with the returned conditions.
+    pub fn assign_did<N>(
+        &self,
+        ctx: &mut SpendContext,
+        lineage_proof: Proof,
+        owner_synthetic_key: PublicKey,
+        did: &Did<N>,
+    ) -> Result<(Conditions, Nft<M>, Proof), DriverError>
+    where
+        M: Clone + ToTreeHash,
+        N: ToClvm<NodePtr> + FromClvm<NodePtr> + Clone + ToTreeHash,
+    {
+        let new_did_owner = NewNftOwner::new(
+            Some(did.launcher_id),
+            Vec::new(),
+            Some(did.singleton_inner_puzzle_hash().into()),
+        );
+
+        let (cs, did_conditions, new_nft, lineage_proof) = self.transfer_to_did(
+            ctx,
+            lineage_proof,
+            owner_synthetic_key,
+            self.p2_puzzle_hash.into(),
+            &new_did_owner,
+            Conditions::new(),
+        )?;
+        ctx.insert_coin_spend(cs)?;
+
+        Ok((did_conditions, new_nft, lineage_proof))
+    }
+
+    /// Removes the DID the NFT is assigned to without changing its owner puzzle hash.
+    /// Unlike assigning a DID, this doesn't require a DID spend.
+    pub fn unassign_did(
+        &self,
+        ctx: &mut SpendContext,
+        lineage_proof: Proof,
+        owner_synthetic_key: PublicKey,
+    ) -> Result<(Nft<M>, Proof), DriverError>
+    where
+        M: Clone + ToTreeHash,
+    {
+        let (cs, _, new_nft, lineage_proof) = self.transfer_to_did(
+            ctx,
+            lineage_proof,
+            owner_synthetic_key,
+            self.p2_puzzle_hash.into(),
+            &NewNftOwner::default(),
+            Conditions::new(),
+        )?;
+        ctx.insert_coin_spend(cs)?;
+
+        Ok((new_nft, lineage_proof))
+    }
+}
+
+/// Calculates the royalty owed on a trade from a percentage in basis points, along with the remainder.
+/// The royalty is rounded down like in the royalty transfer program, and never exceeds the total.
+pub fn calculate_royalty(total: u64, percentage: u16) -> (u64, u64) {
+    let royalty = u128::from(total) * u128::from(percentage) / 10_000;
+    let royalty = u64::try_from(royalty).unwrap_or(total).min(total);
+    (royalty, total - royalty)
 }
 
 #[allow(clippy::missing_panics_doc)]
@@ -308,17 +436,77 @@ where
         self.get_layered_object(None)
             .lineage_proof_for_child(my_parent_name, my_parent_amount)
     }
+
+    /// Reconstructs the mint parameters that would produce an NFT with the current state.
+    /// The DID inner puzzle hash isn't known from the NFT alone, so it must be filled in
+    /// before re-minting with a DID owner.
+    pub fn to_mint(&self) -> NftMint<M> {
+        NftMint {
+            metadata: self.metadata.clone(),
+            royalty_puzzle_hash: self.royalty_puzzle_hash,
+            royalty_percentage: self.royalty_percentage,
+            puzzle_hash: self.p2_puzzle_hash.into(),
+            owner: NewNftOwner::new(self.current_owner, Vec::new(), None),
+        }
+    }
+}
+
+/// Checks whether a full NFT puzzle uses the standard royalty transfer program, curried with its own launcher id.
+/// Only NFTs which pass this check can be parsed as an [`Nft`], so their royalty fields can be trusted.
+pub fn uses_standard_transfer_program(allocator: &Allocator, puzzle: NodePtr) -> bool {
+    let layer_args = |puzzle: NodePtr, mod_hash: TreeHash| {
+        Puzzle::parse(allocator, puzzle)
+            .as_curried()
+            .filter(|curried| curried.mod_hash == mod_hash)
+            .map(|curried| curried.args)
+    };
+
+    let Some(singleton_args) = layer_args(puzzle, SINGLETON_TOP_LAYER_PUZZLE_HASH)
+        .and_then(|args| SingletonArgs::<NodePtr>::from_clvm(allocator, args).ok())
+    else {
+        return false;
+    };
+
+    let Some(state_args) = layer_args(singleton_args.inner_puzzle, NFT_STATE_LAYER_PUZZLE_HASH)
+        .and_then(|args| NftStateLayerArgs::<NodePtr, NodePtr>::from_clvm(allocator, args).ok())
+    else {
+        return false;
+    };
+
+    let Some(ownership_args) = layer_args(state_args.inner_puzzle, NFT_OWNERSHIP_LAYER_PUZZLE_HASH)
+        .and_then(|args| {
+            NftOwnershipLayerArgs::<NodePtr, NodePtr>::from_clvm(allocator, args).ok()
+        })
+    else {
+        return false;
+    };
+
+    let Some(transfer_args) = Puzzle::parse(allocator, ownership_args.transfer_program)
+        .as_curried()
+        .and_then(|curried| NftRoyaltyTransferPuzzleArgs::from_clvm(allocator, curried.args).ok())
+    else {
+        return false;
+    };
+
+    let expected = NftRoyaltyTransferPuzzleArgs::curry_tree_hash(
+        singleton_args.singleton_struct.launcher_id,
+        transfer_args.royalty_puzzle_hash,
+        transfer_args.trade_price_percentage,
+    );
+
+    tree_hash(allocator, ownership_args.transfer_program) == expected
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::{nft_mint, IntermediateLauncher, Launcher, NftMint};
+    use crate::{nft_mint, Did, IntermediateLauncher, Launcher};
 
     use super::*;
 
     use chia_bls::DerivableKey;
-    use chia_puzzles::standard::StandardArgs;
+    use chia_puzzles::{nft::NftMetadata, standard::StandardArgs};
     use chia_sdk_test::{secret_key, test_transaction, Simulator};
+    use clvm_utils::CurriedProgram;
 
     #[tokio::test]
     async fn test_nft_transfer() -> anyhow::Result<()> {
@@ -367,6 +555,201 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn test_assign_and_unassign_did() -> anyhow::Result<()> {
+        let sim = Simulator::new().await?;
+        let peer = sim.connect().await?;
+        let ctx = &mut SpendContext::new();
+
+        let sk = secret_key()?;
+        let pk = sk.public_key();
+
+        let puzzle_hash = StandardArgs::curry_tree_hash(pk).into();
+        let coin = sim.mint_coin(puzzle_hash, 2).await;
+
+        let (create_did, did, did_proof) =
+            Launcher::new(coin.coin_id(), 1).create_simple_did(ctx, pk)?;
+        let (mint_nft, nft, nft_proof) = IntermediateLauncher::new(coin.coin_id(), 0, 1)
+            .create(ctx)?
+            .mint_nft(ctx, nft_mint(puzzle_hash, None))?;
+        ctx.spend_p2_coin(coin, pk, create_did.extend(mint_nft))?;
+
+        test_transaction(
+            &peer,
+            ctx.take_spends(),
+            &[sk.clone()],
+            sim.config().genesis_challenge,
+        )
+        .await;
+
+        assert_eq!(nft.current_owner, None);
+
+        // Assign the NFT to the DID in a separate transaction.
+        let (did_conditions, nft, nft_proof) = nft.assign_did(ctx, nft_proof, pk, &did)?;
+        let _ = ctx.spend_standard_did(&did, did_proof, pk, did_conditions)?;
+
+        test_transaction(
+            &peer,
+            ctx.take_spends(),
+            &[sk.clone()],
+            sim.config().genesis_challenge,
+        )
+        .await;
+
+        assert_eq!(nft.current_owner, Some(did.launcher_id));
+        assert_eq!(nft.p2_puzzle_hash, puzzle_hash.into());
+
+        let (nft, _) = nft.unassign_did(ctx, nft_proof, pk)?;
+
+        test_transaction(
+            &peer,
+            ctx.take_spends(),
+            &[sk],
+            sim.config().genesis_challenge,
+        )
+        .await;
+
+        assert_eq!(nft.current_owner, None);
+
+        let coin_state = sim
+            .coin_state(nft.coin.coin_id())
+            .await
+            .expect("expected nft coin");
+        assert_eq!(coin_state.coin, nft.coin);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_uses_standard_transfer_program() -> anyhow::Result<()> {
+        let ctx = &mut SpendContext::new();
+
+        let puzzle_hash = Bytes32::new([5; 32]);
+        let (_, nft, _) =
+            Launcher::new(Bytes32::default(), 1).mint_nft(ctx, nft_mint(puzzle_hash, None))?;
+
+        let p2_puzzle = ctx.alloc(&1)?;
+        let puzzle = nft
+            .get_layered_object(Some(p2_puzzle))
+            .construct_puzzle(ctx)?;
+        assert!(uses_standard_transfer_program(ctx.allocator(), puzzle));
+
+        // The same NFT with a transfer program that ignores royalties.
+        let transfer_program = ctx.alloc(&1)?;
+        let ownership_layer = ctx.nft_ownership_layer()?;
+        let ownership_puzzle = ctx.alloc(&CurriedProgram {
+            program: ownership_layer,
+            args: NftOwnershipLayerArgs::new(None, transfer_program, p2_puzzle),
+        })?;
+        let state_layer = ctx.nft_state_layer()?;
+        let state_puzzle = ctx.alloc(&CurriedProgram {
+            program: state_layer,
+            args: NftStateLayerArgs::new(nft.metadata.clone(), ownership_puzzle),
+        })?;
+        let singleton_layer = ctx.singleton_top_layer()?;
+        let puzzle = ctx.alloc(&CurriedProgram {
+            program: singleton_layer,
+            args: SingletonArgs::new(nft.launcher_id, state_puzzle),
+        })?;
+        assert!(!uses_standard_transfer_program(ctx.allocator(), puzzle));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_nft_from_puzzle_and_solution() -> anyhow::Result<()> {
+        let sim = Simulator::new().await?;
+        let peer = sim.connect().await?;
+        let ctx = &mut SpendContext::new();
+
+        let sk = secret_key()?;
+        let pk = sk.public_key();
+
+        let puzzle_hash = StandardArgs::curry_tree_hash(pk).into();
+        let coin = sim.mint_coin(puzzle_hash, 1).await;
+
+        let (mint_nft, nft, lineage_proof) =
+            Launcher::new(coin.coin_id(), 1).mint_nft(ctx, nft_mint(puzzle_hash, None))?;
+        ctx.spend_p2_coin(coin, pk, mint_nft)?;
+
+        let (parent_conditions, new_nft, _) = ctx.spend_standard_nft(
+            &nft,
+            lineage_proof,
+            pk,
+            puzzle_hash,
+            None,
+            Conditions::new(),
+        )?;
+        assert_eq!(parent_conditions, Conditions::new());
+
+        test_transaction(
+            &peer,
+            ctx.take_spends(),
+            &[sk],
+            sim.config().genesis_challenge,
+        )
+        .await;
+
+        let height = sim
+            .coin_state(nft.coin.coin_id())
+            .await
+            .and_then(|coin_state| coin_state.spent_height)
+            .expect("expected spent nft");
+        let response = peer
+            .request_puzzle_and_solution(nft.coin.coin_id(), height)
+            .await?;
+
+        let (child, child_coin) = Nft::<NftMetadata>::from_puzzle_and_solution(
+            ctx.allocator_mut(),
+            response.coin_name,
+            &response.puzzle,
+            &response.solution,
+        )?
+        .expect("expected child nft");
+        assert_eq!(child_coin, new_nft.coin);
+        assert_eq!(child.launcher_id, nft.launcher_id);
+        assert_eq!(child.metadata, nft.metadata);
+        assert!(sim.coin_state(child_coin.coin_id()).await.is_some());
+
+        // An NFT spend isn't mistaken for a DID.
+        assert!(Did::<()>::from_puzzle_and_solution(
+            ctx.allocator_mut(),
+            response.coin_name,
+            &response.puzzle,
+            &response.solution,
+        )?
+        .is_none());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_to_mint_round_trip() -> anyhow::Result<()> {
+        let ctx = &mut SpendContext::new();
+
+        let puzzle_hash = Bytes32::new([42; 32]);
+        let parent_coin_id = Bytes32::new([7; 32]);
+
+        let (_, nft, _) =
+            Launcher::new(parent_coin_id, 1).mint_nft(ctx, nft_mint(puzzle_hash, None))?;
+
+        let eve_spend = ctx
+            .take_spends()
+            .into_iter()
+            .find(|cs| cs.coin.coin_id() == nft.coin.parent_coin_info)
+            .expect("expected eve spend");
+        let parsed = Nft::<NftMetadata>::from_parent_spend(ctx.allocator_mut(), &eve_spend)?
+            .expect("expected child nft");
+        assert_eq!(parsed.coin, nft.coin);
+
+        let ctx = &mut SpendContext::new();
+        let (_, reminted, _) = Launcher::new(parent_coin_id, 1).mint_nft(ctx, parsed.to_mint())?;
+        assert_eq!(reminted.coin.puzzle_hash, nft.coin.puzzle_hash);
+        assert_eq!(reminted.coin, nft.coin);
+
+        Ok(())
+    }
+
     #[tokio::test]
     async fn test_nft_lineage() -> anyhow::Result<()> {
         let sim = Simulator::new().await?;
@@ -436,6 +819,214 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn test_reassign_nft_owner() -> anyhow::Result<()> {
+        let sim = Simulator::new().await?;
+        let peer = sim.connect().await?;
+        let ctx = &mut SpendContext::new();
+
+        let sk = secret_key()?;
+        let pk = sk.public_key();
+
+        let puzzle_hash = StandardArgs::curry_tree_hash(pk).into();
+        let coin = sim.mint_coin(puzzle_hash, 2).await;
+        let other_coin = sim.mint_coin(puzzle_hash, 1).await;
+
+        let (create_did, did, did_proof) =
+            Launcher::new(coin.coin_id(), 1).create_simple_did(ctx, pk)?;
+        ctx.spend_p2_coin(coin, pk, create_did)?;
+
+        let (create_did, other_did, other_did_proof) =
+            Launcher::new(other_coin.coin_id(), 1).create_simple_did(ctx, pk)?;
+        ctx.spend_p2_coin(other_coin, pk, create_did)?;
+
+        let (mint_nft, nft, nft_proof) = IntermediateLauncher::new(did.coin.coin_id(), 0, 1)
+            .create(ctx)?
+            .mint_nft(ctx, nft_mint(puzzle_hash, Some(&did)))?;
+        let (did, _) = ctx.spend_standard_did(&did, did_proof, pk, mint_nft)?;
+        assert_eq!(nft.current_owner, Some(did.launcher_id));
+
+        let (new_nft, _, new_did, _) = ctx.reassign_nft_owner(
+            &nft,
+            nft_proof,
+            &other_did,
+            other_did_proof,
+            pk,
+            puzzle_hash,
+        )?;
+        assert_eq!(new_nft.current_owner, Some(other_did.launcher_id));
+        assert_eq!(new_did.launcher_id, other_did.launcher_id);
+
+        test_transaction(
+            &peer,
+            ctx.take_spends(),
+            &[sk],
+            sim.config().genesis_challenge,
+        )
+        .await;
+
+        for coin in [new_nft.coin, new_did.coin] {
+            let coin_state = sim.coin_state(coin.coin_id()).await.expect("expected coin");
+            assert_eq!(coin_state.spent_height, None);
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_calculate_royalty() {
+        assert_eq!(calculate_royalty(1000, 300), (30, 970));
+        assert_eq!(calculate_royalty(999, 300), (29, 970));
+        assert_eq!(calculate_royalty(1, 9999), (0, 1));
+        assert_eq!(calculate_royalty(0, 300), (0, 0));
+        assert_eq!(calculate_royalty(1000, 0), (0, 1000));
+        assert_eq!(calculate_royalty(1000, 10_000), (1000, 0));
+        assert_eq!(calculate_royalty(1000, u16::MAX), (1000, 0));
+        assert_eq!(calculate_royalty(u64::MAX, 10_000), (u64::MAX, 0));
+
+        for (total, percentage) in [(u64::MAX, 1), (u64::MAX, 333), (12_345, 7), (101, 3333)] {
+            let (royalty, remainder) = calculate_royalty(total, percentage);
+            assert_eq!(royalty + remainder, total);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_nft_royalty_payment() -> anyhow::Result<()> {
+        let sim = Simulator::new().await?;
+        let peer = sim.connect().await?;
+        let ctx = &mut SpendContext::new();
+
+        let sk = secret_key()?;
+        let pk = sk.public_key();
+
+        let puzzle_hash = StandardArgs::curry_tree_hash(pk).into();
+        let coin = sim.mint_coin(puzzle_hash, 1).await;
+        let payment_coin = sim.mint_coin(puzzle_hash, 1000).await;
+
+        let (mint_nft, nft, nft_proof) =
+            Launcher::new(coin.coin_id(), 1).mint_nft(ctx, nft_mint(puzzle_hash, None))?;
+        ctx.spend_p2_coin(coin, pk, mint_nft)?;
+
+        // The NFT asserts the royalty announcement from the puzzle hash of the trade price.
+        let trade_price = NftTradePrice::new(1000, puzzle_hash);
+        // There's no new DID owner, so the returned DID conditions aren't needed.
+        let _ = ctx.spend_standard_nft(
+            &nft,
+            nft_proof,
+            pk,
+            puzzle_hash,
+            Some(NewNftOwner::new(None, vec![trade_price], None)),
+            Conditions::new(),
+        )?;
+
+        let (royalty, remainder) =
+            calculate_royalty(trade_price.trade_price, nft.royalty_percentage);
+        assert_eq!((royalty, remainder), (30, 970));
+
+        ctx.spend_p2_coin(
+            payment_coin,
+            pk,
+            Conditions::new()
+                .pay_royalty(nft.launcher_id, nft.royalty_puzzle_hash, royalty)
+                .create_coin(puzzle_hash, remainder),
+        )?;
+
+        test_transaction(
+            &peer,
+            ctx.take_spends(),
+            &[sk],
+            sim.config().genesis_challenge,
+        )
+        .await;
+
+        let royalty_coin = Coin::new(payment_coin.coin_id(), nft.royalty_puzzle_hash, royalty);
+        assert!(sim.coin_state(royalty_coin.coin_id()).await.is_some());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_trade_prices() -> anyhow::Result<()> {
+        let mut ctx = SpendContext::new();
+
+        let pk = PublicKey::default();
+        let puzzle_hash = StandardArgs::curry_tree_hash(pk).into();
+        let coin = Coin::new(Bytes32::new([0; 32]), puzzle_hash, 1);
+
+        let (mint_nft, nft, nft_proof) =
+            Launcher::new(coin.coin_id(), 1).mint_nft(&mut ctx, nft_mint(puzzle_hash, None))?;
+        ctx.spend_p2_coin(coin, pk, mint_nft)?;
+
+        // The amount doesn't fit in a u16, which trade prices were previously limited to.
+        let new_owner = NewNftOwner::default()
+            .with_trade_price(1_000_000_000_000, puzzle_hash)
+            .with_trade_price(500, Bytes32::new([42; 32]));
+
+        let _ = ctx.spend_standard_nft(
+            &nft,
+            nft_proof,
+            pk,
+            puzzle_hash,
+            Some(new_owner.clone()),
+            Conditions::new(),
+        )?;
+
+        let spends = ctx.take_spends();
+        let nft_spend = spends
+            .iter()
+            .find(|cs| cs.coin.coin_id() == nft.coin.coin_id())
+            .expect("missing nft spend");
+
+        let trade_prices = Nft::<NftMetadata>::parse_trade_prices(ctx.allocator_mut(), nft_spend)?;
+        assert_eq!(trade_prices, Some(new_owner.trade_prices.clone()));
+
+        // The funding coin isn't an NFT, so there are no trade prices to parse.
+        let p2_spend = spends
+            .iter()
+            .find(|cs| cs.coin.coin_id() == coin.coin_id())
+            .expect("missing p2 spend");
+        assert_eq!(
+            Nft::<NftMetadata>::parse_trade_prices(ctx.allocator_mut(), p2_spend)?,
+            None
+        );
+
+        // The trade prices are still parsed if the NFT doesn't use the standard transfer program.
+        let p2_puzzle = ctx.alloc(&1)?;
+        let transfer_program = ctx.alloc(&1)?;
+        let ownership_layer = ctx.nft_ownership_layer()?;
+        let ownership_puzzle = ctx.alloc(&CurriedProgram {
+            program: ownership_layer,
+            args: NftOwnershipLayerArgs::new(None, transfer_program, p2_puzzle),
+        })?;
+        let state_layer = ctx.nft_state_layer()?;
+        let state_puzzle = ctx.alloc(&CurriedProgram {
+            program: state_layer,
+            args: NftStateLayerArgs::new(nft.metadata.clone(), ownership_puzzle),
+        })?;
+        let singleton_layer = ctx.singleton_top_layer()?;
+        let puzzle = ctx.alloc(&CurriedProgram {
+            program: singleton_layer,
+            args: SingletonArgs::new(nft.launcher_id, state_puzzle),
+        })?;
+        let solution = ctx.alloc(&SingletonLayerSolution {
+            lineage_proof: nft_proof,
+            amount: 1,
+            inner_solution: NftStateLayerSolution {
+                inner_solution: NftOwnershipLayerSolution {
+                    inner_solution: [new_owner.clone()],
+                },
+            },
+        })?;
+        let nft_spend =
+            CoinSpend::new(nft.coin, ctx.serialize(&puzzle)?, ctx.serialize(&solution)?);
+        assert_eq!(
+            Nft::<NftMetadata>::parse_trade_prices(ctx.allocator_mut(), &nft_spend)?,
+            Some(new_owner.trade_prices)
+        );
+
+        Ok(())
+    }
+
     #[test]
     fn test_parse_nft() -> anyhow::Result<()> {
         let mut ctx = SpendContext::new();
@@ -5,7 +5,10 @@ use clvm_traits::{clvm_quote, FromClvm, ToClvm};
 use clvm_utils::ToTreeHash;
 use clvmr::NodePtr;
 
-use crate::{did_puzzle_assertion, Conditions, DriverError, Launcher, Spend, SpendContext};
+use crate::{
+    did_puzzle_assertion, Conditions, Did, DriverError, IntermediateLauncher, Launcher, Spend,
+    SpendContext,
+};
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct NftMint<M> {
@@ -42,7 +45,7 @@ impl Launcher {
         };
 
         let (launch_singleton, eve_coin) = self
-            .spend(ctx, nft.singleton_inner_puzzle_hash().into(), ())
+            .spend_with_metadata(ctx, nft.singleton_inner_puzzle_hash().into())
             .map_err(DriverError::Spend)?;
 
         let proof = Proof::Eve(EveProof {
@@ -62,6 +65,18 @@ impl Launcher {
         ctx: &mut SpendContext,
         mint: NftMint<M>,
     ) -> Result<(Conditions, Nft<M>, Proof), DriverError>
+    where
+        M: ToClvm<NodePtr> + FromClvm<NodePtr> + Clone + ToTreeHash,
+    {
+        self.mint_nft_inner(ctx, mint)
+            .map_err(|error| error.context("minting NFT"))
+    }
+
+    fn mint_nft_inner<M>(
+        self,
+        ctx: &mut SpendContext,
+        mint: NftMint<M>,
+    ) -> Result<(Conditions, Nft<M>, Proof), DriverError>
     where
         M: ToClvm<NodePtr> + FromClvm<NodePtr> + Clone + ToTreeHash,
     {
@@ -85,7 +100,7 @@ impl Launcher {
         )?;
 
         let (eve_spend, _, lineage_proof) = eve_nft.spend(ctx, eve_proof, inner_spend)?;
-        ctx.insert_coin_spend(eve_spend.clone());
+        ctx.insert_coin_spend(eve_spend.clone())?;
 
         let mut did_conditions = Conditions::new();
 
@@ -105,6 +120,39 @@ impl Launcher {
     }
 }
 
+impl<M> Did<M> {
+    /// Mints each NFT from its own intermediate launcher, created by spending this DID.
+    /// The returned conditions must be output by the DID spend, and the NFTs and proofs are in the same order as the mints.
+    #[allow(clippy::type_complexity)]
+    pub fn bulk_mint_nfts<N>(
+        &self,
+        ctx: &mut SpendContext,
+        mints: Vec<NftMint<N>>,
+    ) -> Result<(Conditions, Vec<Nft<N>>, Vec<Proof>), DriverError>
+    where
+        N: ToClvm<NodePtr> + FromClvm<NodePtr> + Clone + ToTreeHash,
+    {
+        let mint_total = mints.len();
+
+        let mut conditions = Conditions::new();
+        let mut nfts = Vec::with_capacity(mint_total);
+        let mut proofs = Vec::with_capacity(mint_total);
+
+        for (mint_number, mint) in mints.into_iter().enumerate() {
+            let (mint_nft, nft, proof) =
+                IntermediateLauncher::new(self.coin.coin_id(), mint_number, mint_total)
+                    .create(ctx)?
+                    .mint_nft(ctx, mint)?;
+
+            conditions = conditions.extend(mint_nft);
+            nfts.push(nft);
+            proofs.push(proof);
+        }
+
+        Ok((conditions, nfts, proofs))
+    }
+}
+
 #[cfg(test)]
 pub use tests::nft_mint;
 
@@ -112,17 +160,13 @@ use super::Nft;
 
 #[cfg(test)]
 mod tests {
-    use crate::{Did, IntermediateLauncher, Launcher};
-
     use super::*;
 
-    use chia_consensus::gen::{
-        conditions::EmptyVisitor, run_block_generator::run_block_generator,
-        solution_generator::solution_generator,
-    };
-    use chia_protocol::Coin;
-    use chia_puzzles::{nft::NftMetadata, standard::StandardArgs};
+    use chia_bls::PublicKey;
+    use chia_protocol::{Bytes, Coin};
+    use chia_puzzles::{nft::NftMetadata, singleton::LauncherSolution, standard::StandardArgs};
     use chia_sdk_test::{secret_key, test_transaction, Simulator};
+    use clvm_traits::ToNodePtr;
 
     pub fn nft_mint(puzzle_hash: Bytes32, did: Option<&Did<()>>) -> NftMint<NftMetadata> {
         NftMint {
@@ -154,8 +198,7 @@ mod tests {
     {
         let sk = secret_key()?;
         let pk = sk.public_key();
-        let mut owned_ctx = SpendContext::new();
-        let ctx = &mut owned_ctx;
+        let ctx = &mut SpendContext::new();
 
         let puzzle_hash = StandardArgs::curry_tree_hash(pk).into();
         let coin = Coin::new(Bytes32::new([0; 32]), puzzle_hash, 1);
@@ -183,22 +226,72 @@ mod tests {
             Conditions::new().assert_coin_announcement(did.coin.coin_id(), "$"),
         )?;
 
+        assert_eq!(ctx.estimate_cost()?, 122_646_589);
+        assert_eq!(ctx.take_spends().len(), 5);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_mint_nft_error_context() -> anyhow::Result<()> {
+        let ctx = &mut SpendContext::new();
+
+        let puzzle_hash = StandardArgs::curry_tree_hash(PublicKey::default()).into();
+        let launcher = Launcher::new(Bytes32::default(), 1);
+
+        let _ = launcher
+            .clone()
+            .mint_nft(ctx, nft_mint(puzzle_hash, None))?;
+
+        // Spending the same launcher twice fails, and the error says what was being minted.
+        let error = launcher
+            .mint_nft(ctx, nft_mint(puzzle_hash, None))
+            .expect_err("expected duplicate launcher spend");
+        assert!(matches!(error, DriverError::Context { .. }));
+        assert!(error.to_string().starts_with("minting NFT: "));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_mint_nft_with_launcher_metadata() -> anyhow::Result<()> {
+        let sim = Simulator::new().await?;
+        let peer = sim.connect().await?;
+        let ctx = &mut SpendContext::new();
+
+        let sk = secret_key()?;
+        let pk = sk.public_key();
+
+        let puzzle_hash = StandardArgs::curry_tree_hash(pk).into();
+        let coin = sim.mint_coin(puzzle_hash, 1).await;
+
+        let metadata: Vec<(Bytes, Bytes)> = vec![
+            (b"name".to_vec().into(), b"example".to_vec().into()),
+            (b"version".to_vec().into(), vec![1].into()),
+        ];
+
+        let (mint_nft, nft, _) = Launcher::new(coin.coin_id(), 1)
+            .with_metadata(metadata.clone())
+            .mint_nft(ctx, nft_mint(puzzle_hash, None))?;
+        ctx.spend_p2_coin(coin, pk, mint_nft)?;
+
         let coin_spends = ctx.take_spends();
 
-        let generator = solution_generator(
-            coin_spends
-                .iter()
-                .map(|cs| (cs.coin, cs.puzzle_reveal.clone(), cs.solution.clone())),
-        )?;
-        let conds = run_block_generator::<Vec<u8>, EmptyVisitor>(
-            &mut owned_ctx.into(),
-            &generator,
-            &[],
-            11_000_000_000,
-            0,
-        )?;
+        let launcher_spend = coin_spends
+            .iter()
+            .find(|cs| cs.coin.coin_id() == nft.launcher_id)
+            .expect("expected launcher spend");
+        let solution = launcher_spend.solution.to_node_ptr(ctx.allocator_mut())?;
+        let solution = ctx.extract::<LauncherSolution<Vec<(Bytes, Bytes)>>>(solution)?;
+        assert_eq!(solution.key_value_list, metadata);
+
+        test_transaction(&peer, coin_spends, &[sk], sim.config().genesis_challenge).await;
 
-        assert_eq!(conds.cost, 122_646_589);
+        let coin_state = sim
+            .coin_state(nft.coin.coin_id())
+            .await
+            .expect("expected nft coin");
+        assert_eq!(coin_state.coin, nft.coin);
 
         Ok(())
     }
@@ -248,6 +341,127 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn test_bulk_mint_many() -> anyhow::Result<()> {
+        let sim = Simulator::new().await?;
+        let peer = sim.connect().await?;
+        let ctx = &mut SpendContext::new();
+
+        let sk = secret_key()?;
+        let pk = sk.public_key();
+
+        let puzzle_hash = StandardArgs::curry_tree_hash(pk).into();
+        let coin = sim.mint_coin(puzzle_hash, 11).await;
+
+        let (create_did, did, did_proof) =
+            Launcher::new(coin.coin_id(), 1).create_simple_did(ctx, pk)?;
+
+        ctx.spend_p2_coin(coin, pk, create_did)?;
+
+        let mut mints = Vec::new();
+        let mut nfts = Vec::new();
+
+        for index in 0..10 {
+            let (mint_nft, nft, _) = IntermediateLauncher::new(did.coin.coin_id(), index, 10)
+                .create(ctx)?
+                .mint_nft(ctx, nft_mint(puzzle_hash, Some(&did)))?;
+
+            mints.push(mint_nft);
+            nfts.push(nft);
+        }
+
+        let conditions = Conditions::new().extend_all(mints);
+
+        let count = |f: fn(&Condition) -> bool| conditions.as_ref().iter().filter(|c| f(c)).count();
+        assert_eq!(
+            count(|c| matches!(c, Condition::CreatePuzzleAnnouncement(..))),
+            10
+        );
+        assert_eq!(
+            count(|c| matches!(c, Condition::AssertPuzzleAnnouncement(..))),
+            10
+        );
+
+        let _did = ctx.spend_standard_did(&did, did_proof, pk, conditions)?;
+
+        test_transaction(
+            &peer,
+            ctx.take_spends(),
+            &[sk],
+            sim.config().genesis_challenge,
+        )
+        .await;
+
+        for nft in nfts {
+            let coin_state = sim
+                .coin_state(nft.coin.coin_id())
+                .await
+                .expect("expected nft");
+            assert_eq!(coin_state.spent_height, None);
+        }
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_did_bulk_mint_nfts() -> anyhow::Result<()> {
+        let sim = Simulator::new().await?;
+        let peer = sim.connect().await?;
+        let ctx = &mut SpendContext::new();
+
+        let sk = secret_key()?;
+        let pk = sk.public_key();
+
+        let puzzle_hash = StandardArgs::curry_tree_hash(pk).into();
+        let coin = sim.mint_coin(puzzle_hash, 4).await;
+
+        let (create_did, did, did_proof) =
+            Launcher::new(coin.coin_id(), 1).create_simple_did(ctx, pk)?;
+
+        ctx.spend_p2_coin(coin, pk, create_did)?;
+
+        let mints: Vec<NftMint<NftMetadata>> = (0..3)
+            .map(|index| NftMint {
+                royalty_percentage: index,
+                ..nft_mint(puzzle_hash, Some(&did))
+            })
+            .collect();
+
+        let (conditions, nfts, proofs) = did.bulk_mint_nfts(ctx, mints)?;
+        assert_eq!(nfts.len(), 3);
+        assert_eq!(proofs.len(), 3);
+
+        for (index, nft) in (0..).zip(&nfts) {
+            assert_eq!(nft.royalty_percentage, index);
+            assert_eq!(
+                nft.launcher_id,
+                IntermediateLauncher::new(did.coin.coin_id(), index.into(), 3)
+                    .launcher_coin()
+                    .coin_id()
+            );
+        }
+
+        let _did = ctx.spend_standard_did(&did, did_proof, pk, conditions)?;
+
+        test_transaction(
+            &peer,
+            ctx.take_spends(),
+            &[sk],
+            sim.config().genesis_challenge,
+        )
+        .await;
+
+        for nft in nfts {
+            let coin_state = sim
+                .coin_state(nft.coin.coin_id())
+                .await
+                .expect("expected nft");
+            assert_eq!(coin_state.spent_height, None);
+        }
+
+        Ok(())
+    }
+
     #[tokio::test]
     async fn test_nonstandard_intermediate_mint() -> anyhow::Result<()> {
         let sim = Simulator::new().await?;
@@ -0,0 +1,88 @@
+use chia_protocol::Bytes32;
+use chia_puzzles::nft::NftMetadata;
+use clvmr::sha2::{Digest, Sha256};
+
+/// Whether content fetched from an NFT's URIs matches the hash in its metadata.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VerificationStatus {
+    Passed,
+    Failed,
+    /// Either the content wasn't provided, or the metadata has no hash to check it against.
+    Missing,
+}
+
+impl VerificationStatus {
+    fn new(expected_hash: Option<Bytes32>, content: Option<&[u8]>) -> Self {
+        let (Some(expected_hash), Some(content)) = (expected_hash, content) else {
+            return Self::Missing;
+        };
+
+        let hash = Bytes32::new(Sha256::digest(content).into());
+
+        if hash == expected_hash {
+            Self::Passed
+        } else {
+            Self::Failed
+        }
+    }
+}
+
+/// The verification status of each kind of content an NFT's metadata links to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct VerificationReport {
+    pub data: VerificationStatus,
+    pub metadata: VerificationStatus,
+    pub license: VerificationStatus,
+}
+
+impl VerificationReport {
+    /// Checks the content fetched from the data, metadata, and license URIs against the hashes in the NFT metadata.
+    pub fn new(
+        nft_metadata: &NftMetadata,
+        data: Option<&[u8]>,
+        metadata: Option<&[u8]>,
+        license: Option<&[u8]>,
+    ) -> Self {
+        Self {
+            data: VerificationStatus::new(nft_metadata.data_hash, data),
+            metadata: VerificationStatus::new(nft_metadata.metadata_hash, metadata),
+            license: VerificationStatus::new(nft_metadata.license_hash, license),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_verification_report() {
+        let data = b"image bytes";
+
+        let nft_metadata = NftMetadata {
+            data_hash: Some(Bytes32::new(Sha256::digest(data).into())),
+            metadata_hash: Some(Bytes32::new([2; 32])),
+            license_hash: None,
+            ..Default::default()
+        };
+
+        let report = VerificationReport::new(
+            &nft_metadata,
+            Some(data),
+            Some(b"tampered metadata"),
+            Some(b"license text"),
+        );
+        assert_eq!(
+            report,
+            VerificationReport {
+                data: VerificationStatus::Passed,
+                metadata: VerificationStatus::Failed,
+                license: VerificationStatus::Missing,
+            }
+        );
+
+        let report = VerificationReport::new(&nft_metadata, None, None, None);
+        assert_eq!(report.data, VerificationStatus::Missing);
+        assert_eq!(report.metadata, VerificationStatus::Missing);
+    }
+}
@@ -0,0 +1,370 @@
+use chia_protocol::{Bytes32, Coin};
+use clvm_traits::{FromClvm, ToClvm};
+use clvm_utils::{CurriedProgram, ToTreeHash, TreeHash};
+use hex_literal::hex;
+
+use crate::{Conditions, Spend, SpendContext, SpendError};
+
+/// The streaming payment puzzle reveal, which can be found in `puzzles/streaming_payment.clsp.hex`.
+pub const STREAMING_PAYMENT_PUZZLE: [u8; 1018] = hex!(
+    "
+    ff02ffff01ff02ffff03ff8205ffffff01ff02ff26ffff04ff02ffff04ff0bff
+    ff04ff17ffff04ff82017fffff04ff8202ffffff04ff8205ffffff04ffff02ff
+    7effff04ff02ffff04ff82017fffff04ff81bfffff04ff5fffff04ff2fffff04
+    ff8202ffff8080808080808080ff808080808080808080ffff01ff02ffff03ff
+    ff21ffff15ff2fff8202ff80ffff20ffff15ff8202ffff81bf808080ffff01ff
+    0880ffff01ff02ff36ffff04ff02ffff04ff05ffff04ff0bffff04ff17ffff04
+    ff2fffff04ff5fffff04ff82017fffff04ff8202ffffff04ffff02ff7effff04
+    ff02ffff04ff82017fffff04ff81bfffff04ff5fffff04ff2fffff04ff8202ff
+    ff8080808080808080ff808080808080808080808080ff018080ff0180ffff04
+    ffff01ffffffff5549ff463fffff5102ff3304ffffff0101ff02ff02ffff03ff
+    05ffff01ff02ff3affff04ff02ffff04ff0dffff04ffff0bff2affff0bff22ff
+    3c80ffff0bff2affff0bff2affff0bff22ff3280ff0980ffff0bff2aff0bffff
+    0bff22ff8080808080ff8080808080ffff010b80ff0180ffffff04ffff04ff30
+    ffff04ff17ff808080ffff04ffff04ff28ffff04ff5fff808080ffff04ffff04
+    ff38ffff04ffff0bff0bff5f80ff808080ffff04ffff04ff20ffff04ff2fff80
+    8080ffff02ff2effff04ff02ffff04ff05ffff04ff81bfffff04ff05ffff04ff
+    ff02ff2effff04ff02ffff04ff0bffff04ffff11ff17ff81bf80ffff04ff0bff
+    ff01ff80808080808080ff8080808080808080808080ff04ffff04ff30ffff04
+    ff81bfff808080ffff04ffff04ff24ffff04ff82017fff808080ffff02ff2eff
+    ff04ff02ffff04ff0bffff04ff8202ffffff04ff0bffff04ffff02ff2effff04
+    ff02ffff04ffff02ff5effff04ff02ffff04ff05ffff04ffff0bff22ff82017f
+    80ffff04ffff0bff22ff5f80ffff04ffff0bff22ff2f80ffff04ffff0bff22ff
+    1780ffff04ffff0bff22ff0b80ffff04ffff0bff22ff0580ff80808080808080
+    808080ffff04ffff11ff81bfff8202ff80ffff04ff0bffff01ff808080808080
+    80ff808080808080808080ffff02ffff03ffff15ff0bff8080ffff01ff04ffff
+    04ff2cffff04ff05ffff04ff0bffff04ffff04ff17ff8080ff8080808080ff2f
+    80ffff012f80ff0180ffff0bff2affff0bff22ff3480ffff0bff2affff0bff2a
+    ffff0bff22ff3280ff0580ffff0bff2affff02ff3affff04ff02ffff04ff07ff
+    ff04ffff0bff22ff2280ff8080808080ffff0bff22ff8080808080ff02ffff03
+    ffff21ffff15ff2fff5f80ffff20ffff15ff5fff0b808080ff80ffff01ff02ff
+    ff03ffff15ff17ff5f80ffff01ff05ffff14ffff12ff05ffff11ff5fff0b8080
+    ffff11ff17ff0b808080ffff010580ff018080ff0180ff018080
+    "
+);
+
+/// The tree hash of [`STREAMING_PAYMENT_PUZZLE`].
+pub const STREAMING_PAYMENT_PUZZLE_HASH: TreeHash = TreeHash::new(hex!(
+    "1f81c2f2e2f348e2ff4a92dffdf78611ebd6141cc198f757ccc29f27c8913d82"
+));
+
+/// The curried arguments of the streaming payment puzzle.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ToClvm, FromClvm)]
+#[clvm(curry)]
+pub struct StreamingPaymentArgs {
+    pub mod_hash: Bytes32,
+    pub recipient_puzzle_hash: Bytes32,
+    pub clawback_puzzle_hash: Bytes32,
+    pub cliff_time: u64,
+    pub end_time: u64,
+    pub last_payment_time: u64,
+}
+
+/// The solution to the streaming payment puzzle.
+/// The coin id is only revealed when the stream is being cancelled.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ToClvm, FromClvm)]
+#[clvm(list)]
+pub struct StreamingPaymentSolution {
+    pub my_amount: u64,
+    pub payment_time: u64,
+    pub my_id: Option<Bytes32>,
+}
+
+/// A payment which vests linearly to the recipient between its start and end time.
+/// Nothing can be claimed before the cliff, after which everything vested so far can be claimed at once.
+/// The clawback puzzle hash can cancel the stream, paying out the vested amount and reclaiming the rest.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[must_use]
+pub struct StreamingPayment {
+    pub recipient_puzzle_hash: Bytes32,
+    pub clawback_puzzle_hash: Bytes32,
+    pub cliff_time: u64,
+    pub end_time: u64,
+    /// The time up to which the recipient has been paid, which starts out as the start time.
+    pub last_payment_time: u64,
+}
+
+impl StreamingPayment {
+    pub fn new(
+        recipient_puzzle_hash: Bytes32,
+        clawback_puzzle_hash: Bytes32,
+        start_time: u64,
+        cliff_time: u64,
+        end_time: u64,
+    ) -> Self {
+        Self {
+            recipient_puzzle_hash,
+            clawback_puzzle_hash,
+            cliff_time,
+            end_time,
+            last_payment_time: start_time,
+        }
+    }
+
+    /// The puzzle hash of the streaming payment coin.
+    pub fn puzzle_hash(&self) -> TreeHash {
+        CurriedProgram {
+            program: STREAMING_PAYMENT_PUZZLE_HASH,
+            args: self.args(),
+        }
+        .tree_hash()
+    }
+
+    /// Creates the streaming payment coin from the parent coin, hinted to the recipient.
+    /// The conditions must be output by the parent coin's spend (for example with [`SpendContext::spend_p2_coin`]).
+    pub fn create(&self, parent_coin_id: Bytes32, amount: u64) -> (Conditions, Coin) {
+        let puzzle_hash = self.puzzle_hash().into();
+
+        (
+            Conditions::new().create_hinted_coin(puzzle_hash, amount, self.recipient_puzzle_hash),
+            Coin::new(parent_coin_id, puzzle_hash, amount),
+        )
+    }
+
+    /// The amount of the coin that has vested since the last payment, as of the given timestamp.
+    pub fn vested_amount(&self, amount: u64, timestamp: u64) -> u64 {
+        if timestamp < self.cliff_time || timestamp <= self.last_payment_time {
+            return 0;
+        }
+
+        if timestamp >= self.end_time {
+            return amount;
+        }
+
+        let elapsed = u128::from(timestamp - self.last_payment_time);
+        let duration = u128::from(self.end_time - self.last_payment_time);
+
+        // This can't exceed the amount, since the elapsed time is less than the duration.
+        u64::try_from(u128::from(amount) * elapsed / duration).unwrap_or(amount)
+    }
+
+    /// Pays the amount vested as of the timestamp to the recipient, which must not be in the future.
+    /// Returns the stream and coin holding the remaining amount, unless it has been paid out in full.
+    pub fn claim(
+        &self,
+        ctx: &mut SpendContext,
+        coin: Coin,
+        timestamp: u64,
+    ) -> Result<Option<(Self, Coin)>, SpendError> {
+        let remaining = coin.amount - self.vested_amount(coin.amount, timestamp);
+
+        let spend = self.inner_spend(
+            ctx,
+            StreamingPaymentSolution {
+                my_amount: coin.amount,
+                payment_time: timestamp,
+                my_id: None,
+            },
+        )?;
+        ctx.spend(coin, spend)?;
+
+        if remaining == 0 {
+            return Ok(None);
+        }
+
+        let stream = Self {
+            last_payment_time: timestamp,
+            ..*self
+        };
+
+        let child = Coin::new(coin.coin_id(), stream.puzzle_hash().into(), remaining);
+
+        Ok(Some((stream, child)))
+    }
+
+    /// Cancels the stream, paying the amount vested as of the timestamp to the recipient and the rest to the clawback puzzle hash.
+    /// The timestamp must be in the future, so that the recipient receives at least what has vested so far.
+    /// Returns the conditions that a coin with the clawback puzzle hash must output in the same spend bundle.
+    pub fn cancel(
+        &self,
+        ctx: &mut SpendContext,
+        coin: Coin,
+        timestamp: u64,
+    ) -> Result<Conditions, SpendError> {
+        let spend = self.inner_spend(
+            ctx,
+            StreamingPaymentSolution {
+                my_amount: coin.amount,
+                payment_time: timestamp,
+                my_id: Some(coin.coin_id()),
+            },
+        )?;
+        ctx.spend(coin, spend)?;
+
+        Ok(Conditions::new().create_puzzle_announcement(coin.coin_id().to_vec().into()))
+    }
+
+    fn args(&self) -> StreamingPaymentArgs {
+        StreamingPaymentArgs {
+            mod_hash: STREAMING_PAYMENT_PUZZLE_HASH.into(),
+            recipient_puzzle_hash: self.recipient_puzzle_hash,
+            clawback_puzzle_hash: self.clawback_puzzle_hash,
+            cliff_time: self.cliff_time,
+            end_time: self.end_time,
+            last_payment_time: self.last_payment_time,
+        }
+    }
+
+    fn inner_spend(
+        &self,
+        ctx: &mut SpendContext,
+        solution: StreamingPaymentSolution,
+    ) -> Result<Spend, SpendError> {
+        let streaming_payment_puzzle =
+            ctx.puzzle(STREAMING_PAYMENT_PUZZLE_HASH, &STREAMING_PAYMENT_PUZZLE)?;
+
+        let puzzle = ctx.alloc(&CurriedProgram {
+            program: streaming_payment_puzzle,
+            args: self.args(),
+        })?;
+
+        let solution = ctx.alloc(&solution)?;
+
+        Ok(Spend::new(puzzle, solution))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chia_bls::{DerivableKey, Signature};
+    use chia_protocol::SpendBundle;
+    use chia_puzzles::standard::StandardArgs;
+    use chia_sdk_test::{secret_key, test_transaction, Simulator};
+
+    use super::*;
+
+    #[test]
+    fn test_streaming_payment_puzzle_hash() -> anyhow::Result<()> {
+        let mut ctx = SpendContext::new();
+        let puzzle = ctx.puzzle(STREAMING_PAYMENT_PUZZLE_HASH, &STREAMING_PAYMENT_PUZZLE)?;
+        assert_eq!(ctx.tree_hash(puzzle), STREAMING_PAYMENT_PUZZLE_HASH);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_streaming_payment() -> anyhow::Result<()> {
+        let sim = Simulator::new().await?;
+        let peer = sim.connect().await?;
+        let ctx = &mut SpendContext::new();
+
+        let sender_secret_key = secret_key()?.derive_unhardened(0);
+        let sender_key = sender_secret_key.public_key();
+        let sender_puzzle_hash = StandardArgs::curry_tree_hash(sender_key).into();
+
+        let recipient_puzzle_hash =
+            StandardArgs::curry_tree_hash(secret_key()?.derive_unhardened(1).public_key()).into();
+
+        let coin = sim.mint_coin(sender_puzzle_hash, 1000).await;
+        let other_coin = sim.mint_coin(sender_puzzle_hash, 0).await;
+
+        let start_time = sim.timestamp().await;
+        let stream = StreamingPayment::new(
+            recipient_puzzle_hash,
+            sender_puzzle_hash,
+            start_time,
+            start_time + 100,
+            start_time + 1000,
+        );
+
+        let (create_stream, stream_coin) = stream.create(coin.coin_id(), 1000);
+        ctx.spend_p2_coin(coin, sender_key, create_stream)?;
+
+        test_transaction(
+            &peer,
+            ctx.take_spends(),
+            &[sender_secret_key.clone()],
+            sim.config().genesis_challenge,
+        )
+        .await;
+
+        // Nothing can be claimed before the cliff.
+        sim.pass_time(50).await;
+        stream.claim(ctx, stream_coin, start_time + 50)?;
+
+        let ack = peer
+            .send_transaction(SpendBundle::new(ctx.take_spends(), Signature::default()))
+            .await?;
+        assert_eq!(ack.status, 3);
+
+        // Nor can an amount be claimed before it has vested.
+        stream.claim(ctx, stream_coin, start_time + 200)?;
+
+        let ack = peer
+            .send_transaction(SpendBundle::new(ctx.take_spends(), Signature::default()))
+            .await?;
+        assert_eq!(ack.status, 3);
+
+        // After the cliff, everything vested since the start can be claimed.
+        sim.pass_time(150).await;
+        let (stream, stream_coin) = stream
+            .claim(ctx, stream_coin, start_time + 200)?
+            .expect("missing remaining stream");
+        assert_eq!(stream_coin.amount, 800);
+
+        let stream_coin_parent = stream_coin.parent_coin_info;
+        test_transaction(
+            &peer,
+            ctx.take_spends(),
+            &[],
+            sim.config().genesis_challenge,
+        )
+        .await;
+
+        let payment = Coin::new(stream_coin_parent, recipient_puzzle_hash, 200);
+        assert!(sim.coin_state(payment.coin_id()).await.is_some());
+        assert!(sim.coin_state(stream_coin.coin_id()).await.is_some());
+
+        // The amount continues to vest linearly.
+        sim.pass_time(300).await;
+        assert_eq!(
+            stream.vested_amount(stream_coin.amount, start_time + 500),
+            300
+        );
+
+        let (stream, stream_coin) = stream
+            .claim(ctx, stream_coin, start_time + 500)?
+            .expect("missing remaining stream");
+        assert_eq!(stream_coin.amount, 500);
+
+        test_transaction(
+            &peer,
+            ctx.take_spends(),
+            &[],
+            sim.config().genesis_challenge,
+        )
+        .await;
+
+        let payment = Coin::new(stream_coin.parent_coin_info, recipient_puzzle_hash, 300);
+        assert!(sim.coin_state(payment.coin_id()).await.is_some());
+
+        // The sender can cancel the stream, which pays out what will have vested by the payment time.
+        let cancel = stream.cancel(ctx, stream_coin, start_time + 600)?;
+        ctx.spend_p2_coin(other_coin, sender_key, cancel)?;
+
+        test_transaction(
+            &peer,
+            ctx.take_spends(),
+            &[sender_secret_key],
+            sim.config().genesis_challenge,
+        )
+        .await;
+
+        let payment = Coin::new(stream_coin.coin_id(), recipient_puzzle_hash, 100);
+        let refund = Coin::new(stream_coin.coin_id(), sender_puzzle_hash, 400);
+        assert!(sim.coin_state(payment.coin_id()).await.is_some());
+        assert!(sim.coin_state(refund.coin_id()).await.is_some());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_vested_amount() {
+        let stream = StreamingPayment::new(Bytes32::default(), Bytes32::default(), 100, 200, 1100);
+
+        assert_eq!(stream.vested_amount(1000, 150), 0);
+        assert_eq!(stream.vested_amount(1000, 200), 100);
+        assert_eq!(stream.vested_amount(1000, 600), 500);
+        assert_eq!(stream.vested_amount(1000, 1100), 1000);
+        assert_eq!(stream.vested_amount(1000, 5000), 1000);
+    }
+}
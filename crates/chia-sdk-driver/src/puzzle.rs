@@ -1,7 +1,25 @@
+use chia_puzzles::{
+    cat::CAT_PUZZLE_HASH,
+    did::DID_INNER_PUZZLE_HASH,
+    nft::NFT_STATE_LAYER_PUZZLE_HASH,
+    singleton::{SingletonArgs, SINGLETON_TOP_LAYER_PUZZLE_HASH},
+    standard::STANDARD_PUZZLE_HASH,
+};
 use clvm_traits::FromClvm;
 use clvm_utils::{tree_hash, CurriedProgram, ToTreeHash, TreeHash};
 use clvmr::{Allocator, NodePtr};
 
+/// The kind of a puzzle, which can be used to decide how a coin spend should be parsed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PuzzleKind {
+    Nft,
+    Did,
+    Cat,
+    Singleton,
+    Standard,
+    Unknown,
+}
+
 #[derive(Debug, Clone, Copy)]
 pub enum Puzzle {
     Curried(CurriedPuzzle),
@@ -21,6 +39,36 @@ impl Puzzle {
         )
     }
 
+    /// Identifies the kind of puzzle based on its outer mod hash.
+    /// Singletons are further identified by the mod hash of their curried inner puzzle.
+    pub fn identify(allocator: &Allocator, puzzle: NodePtr) -> PuzzleKind {
+        let Some(puzzle) = CurriedPuzzle::parse(allocator, puzzle) else {
+            return PuzzleKind::Unknown;
+        };
+
+        if puzzle.mod_hash == STANDARD_PUZZLE_HASH {
+            return PuzzleKind::Standard;
+        }
+
+        if puzzle.mod_hash == CAT_PUZZLE_HASH {
+            return PuzzleKind::Cat;
+        }
+
+        if puzzle.mod_hash != SINGLETON_TOP_LAYER_PUZZLE_HASH {
+            return PuzzleKind::Unknown;
+        }
+
+        let Ok(args) = SingletonArgs::<NodePtr>::from_clvm(allocator, puzzle.args) else {
+            return PuzzleKind::Singleton;
+        };
+
+        match CurriedPuzzle::parse(allocator, args.inner_puzzle) {
+            Some(inner) if inner.mod_hash == NFT_STATE_LAYER_PUZZLE_HASH => PuzzleKind::Nft,
+            Some(inner) if inner.mod_hash == DID_INNER_PUZZLE_HASH => PuzzleKind::Did,
+            _ => PuzzleKind::Singleton,
+        }
+    }
+
     pub fn curried_puzzle_hash(&self) -> TreeHash {
         match self {
             Self::Curried(curried) => curried.curried_puzzle_hash,
@@ -97,3 +145,71 @@ pub struct RawPuzzle {
     pub puzzle_hash: TreeHash,
     pub ptr: NodePtr,
 }
+
+#[cfg(test)]
+mod tests {
+    use chia_bls::PublicKey;
+    use chia_protocol::{Bytes32, Coin};
+    use chia_puzzles::{cat::CatArgs, standard::StandardArgs};
+    use clvm_traits::ToNodePtr;
+
+    use crate::{nft_mint, Conditions, Launcher, SpendContext};
+
+    use super::*;
+
+    #[test]
+    fn test_identify_puzzle() -> anyhow::Result<()> {
+        let ctx = &mut SpendContext::new();
+
+        let pk = PublicKey::default();
+        let puzzle_hash = StandardArgs::curry_tree_hash(pk).into();
+        let parent = Coin::new(Bytes32::default(), puzzle_hash, 2);
+
+        let standard_puzzle = ctx.standard_puzzle()?;
+        let p2_puzzle = ctx.alloc(&CurriedProgram {
+            program: standard_puzzle,
+            args: StandardArgs::new(pk),
+        })?;
+        assert_eq!(
+            Puzzle::identify(ctx.allocator(), p2_puzzle),
+            PuzzleKind::Standard
+        );
+
+        let cat_puzzle = ctx.cat_puzzle()?;
+        let cat = ctx.alloc(&CurriedProgram {
+            program: cat_puzzle,
+            args: CatArgs::new(Bytes32::default(), p2_puzzle),
+        })?;
+        assert_eq!(Puzzle::identify(ctx.allocator(), cat), PuzzleKind::Cat);
+
+        let singleton_puzzle = ctx.singleton_top_layer()?;
+        let singleton = ctx.alloc(&CurriedProgram {
+            program: singleton_puzzle,
+            args: SingletonArgs::new(Bytes32::default(), p2_puzzle),
+        })?;
+        assert_eq!(
+            Puzzle::identify(ctx.allocator(), singleton),
+            PuzzleKind::Singleton
+        );
+
+        let (_, did, did_proof) = Launcher::new(parent.coin_id(), 1).create_simple_did(ctx, pk)?;
+        let (_, nft, _) = Launcher::new(did.coin.coin_id(), 1)
+            .mint_nft(ctx, nft_mint(puzzle_hash, Some(&did)))?;
+        ctx.spend_standard_did(&did, did_proof, pk, Conditions::new())?;
+
+        for coin_spend in ctx.take_spends() {
+            let puzzle = coin_spend.puzzle_reveal.to_node_ptr(ctx.allocator_mut())?;
+            let kind = Puzzle::identify(ctx.allocator(), puzzle);
+
+            if coin_spend.coin.puzzle_hash == did.coin.puzzle_hash {
+                assert_eq!(kind, PuzzleKind::Did);
+            } else if coin_spend.coin.coin_id() == nft.coin.parent_coin_info {
+                assert_eq!(kind, PuzzleKind::Nft);
+            } else {
+                assert_eq!(kind, PuzzleKind::Unknown);
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -1,4 +1,6 @@
 use chia_protocol::{Coin, CoinSpend};
+use chia_sdk_types::conditions::{run_puzzle, CreateCoin};
+use clvm_traits::{FromClvm, ToNodePtr};
 use clvmr::{Allocator, NodePtr};
 
 use crate::{DriverError, SpendContext};
@@ -39,4 +41,62 @@ pub trait OuterPuzzleLayer {
         coin: Coin,
         solution: Self::Solution,
     ) -> Result<CoinSpend, DriverError>;
+
+    /// Solves the puzzle and also returns the child coin created with an odd amount.
+    /// Returns [`None`] for the child if there isn't exactly one, in which case
+    /// the caller should fall back to parsing the coin spend.
+    fn solve_with_child(
+        &self,
+        ctx: &mut SpendContext,
+        coin: Coin,
+        solution: Self::Solution,
+    ) -> Result<(CoinSpend, Option<Coin>), DriverError> {
+        let coin_spend = self.solve(ctx, coin, solution)?;
+        let allocator = ctx.allocator_mut();
+        let puzzle = coin_spend
+            .puzzle_reveal
+            .to_node_ptr(allocator)
+            .map_err(DriverError::ToClvm)?;
+        let solution = coin_spend
+            .solution
+            .to_node_ptr(allocator)
+            .map_err(DriverError::ToClvm)?;
+        let child = odd_child(allocator, coin_spend.coin, puzzle, solution)?;
+        Ok((coin_spend, child))
+    }
+}
+
+/// Runs the puzzle and returns the coin created with an odd amount, if there's exactly one.
+pub(crate) fn odd_child(
+    allocator: &mut Allocator,
+    coin: Coin,
+    puzzle: NodePtr,
+    solution: NodePtr,
+) -> Result<Option<Coin>, DriverError> {
+    let output = run_puzzle(allocator, puzzle, solution).map_err(DriverError::Eval)?;
+    let conditions = Vec::<NodePtr>::from_clvm(allocator, output).map_err(DriverError::FromClvm)?;
+
+    let mut child = None;
+
+    for condition in conditions {
+        let Ok(create_coin) = CreateCoin::from_clvm(allocator, condition) else {
+            continue;
+        };
+
+        if create_coin.amount % 2 == 0 {
+            continue;
+        }
+
+        if child.is_some() {
+            return Ok(None);
+        }
+
+        child = Some(Coin::new(
+            coin.coin_id(),
+            create_coin.puzzle_hash,
+            create_coin.amount,
+        ));
+    }
+
+    Ok(child)
 }
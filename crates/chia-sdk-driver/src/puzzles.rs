@@ -1,3 +1,5 @@
 mod cat;
+mod standard;
 
 pub use cat::*;
+pub use standard::*;
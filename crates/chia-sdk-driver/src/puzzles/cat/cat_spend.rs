@@ -7,7 +7,7 @@ use chia_sdk_types::conditions::CreateCoin;
 use clvm_utils::CurriedProgram;
 use clvmr::NodePtr;
 
-use crate::{Spend, SpendContext, SpendError};
+use crate::{Cat, DriverError, Spend, SpendContext, SpendError};
 
 #[derive(Debug, Default)]
 #[must_use]
@@ -48,22 +48,33 @@ impl CatSpend {
         self
     }
 
+    /// Adds a parsed [`Cat`] to the ring, using its coin for the spend.
+    /// Fails if the asset id of the CAT doesn't match the asset id of the ring.
+    pub fn spend_cat(
+        self,
+        cat: &Cat,
+        inner_spend: Spend,
+        lineage_proof: LineageProof,
+        extra_delta: i64,
+    ) -> Result<Self, DriverError> {
+        if cat.asset_id != self.asset_id {
+            return Err(DriverError::MismatchedAssetId);
+        }
+
+        Ok(self.spend(cat.coin, inner_spend, lineage_proof, extra_delta))
+    }
+
+    /// Spends each coin in the ring. The extra delta of a coin is the amount minted (if positive) or melted (if negative)
+    /// by running the TAIL, so the deltas of all coins in the ring must add up to zero.
     pub fn finish(self, ctx: &mut SpendContext) -> Result<(), SpendError> {
         let cat_puzzle_ptr = ctx.cat_puzzle()?;
         let len = self.cat_spends.len();
 
-        let mut total_delta = 0;
-
-        for (index, item) in self.cat_spends.iter().enumerate() {
-            let CatSpendItem {
-                coin,
-                inner_spend,
-                lineage_proof,
-                extra_delta,
-            } = item;
+        // Calculate the delta of each coin, so that the ring can be checked before anything is spent.
+        let mut deltas = Vec::with_capacity(len);
 
-            // Calculate the delta and add it to the subtotal.
-            let output = ctx.run(inner_spend.puzzle(), inner_spend.solution())?;
+        for item in &self.cat_spends {
+            let output = ctx.run(item.inner_spend.puzzle(), item.inner_spend.solution())?;
             let conditions: Vec<NodePtr> = ctx.extract(output)?;
 
             let create_coins = conditions
@@ -71,10 +82,29 @@ impl CatSpend {
                 .filter_map(|ptr| ctx.extract::<CreateCoin>(ptr).ok());
 
             let delta = create_coins.fold(
-                i128::from(coin.amount) - i128::from(*extra_delta),
+                i128::from(item.coin.amount) + i128::from(item.extra_delta),
                 |delta, create_coin| delta - i128::from(create_coin.amount),
             );
 
+            deltas.push(delta);
+        }
+
+        let total: i128 = deltas.iter().sum();
+
+        if total != 0 {
+            return Err(SpendError::UnbalancedCatRing(total));
+        }
+
+        let mut total_delta = 0;
+
+        for (index, (item, delta)) in self.cat_spends.iter().zip(deltas).enumerate() {
+            let CatSpendItem {
+                coin,
+                inner_spend,
+                lineage_proof,
+                extra_delta,
+            } = item;
+
             let prev_subtotal = total_delta;
             total_delta += delta;
 
@@ -91,6 +121,8 @@ impl CatSpend {
                 },
             })?;
 
+            let next_inner_puzzle_hash = ctx.tree_hash(next_cat.inner_spend.puzzle());
+
             let solution = ctx.serialize(&CatSolution {
                 inner_puzzle_solution: inner_spend.solution(),
                 lineage_proof: Some(*lineage_proof),
@@ -98,14 +130,14 @@ impl CatSpend {
                 this_coin_info: *coin,
                 next_coin_proof: CoinProof {
                     parent_coin_info: next_cat.coin.parent_coin_info,
-                    inner_puzzle_hash: ctx.tree_hash(inner_spend.puzzle()).into(),
+                    inner_puzzle_hash: next_inner_puzzle_hash.into(),
                     amount: next_cat.coin.amount,
                 },
                 prev_subtotal: prev_subtotal.try_into()?,
                 extra_delta: *extra_delta,
             })?;
 
-            ctx.insert_coin_spend(CoinSpend::new(*coin, puzzle_reveal, solution));
+            ctx.insert_coin_spend(CoinSpend::new(*coin, puzzle_reveal, solution))?;
         }
 
         Ok(())
@@ -114,6 +146,8 @@ impl CatSpend {
 
 #[cfg(test)]
 mod tests {
+    use chia_bls::DerivableKey;
+    use chia_bls::PublicKey;
     use chia_puzzles::{cat::EverythingWithSignatureTailArgs, standard::StandardArgs};
     use chia_sdk_test::{secret_key, test_transaction, Simulator};
     use chia_sdk_types::conditions::{Condition, RunTail};
@@ -187,6 +221,84 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn test_cat_spend_ring_different_owners() -> anyhow::Result<()> {
+        let sim = Simulator::new().await?;
+        let peer = sim.connect().await?;
+        let ctx = &mut SpendContext::new();
+
+        let sk = secret_key()?;
+        let pk = sk.public_key();
+        let puzzle_hash = StandardArgs::curry_tree_hash(pk).into();
+        let coin = sim.mint_coin(puzzle_hash, 6).await;
+
+        let secret_keys: Vec<_> = (0..3).map(|index| sk.derive_unhardened(index)).collect();
+        let owners: Vec<(PublicKey, Bytes32)> = secret_keys
+            .iter()
+            .map(|sk| {
+                let pk = sk.public_key();
+                (pk, StandardArgs::curry_tree_hash(pk).into())
+            })
+            .collect();
+
+        let mut conditions = Conditions::new();
+        for (index, (_, owner_puzzle_hash)) in owners.iter().enumerate() {
+            conditions = conditions.create_hinted_coin(
+                *owner_puzzle_hash,
+                index as u64 + 1,
+                *owner_puzzle_hash,
+            );
+        }
+
+        let (issue_cat, issuance) = issue_cat_from_coin(ctx, coin.coin_id(), 6, conditions)?;
+        ctx.spend_p2_coin(coin, pk, issue_cat)?;
+
+        let mut cat_spend = CatSpend::new(issuance.asset_id);
+
+        for (index, (owner_pk, owner_puzzle_hash)) in owners.iter().enumerate() {
+            let amount = index as u64 + 1;
+            let cat = Cat::new(
+                Coin::new(
+                    issuance.eve_coin.coin_id(),
+                    CatArgs::curry_tree_hash(issuance.asset_id, (*owner_puzzle_hash).into()).into(),
+                    amount,
+                ),
+                issuance.asset_id,
+                (*owner_puzzle_hash).into(),
+                None,
+            );
+
+            // Consolidate the whole ring into the first owner.
+            let conditions = if index == 0 {
+                Conditions::new().create_hinted_coin(*owner_puzzle_hash, 6, *owner_puzzle_hash)
+            } else {
+                Conditions::new()
+            };
+
+            cat_spend = cat_spend.spend_cat(
+                &cat,
+                conditions.p2_spend(ctx, *owner_pk)?,
+                issuance.lineage_proof,
+                0,
+            )?;
+        }
+
+        cat_spend.finish(ctx)?;
+
+        let mut signing_keys = secret_keys;
+        signing_keys.push(sk);
+
+        test_transaction(
+            &peer,
+            ctx.take_spends(),
+            &signing_keys,
+            sim.config().genesis_challenge,
+        )
+        .await;
+
+        Ok(())
+    }
+
     #[tokio::test]
     async fn test_cat_spend() -> anyhow::Result<()> {
         let sim = Simulator::new().await?;
@@ -230,6 +342,97 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn test_cat_spend_ring_extra_deltas() -> anyhow::Result<()> {
+        let sim = Simulator::new().await?;
+        let peer = sim.connect().await?;
+        let ctx = &mut SpendContext::new();
+
+        let sk = secret_key()?;
+        let pk = sk.public_key();
+
+        let puzzle_hash = StandardArgs::curry_tree_hash(pk).into();
+        let coin = sim.mint_coin(puzzle_hash, 10000).await;
+
+        let conditions = Conditions::new()
+            .create_hinted_coin(puzzle_hash, 4000, puzzle_hash)
+            .create_hinted_coin(puzzle_hash, 6000, puzzle_hash);
+        let (issue_cat, issuance) = issue_cat_from_key(ctx, coin.coin_id(), pk, 10000, conditions)?;
+
+        ctx.spend_p2_coin(coin, pk, issue_cat)?;
+
+        let tail = ctx.everything_with_signature_tail_puzzle()?;
+        let tail_program = ctx.alloc(&CurriedProgram {
+            program: tail,
+            args: EverythingWithSignatureTailArgs::new(pk),
+        })?;
+        let run_tail = Condition::Other(ctx.alloc(&RunTail::new(tail_program, ()))?);
+
+        let cat_puzzle_hash =
+            CatArgs::curry_tree_hash(issuance.asset_id, puzzle_hash.into()).into();
+        let first = Coin::new(issuance.eve_coin.coin_id(), cat_puzzle_hash, 4000);
+        let second = Coin::new(issuance.eve_coin.coin_id(), cat_puzzle_hash, 6000);
+
+        // The first coin mints 500 and the second melts 2000, so the outputs total 1500 less than the inputs.
+        // Neither coin outputs its own amount plus its extra delta, so value has to move around the ring.
+        let ring = |ctx: &mut SpendContext,
+                    first_extra_delta: i64,
+                    second_extra_delta: i64|
+         -> anyhow::Result<()> {
+            let first_spend = Conditions::new()
+                .create_hinted_coin(puzzle_hash, 1500, puzzle_hash)
+                .condition(run_tail.clone())
+                .p2_spend(ctx, pk)?;
+            let second_spend = Conditions::new()
+                .create_hinted_coin(puzzle_hash, 7000, puzzle_hash)
+                .condition(run_tail.clone())
+                .p2_spend(ctx, pk)?;
+
+            CatSpend::new(issuance.asset_id)
+                .spend(
+                    first,
+                    first_spend,
+                    issuance.lineage_proof,
+                    first_extra_delta,
+                )
+                .spend(
+                    second,
+                    second_spend,
+                    issuance.lineage_proof,
+                    second_extra_delta,
+                )
+                .finish(ctx)?;
+
+            Ok(())
+        };
+
+        // Without the deltas, or when they don't make up the difference, the ring doesn't balance.
+        let unbalanced = [(0, 0, 1500), (500, -1000, 1000)];
+        let issuance_spends = ctx.spends().len();
+
+        for (first_extra_delta, second_extra_delta, total) in unbalanced {
+            let error = ring(ctx, first_extra_delta, second_extra_delta)
+                .expect_err("expected unbalanced ring");
+            assert!(matches!(
+                error.downcast_ref::<SpendError>(),
+                Some(SpendError::UnbalancedCatRing(actual)) if *actual == total
+            ));
+        }
+        assert_eq!(ctx.spends().len(), issuance_spends);
+
+        ring(ctx, 500, -2000)?;
+
+        test_transaction(
+            &peer,
+            ctx.take_spends(),
+            &[sk],
+            sim.config().genesis_challenge,
+        )
+        .await;
+
+        Ok(())
+    }
+
     #[tokio::test]
     async fn test_cat_melt() -> anyhow::Result<()> {
         let sim = Simulator::new().await?;
@@ -277,4 +480,32 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn test_spend_cat_mismatched_asset_id() -> anyhow::Result<()> {
+        let ctx = &mut SpendContext::new();
+
+        let pk = secret_key()?.public_key();
+        let puzzle_hash = StandardArgs::curry_tree_hash(pk);
+        let cat = Cat::new(
+            Coin::new(Bytes32::default(), puzzle_hash.into(), 1),
+            Bytes32::new([1; 32]),
+            puzzle_hash,
+            None,
+        );
+
+        let result = CatSpend::new(Bytes32::new([2; 32])).spend_cat(
+            &cat,
+            Conditions::new().p2_spend(ctx, pk)?,
+            LineageProof {
+                parent_parent_coin_id: Bytes32::default(),
+                parent_inner_puzzle_hash: Bytes32::default(),
+                parent_amount: 1,
+            },
+            0,
+        );
+        assert!(matches!(result, Err(DriverError::MismatchedAssetId)));
+
+        Ok(())
+    }
 }
@@ -112,7 +112,7 @@ where
     })?;
 
     let puzzle_reveal = ctx.serialize(&puzzle)?;
-    ctx.insert_coin_spend(CoinSpend::new(eve_coin, puzzle_reveal, solution));
+    ctx.insert_coin_spend(CoinSpend::new(eve_coin, puzzle_reveal, solution))?;
 
     let chained_spend = Conditions::new().create_hinted_coin(puzzle_hash, amount, puzzle_hash);
 
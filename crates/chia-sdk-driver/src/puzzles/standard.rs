@@ -0,0 +1,47 @@
+use chia_bls::PublicKey;
+use chia_protocol::Bytes32;
+use chia_puzzles::{standard::StandardArgs, DeriveSynthetic};
+
+/// Derives the synthetic key from a public key, using the default hidden puzzle.
+/// This is the key that gets curried into the standard puzzle.
+pub fn synthetic_key(public_key: PublicKey) -> PublicKey {
+    public_key.derive_synthetic()
+}
+
+/// The puzzle hash of the standard puzzle for a public key which isn't synthetic yet.
+pub fn standard_puzzle_hash(public_key: PublicKey) -> Bytes32 {
+    StandardArgs::curry_tree_hash(synthetic_key(public_key)).into()
+}
+
+#[cfg(test)]
+mod tests {
+    use chia_bls::{derive_keys::master_to_wallet_unhardened, SecretKey};
+    use hex_literal::hex;
+
+    use super::*;
+
+    #[test]
+    fn test_synthetic_key() -> anyhow::Result<()> {
+        let sk = SecretKey::from_bytes(&hex!(
+            "6bb19282e27bc6e7e397fb19efc2627a412410fdfd13bf14f4ce5bfdce084c71"
+        ))?;
+        let pk = master_to_wallet_unhardened(&sk.public_key(), 0);
+
+        let synthetic = synthetic_key(pk);
+        assert_eq!(
+            synthetic.to_bytes(),
+            hex!("b0c8cf08fdbe7fdb7bb1795740153b944c32364b100c372a05833554cb97794563b096cb5f57bfa09f38d7aebb48704e")
+        );
+
+        assert_eq!(
+            standard_puzzle_hash(pk),
+            StandardArgs::curry_tree_hash(synthetic).into()
+        );
+        assert_ne!(
+            standard_puzzle_hash(pk),
+            StandardArgs::curry_tree_hash(pk).into()
+        );
+
+        Ok(())
+    }
+}
@@ -1,7 +1,11 @@
 use std::collections::HashMap;
 
-use chia_bls::PublicKey;
-use chia_protocol::{Bytes32, Coin, CoinSpend, Program};
+use chia_bls::{sign, PublicKey, SecretKey, Signature};
+use chia_consensus::gen::{
+    conditions::EmptyVisitor, run_block_generator::run_block_generator,
+    solution_generator::solution_generator,
+};
+use chia_protocol::{Bytes32, Coin, CoinSpend, Program, SpendBundle};
 use chia_puzzles::{
     cat::{
         CAT_PUZZLE, CAT_PUZZLE_HASH, EVERYTHING_WITH_SIGNATURE_TAIL_PUZZLE,
@@ -22,10 +26,11 @@ use chia_puzzles::{
     standard::{StandardArgs, STANDARD_PUZZLE, STANDARD_PUZZLE_HASH},
     Proof,
 };
-use chia_sdk_types::conditions::NewNftOwner;
+use chia_sdk_signer::RequiredSignature;
+use chia_sdk_types::{conditions::NewNftOwner, MAX_BLOCK_COST};
 use clvm_traits::{FromClvm, FromNodePtr, ToClvm, ToNodePtr};
-use clvm_utils::{tree_hash, ToTreeHash, TreeHash};
-use clvmr::{run_program, serde::node_from_bytes, Allocator, ChiaDialect, NodePtr};
+use clvm_utils::{tree_hash_atom, tree_hash_pair, ToTreeHash, TreeHash};
+use clvmr::{run_program, serde::node_from_bytes, Allocator, ChiaDialect, NodePtr, SExp};
 
 use crate::{spend_error::SpendError, Conditions, Did, DriverError, Nft, Spend};
 
@@ -34,7 +39,9 @@ use crate::{spend_error::SpendError, Conditions, Did, DriverError, Nft, Spend};
 pub struct SpendContext {
     allocator: Allocator,
     puzzles: HashMap<TreeHash, NodePtr>,
+    tree_hashes: HashMap<NodePtr, TreeHash>,
     coin_spends: Vec<CoinSpend>,
+    designated_fee: u64,
 }
 
 impl SpendContext {
@@ -49,30 +56,149 @@ impl SpendContext {
     }
 
     /// Get a mutable reference to the [`Allocator`].
+    /// This clears the cached tree hashes, since the allocator could be reset through it.
+    /// Prefer [`SpendContext::alloc`] for allocating values, which keeps the cache.
+    /// To reset the allocator, use [`SpendContext::replace_allocator`] instead, so that cached puzzles aren't reused.
     pub fn allocator_mut(&mut self) -> &mut Allocator {
+        self.tree_hashes.clear();
         &mut self.allocator
     }
 
+    /// Replaces the [`Allocator`] and clears the puzzle and tree hash caches, returning the old allocator.
+    pub fn replace_allocator(&mut self, allocator: Allocator) -> Allocator {
+        self.puzzles.clear();
+        self.tree_hashes.clear();
+        std::mem::replace(&mut self.allocator, allocator)
+    }
+
     /// Get a reference to the list of coin spends.
     pub fn spends(&self) -> &[CoinSpend] {
         &self.coin_spends
     }
 
     /// Take the coin spends out of the [`SpendContext`].
+    /// This also resets the designated fee, since it belongs to the spends.
     pub fn take_spends(&mut self) -> Vec<CoinSpend> {
+        self.designated_fee = 0;
         std::mem::take(&mut self.coin_spends)
     }
 
-    /// Add a [`CoinSpend`] to the list.
-    pub fn insert_coin_spend(&mut self, coin_spend: CoinSpend) {
+    /// The total fee reserved by coins designated with [`SpendContext::designate_fee_coin`].
+    pub fn designated_fee(&self) -> u64 {
+        self.designated_fee
+    }
+
+    /// Spends a standard p2 coin which only pays the fee, returning its change coin if there is any.
+    /// The change is sent back to the same puzzle hash, and the fee is added to [`SpendContext::designated_fee`].
+    pub fn designate_fee_coin(
+        &mut self,
+        coin: Coin,
+        synthetic_key: PublicKey,
+        fee: u64,
+    ) -> Result<Option<Coin>, SpendError> {
+        let Some(change) = coin.amount.checked_sub(fee) else {
+            return Err(SpendError::InsufficientFeeCoin {
+                fee,
+                amount: coin.amount,
+            });
+        };
+
+        let mut conditions = Conditions::new().reserve_fee(fee);
+
+        if change > 0 {
+            conditions = conditions.create_coin(coin.puzzle_hash, change);
+        }
+
+        self.spend_p2_coin(coin, synthetic_key, conditions)?;
+        self.designated_fee += fee;
+
+        Ok((change > 0).then(|| Coin::new(coin.coin_id(), coin.puzzle_hash, change)))
+    }
+
+    /// Add a [`CoinSpend`] to the list, unless the coin is already being spent.
+    pub fn insert_coin_spend(&mut self, coin_spend: CoinSpend) -> Result<(), SpendError> {
+        let coin_id = coin_spend.coin.coin_id();
+
+        if self
+            .coin_spends
+            .iter()
+            .any(|item| item.coin.coin_id() == coin_id)
+        {
+            return Err(SpendError::DuplicateCoinSpend(coin_id));
+        }
+
         self.coin_spends.push(coin_spend);
+        Ok(())
     }
 
     /// Serializes a [`Spend`] and adds it to the list of coin spends.
     pub fn spend(&mut self, coin: Coin, spend: Spend) -> Result<(), SpendError> {
         let puzzle_reveal = self.serialize(&spend.puzzle())?;
         let solution = self.serialize(&spend.solution())?;
-        self.insert_coin_spend(CoinSpend::new(coin, puzzle_reveal, solution));
+        self.insert_coin_spend(CoinSpend::new(coin, puzzle_reveal, solution))
+    }
+
+    /// Takes the coin spends and signs them with the given secret keys, returning a spend bundle ready to broadcast.
+    /// Fails if a required signature's public key doesn't match any of the secret keys.
+    pub fn spend_bundle(
+        &mut self,
+        secret_keys: &[SecretKey],
+        genesis_challenge: Bytes32,
+    ) -> Result<SpendBundle, DriverError> {
+        let required_signatures = RequiredSignature::from_coin_spends(
+            &mut Allocator::new(),
+            &self.coin_spends,
+            genesis_challenge,
+        )?;
+
+        let key_pairs: HashMap<PublicKey, &SecretKey> = secret_keys
+            .iter()
+            .map(|secret_key| (secret_key.public_key(), secret_key))
+            .collect();
+
+        let mut aggregated_signature = Signature::default();
+
+        for required in required_signatures {
+            let Some(secret_key) = key_pairs.get(&required.public_key()) else {
+                return Err(DriverError::MissingKey(
+                    required.public_key().to_bytes().into(),
+                ));
+            };
+            aggregated_signature += &sign(secret_key, required.final_message());
+        }
+
+        Ok(SpendBundle::new(self.take_spends(), aggregated_signature))
+    }
+
+    /// Estimates the CLVM cost of the coin spends by running them in a block generator.
+    /// The coin spends are left in place, so they can still be taken afterward.
+    ///
+    /// This doesn't take a genesis challenge, since signatures aren't checked and the cost
+    /// doesn't depend on the network.
+    pub fn estimate_cost(&self) -> Result<u64, DriverError> {
+        generator_cost(&self.coin_spends)
+    }
+
+    /// Estimates the CLVM cost of each coin spend individually, paired with its coin id.
+    /// The costs include some block generator overhead, so they won't sum to [`SpendContext::estimate_cost`].
+    pub fn per_spend_costs(&self) -> Result<Vec<(Bytes32, u64)>, DriverError> {
+        self.coin_spends
+            .iter()
+            .map(|coin_spend| {
+                let cost = generator_cost(std::slice::from_ref(coin_spend))?;
+                Ok((coin_spend.coin.coin_id(), cost))
+            })
+            .collect()
+    }
+
+    /// Checks that the estimated cost of the coin spends doesn't exceed the maximum.
+    pub fn assert_within_cost(&self, max: u64) -> Result<(), DriverError> {
+        let cost = self.estimate_cost()?;
+
+        if cost > max {
+            return Err(DriverError::CostExceeded { cost, max });
+        }
+
         Ok(())
     }
 
@@ -93,8 +219,39 @@ impl SpendContext {
     }
 
     /// Compute the tree hash of a node pointer.
-    pub fn tree_hash(&self, ptr: NodePtr) -> TreeHash {
-        tree_hash(&self.allocator, ptr)
+    /// The hash of every node visited is cached, so shared subtrees such as puzzle mods are only walked once.
+    pub fn tree_hash(&mut self, ptr: NodePtr) -> TreeHash {
+        let mut stack = vec![ptr];
+        let mut hash = TreeHash::new([0; 32]);
+
+        // A pair stays on the stack until both of its children have been hashed.
+        // The last node popped is `ptr` itself, since it's at the bottom of the stack.
+        while let Some(&node) = stack.last() {
+            if let Some(cached) = self.tree_hashes.get(&node) {
+                hash = *cached;
+                stack.pop();
+                continue;
+            }
+
+            match self.allocator.sexp(node) {
+                SExp::Atom => hash = tree_hash_atom(self.allocator.atom(node).as_ref()),
+                SExp::Pair(first, rest) => {
+                    let (Some(first_hash), Some(rest_hash)) =
+                        (self.tree_hashes.get(&first), self.tree_hashes.get(&rest))
+                    else {
+                        stack.push(first);
+                        stack.push(rest);
+                        continue;
+                    };
+                    hash = tree_hash_pair(*first_hash, *rest_hash);
+                }
+            }
+
+            self.tree_hashes.insert(node, hash);
+            stack.pop();
+        }
+
+        hash
     }
 
     /// Run a puzzle with a solution and return the result.
@@ -118,6 +275,11 @@ impl SpendContext {
         Ok(Program::from_node_ptr(&self.allocator, ptr)?)
     }
 
+    /// Serialize a value that has already been allocated and return a `Program`.
+    pub fn serialize_ptr(&self, ptr: NodePtr) -> Result<Program, SpendError> {
+        Ok(Program::from_node_ptr(&self.allocator, ptr)?)
+    }
+
     /// Allocate the standard puzzle and return its pointer.
     pub fn standard_puzzle(&mut self) -> Result<NodePtr, SpendError> {
         self.puzzle(STANDARD_PUZZLE_HASH, &STANDARD_PUZZLE)
@@ -206,11 +368,11 @@ impl SpendContext {
         puzzle_hash: TreeHash,
         puzzle_bytes: &[u8],
     ) -> Result<NodePtr, SpendError> {
-        if let Some(puzzle) = self.puzzles.get(&puzzle_hash) {
-            Ok(*puzzle)
+        if let Some(puzzle) = self.get_puzzle(&puzzle_hash) {
+            Ok(puzzle)
         } else {
             let puzzle = node_from_bytes(&mut self.allocator, puzzle_bytes)?;
-            self.puzzles.insert(puzzle_hash, puzzle);
+            self.preload(puzzle_hash, puzzle);
             Ok(puzzle)
         }
     }
@@ -248,7 +410,7 @@ impl SpendContext {
             .p2_spend(self, synthetic_key)?;
 
         let (did_spend, new_did, new_proof) = did.spend(self, lineage_proof, p2_spend)?;
-        self.insert_coin_spend(did_spend);
+        self.insert_coin_spend(did_spend)?;
 
         Ok((new_did, new_proof))
     }
@@ -276,7 +438,7 @@ impl SpendContext {
                 extra_conditions,
             )?;
 
-            self.insert_coin_spend(cs);
+            self.insert_coin_spend(cs)?;
             return Ok((conds, new_nft, lp));
         }
 
@@ -288,9 +450,45 @@ impl SpendContext {
             extra_conditions,
         )?;
 
-        self.insert_coin_spend(cs);
+        self.insert_coin_spend(cs)?;
         Ok((Conditions::new(), new_nft, lp))
     }
+
+    /// Assigns an NFT to a DID, and spends the DID to approve the assignment in the same call.
+    /// Both are owned by the synthetic key, and the NFT is sent to the new owner puzzle hash.
+    pub fn reassign_nft_owner<M, N>(
+        &mut self,
+        nft: &Nft<M>,
+        nft_proof: Proof,
+        did: &Did<N>,
+        did_proof: Proof,
+        synthetic_key: PublicKey,
+        new_owner: Bytes32,
+    ) -> Result<(Nft<M>, Proof, Did<N>, Proof), DriverError>
+    where
+        M: ToClvm<NodePtr> + FromClvm<NodePtr> + Clone + ToTreeHash,
+        N: ToClvm<NodePtr> + FromClvm<NodePtr> + Clone + ToTreeHash,
+    {
+        let new_nft_owner = NewNftOwner::new(
+            Some(did.launcher_id),
+            Vec::new(),
+            Some(did.singleton_inner_puzzle_hash().into()),
+        );
+
+        let (did_conditions, new_nft, new_nft_proof) = self.spend_standard_nft(
+            nft,
+            nft_proof,
+            synthetic_key,
+            new_owner,
+            Some(new_nft_owner),
+            Conditions::new(),
+        )?;
+
+        let (new_did, new_did_proof) =
+            self.spend_standard_did(did, did_proof, synthetic_key, did_conditions)?;
+
+        Ok((new_nft, new_nft_proof, new_did, new_did_proof))
+    }
 }
 
 impl From<Allocator> for SpendContext {
@@ -298,7 +496,9 @@ impl From<Allocator> for SpendContext {
         Self {
             allocator,
             puzzles: HashMap::new(),
+            tree_hashes: HashMap::new(),
             coin_spends: Vec::new(),
+            designated_fee: 0,
         }
     }
 }
@@ -308,3 +508,247 @@ impl From<SpendContext> for Allocator {
         ctx.allocator
     }
 }
+
+fn generator_cost(coin_spends: &[CoinSpend]) -> Result<u64, DriverError> {
+    let generator = solution_generator(coin_spends.iter().map(|coin_spend| {
+        (
+            coin_spend.coin,
+            coin_spend.puzzle_reveal.clone(),
+            coin_spend.solution.clone(),
+        )
+    }))
+    .map_err(SpendError::Io)?;
+
+    let conds = run_block_generator::<&[u8], EmptyVisitor>(
+        &mut Allocator::new(),
+        &generator,
+        &[],
+        MAX_BLOCK_COST,
+        0,
+    )
+    .map_err(DriverError::Validation)?;
+
+    Ok(conds.cost)
+}
+
+#[cfg(test)]
+mod tests {
+    use chia_bls::DerivableKey;
+    use chia_sdk_test::{secret_key, test_transaction, Simulator};
+    use chia_sdk_types::conditions::{parse_bundle_conditions, Condition, ReserveFee};
+
+    use clvm_utils::tree_hash;
+
+    use crate::{PuzzleLayer, SingletonLayer, TransparentLayer};
+
+    use super::*;
+
+    #[test]
+    fn test_duplicate_coin_spend() -> anyhow::Result<()> {
+        let ctx = &mut SpendContext::new();
+
+        let pk = PublicKey::default();
+        let puzzle_hash = StandardArgs::curry_tree_hash(pk).into();
+        let coin = Coin::new(Bytes32::default(), puzzle_hash, 1);
+
+        ctx.spend_p2_coin(coin, pk, Conditions::new())?;
+
+        let result = ctx.spend_p2_coin(coin, pk, Conditions::new());
+        assert!(matches!(
+            result,
+            Err(SpendError::DuplicateCoinSpend(coin_id)) if coin_id == coin.coin_id()
+        ));
+        assert_eq!(ctx.spends().len(), 1);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_designate_fee_coin() -> anyhow::Result<()> {
+        let sim = Simulator::new().await?;
+        let peer = sim.connect().await?;
+        let ctx = &mut SpendContext::new();
+
+        let sk = secret_key()?;
+        let pk = sk.public_key();
+
+        let puzzle_hash = StandardArgs::curry_tree_hash(pk).into();
+        let coin = sim.mint_coin(puzzle_hash, 1000).await;
+
+        assert!(matches!(
+            ctx.designate_fee_coin(coin, pk, 1001),
+            Err(SpendError::InsufficientFeeCoin {
+                fee: 1001,
+                amount: 1000
+            })
+        ));
+        assert!(ctx.spends().is_empty());
+
+        let change = ctx
+            .designate_fee_coin(coin, pk, 300)?
+            .expect("expected change coin");
+        assert_eq!(change.amount, 700);
+        assert_eq!(ctx.designated_fee(), 300);
+
+        let coin_spends = ctx.take_spends();
+        assert_eq!(ctx.designated_fee(), 0);
+
+        let conditions = parse_bundle_conditions(
+            &mut Allocator::new(),
+            &SpendBundle::new(coin_spends.clone(), Signature::default()),
+        )?;
+        assert!(conditions[0]
+            .1
+            .contains(&Condition::ReserveFee(ReserveFee::new(300))));
+
+        test_transaction(&peer, coin_spends, &[sk], sim.config().genesis_challenge).await;
+
+        let coin_state = sim
+            .coin_state(change.coin_id())
+            .await
+            .expect("expected change coin");
+        assert_eq!(coin_state.coin, change);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_assert_within_cost() -> anyhow::Result<()> {
+        let ctx = &mut SpendContext::new();
+
+        let pk = PublicKey::default();
+        let puzzle_hash = StandardArgs::curry_tree_hash(pk).into();
+
+        for index in 0..50 {
+            let coin = Coin::new(Bytes32::new([index; 32]), puzzle_hash, 1);
+            ctx.spend_p2_coin(coin, pk, Conditions::new().create_coin(puzzle_hash, 1))?;
+        }
+
+        let cost = ctx.estimate_cost()?;
+
+        ctx.assert_within_cost(cost)?;
+
+        assert!(matches!(
+            ctx.assert_within_cost(cost - 1),
+            Err(DriverError::CostExceeded { cost: actual, max }) if actual == cost && max == cost - 1
+        ));
+
+        assert_eq!(ctx.take_spends().len(), 50);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_per_spend_costs() -> anyhow::Result<()> {
+        let ctx = &mut SpendContext::new();
+
+        let pk = PublicKey::default();
+        let puzzle_hash = StandardArgs::curry_tree_hash(pk).into();
+
+        let cheap = Coin::new(Bytes32::new([1; 32]), puzzle_hash, 1);
+        ctx.spend_p2_coin(cheap, pk, Conditions::new().create_coin(puzzle_hash, 1))?;
+
+        let expensive = Coin::new(Bytes32::new([2; 32]), puzzle_hash, 10);
+        let mut conditions = Conditions::new();
+        for index in 0..10 {
+            conditions = conditions.create_coin(Bytes32::new([index; 32]), 1);
+        }
+        ctx.spend_p2_coin(expensive, pk, conditions)?;
+
+        let costs = ctx.per_spend_costs()?;
+        assert_eq!(costs.len(), 2);
+        assert_eq!(costs[0].0, cheap.coin_id());
+        assert_eq!(costs[1].0, expensive.coin_id());
+        assert!(costs[1].1 > costs[0].1);
+
+        let total = ctx.estimate_cost()?;
+        assert!(costs.iter().map(|(_, cost)| cost).sum::<u64>() >= total);
+
+        assert_eq!(ctx.take_spends().len(), 2);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_serialize_ptr() -> anyhow::Result<()> {
+        let ctx = &mut SpendContext::new();
+
+        let ptr = ctx.alloc(&(1, (2, 3)))?;
+        let program = ctx.serialize_ptr(ptr)?;
+
+        assert_eq!(program, ctx.serialize(&(1, (2, 3)))?);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_tree_hash_cache() -> anyhow::Result<()> {
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<SpendContext>();
+
+        let ctx = &mut SpendContext::new();
+
+        let mod_ptr = ctx.singleton_top_layer()?;
+        assert_eq!(ctx.tree_hash(mod_ptr), SINGLETON_TOP_LAYER_PUZZLE_HASH);
+
+        // Building a layer doesn't clear the cache, so the mod isn't walked again.
+        let inner_puzzle = ctx.alloc(&1)?;
+        let layer = SingletonLayer {
+            launcher_id: Bytes32::default(),
+            inner_puzzle: TransparentLayer::<false>::new(
+                ctx.tree_hash(inner_puzzle),
+                Some(inner_puzzle),
+            ),
+        };
+        let puzzle = layer.construct_puzzle(ctx)?;
+        assert!(ctx.tree_hashes.contains_key(&mod_ptr));
+        assert_eq!(ctx.tree_hash(puzzle), layer.tree_hash());
+        assert_eq!(ctx.tree_hash(puzzle), tree_hash(ctx.allocator(), puzzle));
+
+        // Restoring the allocator reuses the node pointer for a different value.
+        let checkpoint = ctx.allocator().checkpoint();
+        let ptr = ctx.alloc(&(1, 2))?;
+        assert_eq!(ctx.tree_hash(ptr), (1, 2).tree_hash());
+
+        ctx.allocator_mut().restore_checkpoint(&checkpoint);
+        let other = ctx.alloc(&(3, 4))?;
+        assert_eq!(other, ptr);
+        assert_eq!(ctx.tree_hash(other), (3, 4).tree_hash());
+
+        ctx.replace_allocator(Allocator::new());
+        assert_eq!(ctx.get_puzzle(&SINGLETON_TOP_LAYER_PUZZLE_HASH), None);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_spend_bundle() -> anyhow::Result<()> {
+        let sim = Simulator::new().await?;
+        let peer = sim.connect().await?;
+        let ctx = &mut SpendContext::new();
+
+        let sk = secret_key()?;
+        let pk = sk.public_key();
+        let puzzle_hash = StandardArgs::curry_tree_hash(pk).into();
+
+        let coin = sim.mint_coin(puzzle_hash, 1).await;
+        ctx.spend_p2_coin(coin, pk, Conditions::new().create_coin(puzzle_hash, 1))?;
+
+        let other_sk = sk.derive_unhardened(0);
+        assert!(matches!(
+            ctx.spend_bundle(&[other_sk], sim.config().genesis_challenge),
+            Err(DriverError::MissingKey(key)) if key == pk.to_bytes().into()
+        ));
+
+        // The spends are kept if signing fails.
+        assert_eq!(ctx.spends().len(), 1);
+
+        let spend_bundle = ctx.spend_bundle(&[sk], sim.config().genesis_challenge)?;
+        assert!(ctx.spends().is_empty());
+
+        let ack = peer.send_transaction(spend_bundle).await?;
+        assert_eq!(ack.status, 1);
+
+        Ok(())
+    }
+}
@@ -1,5 +1,6 @@
 use std::num::TryFromIntError;
 
+use chia_protocol::Bytes32;
 use clvm_traits::{FromClvmError, ToClvmError};
 use clvmr::reduction::EvalErr;
 use thiserror::Error;
@@ -26,4 +27,16 @@ pub enum SpendError {
     /// An error occurred while casting an integer.
     #[error("failed to cast integer: {0}")]
     FromInt(#[from] TryFromIntError),
+
+    /// The coin has already been spent in the same context, which would result in a double spend.
+    #[error("duplicate coin spend: {0}")]
+    DuplicateCoinSpend(Bytes32),
+
+    /// The fee is larger than the amount of the coin designated to pay it.
+    #[error("fee of {fee} exceeds the fee coin amount of {amount}")]
+    InsufficientFeeCoin { fee: u64, amount: u64 },
+
+    /// The deltas of the coins in a CAT ring, including their extra deltas, don't add up to zero.
+    #[error("CAT ring is unbalanced, the deltas add up to {0} instead of zero")]
+    UnbalancedCatRing(i128),
 }
@@ -0,0 +1,51 @@
+use chia_protocol::Bytes32;
+use chia_puzzles::{
+    cat::{CatArgs, CAT_PUZZLE_HASH},
+    offer::SETTLEMENT_PAYMENTS_PUZZLE_HASH,
+    singleton::{SingletonArgs, SINGLETON_TOP_LAYER_PUZZLE_HASH},
+};
+use chia_sdk_driver::{Puzzle, PuzzleKind};
+use clvm_traits::FromClvm;
+use clvmr::{Allocator, NodePtr};
+
+/// The kind of asset paid to a settlement payments puzzle.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AssetKind {
+    Xch,
+    Cat(Bytes32),
+    Nft(Bytes32),
+    Unknown(Bytes32),
+}
+
+impl AssetKind {
+    /// Identifies the asset kind from the puzzle that payments are requested with.
+    /// The asset id or launcher id is extracted from the curried arguments, if applicable.
+    pub fn from_puzzle(allocator: &Allocator, puzzle: NodePtr) -> Self {
+        let parsed = Puzzle::parse(allocator, puzzle);
+        let puzzle_hash = parsed.curried_puzzle_hash();
+
+        if puzzle_hash == SETTLEMENT_PAYMENTS_PUZZLE_HASH {
+            return Self::Xch;
+        }
+
+        let Some(curried) = parsed.as_curried() else {
+            return Self::Unknown(puzzle_hash.into());
+        };
+
+        if curried.mod_hash == CAT_PUZZLE_HASH {
+            if let Ok(args) = CatArgs::<NodePtr>::from_clvm(allocator, curried.args) {
+                return Self::Cat(args.asset_id);
+            }
+        }
+
+        if curried.mod_hash == SINGLETON_TOP_LAYER_PUZZLE_HASH
+            && Puzzle::identify(allocator, puzzle) == PuzzleKind::Nft
+        {
+            if let Ok(args) = SingletonArgs::<NodePtr>::from_clvm(allocator, curried.args) {
+                return Self::Nft(args.singleton_struct.launcher_id);
+            }
+        }
+
+        Self::Unknown(puzzle_hash.into())
+    }
+}
@@ -8,4 +8,7 @@ pub enum OfferError {
 
     #[error("to clvm error: {0}")]
     ToClvm(#[from] ToClvmError),
+
+    #[error("total requested amount overflows a u64")]
+    AmountOverflow,
 }
@@ -1,3 +1,4 @@
+mod asset_kind;
 mod compression;
 mod encoding;
 mod error;
@@ -6,6 +7,7 @@ mod offer_builder;
 mod requested_payments;
 mod settlement;
 
+pub use asset_kind::*;
 pub use compression::*;
 pub use encoding::*;
 pub use error::*;
@@ -1,10 +1,12 @@
 use chia_bls::Signature;
 use chia_protocol::{CoinSpend, Program, SpendBundle};
-use chia_puzzles::offer::NotarizedPayment;
+use chia_puzzles::offer::{NotarizedPayment, Payment};
 use chia_sdk_driver::{SpendContext, SpendError};
+use clvm_traits::ToNodePtr;
+use clvmr::Allocator;
 use indexmap::IndexMap;
 
-use crate::{parse_payments, payment_coin_spend};
+use crate::{parse_payments, payment_coin_spend, AssetKind, OfferError};
 
 #[derive(Debug)]
 pub struct Offer {
@@ -67,6 +69,47 @@ impl Offer {
         &self.requested_payments
     }
 
+    /// The total amount requested of each kind of asset, in the order they were requested.
+    pub fn requested_assets(&self) -> Result<Vec<(AssetKind, u64)>, OfferError> {
+        let mut allocator = Allocator::new();
+        let mut requested_assets = IndexMap::<AssetKind, u64>::new();
+
+        for (puzzle_reveal, notarized_payments) in &self.requested_payments {
+            let puzzle = puzzle_reveal.to_node_ptr(&mut allocator)?;
+            let asset_kind = AssetKind::from_puzzle(&allocator, puzzle);
+
+            let amount = requested_assets.entry(asset_kind).or_default();
+
+            for payment in notarized_payments.iter().flat_map(|item| &item.payments) {
+                let payment_amount = match payment {
+                    Payment::WithoutMemos(payment) => payment.amount,
+                    Payment::WithMemos(payment) => payment.amount,
+                };
+
+                *amount = amount
+                    .checked_add(payment_amount)
+                    .ok_or(OfferError::AmountOverflow)?;
+            }
+        }
+
+        Ok(requested_assets.into_iter().collect())
+    }
+
+    /// Checks whether the available balances cover every requested asset.
+    pub fn can_fulfill(&self, available: &[(AssetKind, u64)]) -> Result<bool, OfferError> {
+        Ok(self
+            .requested_assets()?
+            .into_iter()
+            .all(|(asset_kind, amount)| {
+                let balance: u128 = available
+                    .iter()
+                    .filter(|(kind, _)| *kind == asset_kind)
+                    .map(|(_, amount)| u128::from(*amount))
+                    .sum();
+                balance >= u128::from(amount)
+            }))
+    }
+
     pub fn offered_coin_spends(&self) -> &[CoinSpend] {
         &self.offered_coin_spends
     }
@@ -75,3 +118,86 @@ impl Offer {
         &self.aggregated_signature
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use chia_protocol::Bytes32;
+    use chia_puzzles::offer::PaymentWithoutMemos;
+
+    use crate::OfferBuilder;
+
+    use super::*;
+
+    #[test]
+    fn test_can_fulfill() -> anyhow::Result<()> {
+        let ctx = &mut SpendContext::new();
+
+        let asset_id = Bytes32::new([1; 32]);
+        let puzzle_hash = Bytes32::new([2; 32]);
+
+        let payment = |amount| {
+            Payment::WithoutMemos(PaymentWithoutMemos {
+                puzzle_hash,
+                amount,
+            })
+        };
+
+        let (_, builder) = OfferBuilder::new(vec![Bytes32::default()]).request_cat_payments(
+            ctx,
+            asset_id,
+            vec![payment(300), payment(200)],
+        )?;
+        let (_, builder) = builder.request_standard_payments(ctx, vec![payment(1000)])?;
+        let offer = builder
+            .make_payments()
+            .finish(Vec::new(), Signature::default())?;
+
+        assert_eq!(
+            offer.requested_assets()?,
+            vec![(AssetKind::Cat(asset_id), 500), (AssetKind::Xch, 1000)]
+        );
+
+        assert!(offer.can_fulfill(&[(AssetKind::Cat(asset_id), 500), (AssetKind::Xch, 1000)])?);
+        assert!(offer.can_fulfill(&[
+            (AssetKind::Xch, 1000),
+            (AssetKind::Cat(asset_id), 300),
+            (AssetKind::Cat(asset_id), 200),
+        ])?);
+        assert!(!offer.can_fulfill(&[(AssetKind::Cat(asset_id), 499), (AssetKind::Xch, 1000)])?);
+        assert!(!offer.can_fulfill(&[
+            (AssetKind::Cat(Bytes32::new([3; 32])), 500),
+            (AssetKind::Xch, 1000)
+        ])?);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_requested_amount_overflow() -> anyhow::Result<()> {
+        let ctx = &mut SpendContext::new();
+
+        let payment = |amount| {
+            Payment::WithoutMemos(PaymentWithoutMemos {
+                puzzle_hash: Bytes32::new([2; 32]),
+                amount,
+            })
+        };
+
+        let (_, builder) = OfferBuilder::new(vec![Bytes32::default()])
+            .request_standard_payments(ctx, vec![payment(u64::MAX), payment(1)])?;
+        let offer = builder
+            .make_payments()
+            .finish(Vec::new(), Signature::default())?;
+
+        assert!(matches!(
+            offer.requested_assets(),
+            Err(OfferError::AmountOverflow)
+        ));
+        assert!(matches!(
+            offer.can_fulfill(&[(AssetKind::Xch, u64::MAX)]),
+            Err(OfferError::AmountOverflow)
+        ));
+
+        Ok(())
+    }
+}
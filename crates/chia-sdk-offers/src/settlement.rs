@@ -25,7 +25,6 @@ impl SettlementSpend {
         let inner_spend = self.inner_spend(ctx)?;
         let puzzle_reveal = ctx.serialize(&inner_spend.puzzle())?;
         let solution = ctx.serialize(&inner_spend.solution())?;
-        ctx.insert_coin_spend(CoinSpend::new(coin, puzzle_reveal, solution));
-        Ok(())
+        ctx.insert_coin_spend(CoinSpend::new(coin, puzzle_reveal, solution))
     }
 }
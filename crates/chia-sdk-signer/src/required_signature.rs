@@ -1,4 +1,4 @@
-use chia_bls::PublicKey;
+use chia_bls::{sign, PublicKey, SecretKey, Signature};
 use chia_protocol::{Bytes, Bytes32, Coin, CoinSpend};
 use chia_sdk_types::conditions::{puzzle_conditions, AggSig, AggSigKind, Condition};
 use clvm_traits::ToNodePtr;
@@ -123,6 +123,25 @@ impl RequiredSignature {
         Ok(required_signatures)
     }
 
+    /// Signs each of the required signatures that can be made with the given secret keys, skipping the rest.
+    /// Partial signatures from each party can be added together to form the aggregated signature.
+    pub fn sign_partial(required_signatures: &[Self], secret_keys: &[SecretKey]) -> Signature {
+        let mut signature = Signature::default();
+
+        for required in required_signatures {
+            let Some(secret_key) = secret_keys
+                .iter()
+                .find(|secret_key| secret_key.public_key() == required.public_key)
+            else {
+                continue;
+            };
+
+            signature += &sign(secret_key, required.final_message());
+        }
+
+        signature
+    }
+
     /// The public key required to verify the signature.
     pub fn public_key(&self) -> PublicKey {
         self.public_key
@@ -169,6 +188,45 @@ mod tests {
     use chia_puzzles::DeriveSynthetic;
     use hex_literal::hex;
 
+    #[test]
+    fn test_sign_partial() {
+        let coin = Coin::new(Bytes32::from([1; 32]), Bytes32::from([2; 32]), 3);
+        let agg_sig_data = Bytes32::new([4u8; 32]);
+
+        let root_sk = SecretKey::from_bytes(&hex!(
+            "1b72f8ed55860ea5441729c8e36ce1d6f4c8be9bbcf658502a7a0169f55638b9"
+        ))
+        .unwrap();
+        let first_sk = master_to_wallet_unhardened(&root_sk, 0);
+        let second_sk = master_to_wallet_unhardened(&root_sk, 1);
+
+        let required_signatures: Vec<RequiredSignature> = [&first_sk, &second_sk]
+            .into_iter()
+            .map(|sk| {
+                RequiredSignature::from_condition(
+                    &coin,
+                    AggSig {
+                        kind: AggSigKind::Me,
+                        public_key: sk.public_key(),
+                        message: vec![1, 2, 3].into(),
+                    },
+                    agg_sig_data,
+                )
+            })
+            .collect();
+
+        let full = RequiredSignature::sign_partial(
+            &required_signatures,
+            &[first_sk.clone(), second_sk.clone()],
+        );
+
+        let mut combined = RequiredSignature::sign_partial(&required_signatures, &[first_sk]);
+        assert_ne!(combined, full);
+
+        combined += &RequiredSignature::sign_partial(&required_signatures, &[second_sk]);
+        assert_eq!(combined, full);
+    }
+
     #[test]
     fn test_messages() {
         let coin = Coin::new(Bytes32::from([1; 32]), Bytes32::from([2; 32]), 3);
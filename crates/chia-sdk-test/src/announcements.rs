@@ -1,7 +1,12 @@
+use std::collections::HashSet;
+
 use chia_protocol::{Bytes, Bytes32, CoinSpend};
-use chia_sdk_types::conditions::{
-    announcement_id, AssertCoinAnnouncement, AssertPuzzleAnnouncement, CreateCoinAnnouncement,
-    CreatePuzzleAnnouncement,
+use chia_sdk_types::{
+    conditions::{
+        announcement_id, AssertCoinAnnouncement, AssertPuzzleAnnouncement, CreateCoinAnnouncement,
+        CreatePuzzleAnnouncement,
+    },
+    MAX_BLOCK_COST,
 };
 use clvm_traits::{FromClvm, ToNodePtr};
 use clvmr::{reduction::Reduction, run_program, Allocator, ChiaDialect, NodePtr};
@@ -70,6 +75,46 @@ pub fn debug_announcements(coin_spends: &[CoinSpend]) {
     );
 }
 
+/// Returns the ids of the coin and puzzle announcements asserted by the coin spends that none of them create.
+/// This explains why a spend bundle which links its spends with announcements was rejected.
+pub fn check_announcements(coin_spends: &[CoinSpend]) -> anyhow::Result<Vec<Bytes32>> {
+    let mut created_coin = HashSet::new();
+    let mut created_puzzle = HashSet::new();
+    let mut unmatched = Vec::new();
+
+    let all_announcements = coin_spends
+        .iter()
+        .map(announcements_for_spend)
+        .collect::<anyhow::Result<Vec<Announcements>>>()?;
+
+    for (coin_spend, announcements) in coin_spends.iter().zip(&all_announcements) {
+        for message in &announcements.created_coin {
+            created_coin.insert(announcement_id(coin_spend.coin.coin_id(), message));
+        }
+
+        for message in &announcements.created_puzzle {
+            created_puzzle.insert(announcement_id(coin_spend.coin.puzzle_hash, message));
+        }
+    }
+
+    for announcements in all_announcements {
+        unmatched.extend(
+            announcements
+                .asserted_coin
+                .into_iter()
+                .filter(|id| !created_coin.contains(id)),
+        );
+        unmatched.extend(
+            announcements
+                .asserted_puzzle
+                .into_iter()
+                .filter(|id| !created_puzzle.contains(id)),
+        );
+    }
+
+    Ok(unmatched)
+}
+
 pub fn announcements_for_spend(coin_spend: &CoinSpend) -> anyhow::Result<Announcements> {
     let mut announcements = Announcements::default();
 
@@ -82,7 +127,7 @@ pub fn announcements_for_spend(coin_spend: &CoinSpend) -> anyhow::Result<Announc
         &ChiaDialect::new(0),
         puzzle,
         solution,
-        11_000_000_000,
+        MAX_BLOCK_COST,
     )?;
 
     let conditions = Vec::<NodePtr>::from_clvm(allocator, output)?;
@@ -103,3 +148,56 @@ pub fn announcements_for_spend(coin_spend: &CoinSpend) -> anyhow::Result<Announc
 
     Ok(announcements)
 }
+
+#[cfg(test)]
+mod tests {
+    use chia_protocol::Coin;
+
+    use crate::{to_program, to_puzzle};
+
+    use super::*;
+
+    #[test]
+    fn test_check_announcements() -> anyhow::Result<()> {
+        let (puzzle_hash, puzzle_reveal) = to_puzzle(1)?;
+
+        let first = Coin::new(Bytes32::new([1; 32]), puzzle_hash, 1);
+        let second = Coin::new(Bytes32::new([2; 32]), puzzle_hash, 1);
+
+        let missing_coin = announcement_id(second.coin_id(), [3]);
+        let missing_puzzle = announcement_id(puzzle_hash, [4]);
+
+        let coin_spends = vec![
+            CoinSpend::new(
+                first,
+                puzzle_reveal.clone(),
+                to_program((
+                    CreateCoinAnnouncement::new(Bytes::new(vec![1])),
+                    (
+                        AssertPuzzleAnnouncement::new(announcement_id(puzzle_hash, [2])),
+                        (AssertCoinAnnouncement::new(missing_coin), ()),
+                    ),
+                ))?,
+            ),
+            CoinSpend::new(
+                second,
+                puzzle_reveal,
+                to_program((
+                    CreatePuzzleAnnouncement::new(Bytes::new(vec![2])),
+                    (
+                        AssertCoinAnnouncement::new(announcement_id(first.coin_id(), [1])),
+                        (AssertPuzzleAnnouncement::new(missing_puzzle), ()),
+                    ),
+                ))?,
+            ),
+        ];
+
+        assert_eq!(
+            check_announcements(&coin_spends)?,
+            vec![missing_coin, missing_puzzle]
+        );
+        assert_eq!(check_announcements(&coin_spends[..0])?, Vec::new());
+
+        Ok(())
+    }
+}
@@ -0,0 +1,77 @@
+use chia_protocol::{Bytes32, CoinState};
+
+use crate::Simulator;
+
+/// Finds the coin created by the parent with the given puzzle hash and amount, and checks that it's unspent.
+///
+/// # Panics
+/// Will panic if there is no such child, or if it has already been spent.
+pub async fn assert_coin_created(
+    sim: &Simulator,
+    parent_coin_id: Bytes32,
+    puzzle_hash: Bytes32,
+    amount: u64,
+) -> CoinState {
+    let children = sim.children(parent_coin_id).await;
+
+    let Some(coin_state) = children
+        .iter()
+        .find(|cs| cs.coin.puzzle_hash == puzzle_hash && cs.coin.amount == amount)
+    else {
+        panic!(
+            "expected parent {parent_coin_id} to create a coin with puzzle hash {puzzle_hash} and amount {amount}, \
+            but its children are {children:?}"
+        );
+    };
+
+    assert_eq!(
+        coin_state.spent_height,
+        None,
+        "expected coin {} to be unspent",
+        coin_state.coin.coin_id()
+    );
+
+    *coin_state
+}
+
+#[cfg(test)]
+mod tests {
+    use chia_bls::Signature;
+    use chia_protocol::{Coin, CoinSpend, SpendBundle};
+    use chia_sdk_types::conditions::CreateCoin;
+
+    use crate::{to_program, to_puzzle};
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_assert_coin_created() -> anyhow::Result<()> {
+        let sim = Simulator::new().await?;
+        let peer = sim.connect().await?;
+
+        let (puzzle_hash, puzzle_reveal) = to_puzzle(1)?;
+
+        let coin = sim.mint_coin(puzzle_hash, 3).await;
+
+        let spend_bundle = SpendBundle::new(
+            vec![CoinSpend::new(
+                coin,
+                puzzle_reveal,
+                to_program([
+                    CreateCoin::new(puzzle_hash, 1),
+                    CreateCoin::new(puzzle_hash, 2),
+                ])?,
+            )],
+            Signature::default(),
+        );
+
+        let ack = peer.send_transaction(spend_bundle).await?;
+        assert_eq!(ack.status, 1);
+
+        let coin_state = assert_coin_created(&sim, coin.coin_id(), puzzle_hash, 2).await;
+        assert_eq!(coin_state.coin, Coin::new(coin.coin_id(), puzzle_hash, 2));
+        assert_eq!(coin_state.created_height, Some(0));
+
+        Ok(())
+    }
+}
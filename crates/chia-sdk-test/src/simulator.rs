@@ -1,29 +1,78 @@
-use std::{net::SocketAddr, sync::Arc};
+use std::{
+    collections::HashSet, io::Cursor, net::SocketAddr, path::Path, sync::Arc, time::Duration,
+};
 
 use chia_client::Peer;
-use chia_protocol::{Bytes32, Coin, CoinState};
-use error::SimulatorError;
+use chia_consensus::gen::validation_error::{ErrorCode, ValidationErr};
+use chia_protocol::{
+    Bytes32, Coin, CoinState, CoinStateFilters, ProtocolMessageTypes, PuzzleSolutionResponse,
+    RejectStateReason, SpendBundle,
+};
+use chia_traits::Streamable;
+pub use error::SimulatorError;
+use futures_util::SinkExt;
+use indexmap::{IndexMap, IndexSet};
 use peer_map::PeerMap;
 use rand::{Rng, SeedableRng};
 use rand_chacha::ChaCha8Rng;
-use simulator_config::SimulatorConfig;
-use simulator_data::SimulatorData;
-use tokio::{net::TcpListener, sync::Mutex, task::JoinHandle};
-use tokio_tungstenite::connect_async;
-use ws_connection::ws_connection;
+pub use simulator_config::SimulatorConfig;
+use simulator_data::{new_transaction, SimulatorData};
+use tokio::{
+    net::TcpListener,
+    sync::{Mutex, Notify},
+    task::JoinHandle,
+    time::{sleep, timeout},
+};
+use tokio_tungstenite::{connect_async, tungstenite::Message as WsMessage};
+pub use transaction_status::{RespondTransactionStatus, TransactionStatus};
+use ws_connection::{broadcast_updates, new_peak_message, ws_connection};
 
 mod error;
 mod peer_map;
 mod simulator_config;
 mod simulator_data;
+mod transaction_status;
 mod ws_connection;
 
+/// Connects a [`Peer`] to a plain `ws://` URL, such as that of another simulator or a local full node.
+pub async fn connect_peer_url(url: &str) -> Result<Peer, SimulatorError> {
+    let (ws, _) = connect_async(url).await?;
+    Ok(Peer::new(ws))
+}
+
+/// The outcome of a transaction accepted by the simulator.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TransactionInfo {
+    /// The cost of running the spend bundle.
+    pub cost: u64,
+    /// The amount of the removals which isn't spent on additions.
+    pub fee: u64,
+    pub additions: Vec<Coin>,
+    pub removals: Vec<Coin>,
+}
+
+/// The coins created and spent by a spend bundle applied with [`Simulator::apply_bundle`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BundleEffects {
+    pub created_coins: Vec<Coin>,
+    pub spent_coin_ids: Vec<Bytes32>,
+}
+
+/// A copy of the simulator's blockchain state, which can be restored later.
+/// Peer subscriptions aren't included, since they belong to the live connections.
+#[derive(Debug, Clone)]
+pub struct SimulatorSnapshot {
+    data: SimulatorData,
+}
+
 #[derive(Debug)]
 pub struct Simulator {
     config: Arc<SimulatorConfig>,
     rng: Mutex<ChaCha8Rng>,
     addr: SocketAddr,
+    peer_map: PeerMap,
     data: Arc<Mutex<SimulatorData>>,
+    shutdown: Arc<Notify>,
     join_handle: JoinHandle<()>,
 }
 
@@ -44,12 +93,24 @@ impl Simulator {
 
         let data_clone = data.clone();
         let config_clone = config.clone();
+        let peer_map_clone = peer_map.clone();
+        let shutdown = Arc::new(Notify::new());
+        let shutdown_clone = shutdown.clone();
 
         let join_handle = tokio::spawn(async move {
             let data = data_clone;
             let config = config_clone;
+            let peer_map = peer_map_clone;
+
+            loop {
+                let (stream, addr) = tokio::select! {
+                    result = listener.accept() => match result {
+                        Ok(connection) => connection,
+                        Err(_) => break,
+                    },
+                    () = shutdown_clone.notified() => break,
+                };
 
-            while let Ok((stream, addr)) = listener.accept().await {
                 let stream = match tokio_tungstenite::accept_async(stream).await {
                     Ok(stream) => stream,
                     Err(error) => {
@@ -71,19 +132,86 @@ impl Simulator {
             config,
             rng: Mutex::new(ChaCha8Rng::seed_from_u64(0)),
             addr,
+            peer_map,
+            shutdown,
             join_handle,
             data,
         })
     }
 
+    /// Starts a simulator with the state previously written to a file with [`Simulator::save`].
+    pub async fn load(
+        path: impl AsRef<Path>,
+        config: SimulatorConfig,
+    ) -> Result<Self, SimulatorError> {
+        let bytes = tokio::fs::read(path).await?;
+        let mut input = Cursor::new(bytes.as_slice());
+
+        let seed = Bytes32::parse::<false>(&mut input)?;
+        let word_pos = u128::parse::<false>(&mut input)?;
+        let data = SimulatorData::parse(&mut input)?;
+
+        if input.position() != bytes.len() as u64 {
+            return Err(chia_traits::Error::InputTooLarge.into());
+        }
+
+        let mut rng = ChaCha8Rng::from_seed(seed.to_bytes());
+        rng.set_word_pos(word_pos);
+
+        let sim = Self::with_config(config).await?;
+        *sim.rng.lock().await = rng;
+        *sim.data.lock().await = data;
+        Ok(sim)
+    }
+
+    /// Writes the coin set, hints, height, and minting RNG state to a file.
+    /// Peer subscriptions aren't included, since they belong to the live connections.
+    pub async fn save(&self, path: impl AsRef<Path>) -> Result<(), SimulatorError> {
+        let mut bytes = Vec::new();
+
+        {
+            let rng = self.rng.lock().await;
+            Bytes32::new(rng.get_seed()).stream(&mut bytes)?;
+            rng.get_word_pos().stream(&mut bytes)?;
+        }
+
+        self.data.lock().await.stream(&mut bytes)?;
+
+        tokio::fs::write(path, bytes).await?;
+        Ok(())
+    }
+
     pub fn config(&self) -> &SimulatorConfig {
         &self.config
     }
 
     pub async fn connect(&self) -> Result<Peer, SimulatorError> {
         log::info!("connecting new peer to simulator");
-        let (ws, _) = connect_async(format!("ws://{}", self.addr)).await?;
-        Ok(Peer::new(ws))
+        connect_peer_url(&format!("ws://{}", self.addr)).await
+    }
+
+    /// Closes the connection to every connected peer.
+    pub async fn disconnect_all(&self) {
+        for (_, mut peer) in self.peer_map.peers().await {
+            peer.send(WsMessage::Close(None)).await.ok();
+        }
+    }
+
+    /// Stops accepting connections, closes existing ones with a close frame, and waits for the peers to disconnect.
+    /// Dropping the simulator without calling this aborts the server instead.
+    pub async fn shutdown(mut self) {
+        self.shutdown.notify_one();
+        let _ = (&mut self.join_handle).await;
+
+        self.disconnect_all().await;
+
+        // Give peers a chance to acknowledge the close frame, so they don't see the connection reset.
+        let _ = timeout(Duration::from_secs(5), async {
+            while !self.peer_map.is_empty().await {
+                sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await;
     }
 
     pub async fn reset(&self) -> Result<(), SimulatorError> {
@@ -105,6 +233,174 @@ impl Simulator {
         coin
     }
 
+    /// Mints a coin whose parent is derived from the genesis challenge, following the coinbase convention
+    /// of the first 16 bytes of the genesis challenge followed by the height 0 as a 16 byte big-endian integer.
+    /// The coin id is the same across runs with the same config, puzzle hash, and amount.
+    /// If the coin already exists, its state is left unchanged (even if it has been spent).
+    pub async fn genesis_coin(&self, puzzle_hash: Bytes32, amount: u64) -> Coin {
+        let mut parent_coin_info = [0; 32];
+        parent_coin_info[..16].copy_from_slice(&self.config.genesis_challenge[..16]);
+
+        let coin = Coin::new(parent_coin_info.into(), puzzle_hash, amount);
+
+        let mut data = self.data.lock().await;
+
+        if data.coin_state(coin.coin_id()).is_none() {
+            data.create_coin(coin);
+        }
+
+        coin
+    }
+
+    /// Mints a coin for each puzzle hash and amount pair.
+    /// If [`SimulatorConfig::sequential_mint_heights`] is set, each coin is confirmed in its own block,
+    /// and peers are notified of every new peak.
+    pub async fn mint_coins(
+        &self,
+        coins: Vec<(Bytes32, u64)>,
+    ) -> Result<Vec<Coin>, SimulatorError> {
+        let mut data = self.data.lock().await;
+        let mut rng = self.rng.lock().await;
+        let mut minted = Vec::with_capacity(coins.len());
+
+        for (puzzle_hash, amount) in coins {
+            let coin = Coin::new(Bytes32::new(rng.gen()), puzzle_hash, amount);
+            data.create_coin(coin);
+            minted.push(coin);
+
+            if !self.config.sequential_mint_heights {
+                continue;
+            }
+
+            data.new_block();
+
+            let new_peak = new_peak_message(&data)?;
+
+            for (_, mut peer) in self.peer_map.peers().await {
+                peer.send(new_peak.clone()).await.ok();
+            }
+        }
+
+        Ok(minted)
+    }
+
+    /// Farms a block, creating the farmer and pool reward coins for the current height with the given puzzle hash.
+    /// Their parent coin ids are derived from the genesis challenge and height, like on a real network,
+    /// and the farmer reward includes the fees of transactions confirmed since the last farmed block.
+    /// Subscribed peers are notified of the new coins.
+    pub async fn farm_block_to(
+        &self,
+        puzzle_hash: Bytes32,
+    ) -> Result<(Coin, Coin), SimulatorError> {
+        let mut data = self.data.lock().await;
+
+        let (farmer_coin, pool_coin, updates) =
+            data.farm_block(puzzle_hash, self.config.genesis_challenge);
+
+        broadcast_updates(&self.peer_map, &data, &updates).await?;
+
+        Ok((farmer_coin, pool_coin))
+    }
+
+    /// Validates and applies a spend bundle directly, rather than sending it through a connected peer.
+    /// Invalid signatures and double spends are reported as their own errors, and other failures as [`SimulatorError::Validation`].
+    /// Subscribed peers are notified of the resulting coin state updates.
+    pub async fn apply_bundle(
+        &self,
+        spend_bundle: SpendBundle,
+    ) -> Result<BundleEffects, SimulatorError> {
+        let mut data = self.data.lock().await;
+        let transaction_id = spend_bundle.name();
+
+        let updates = match new_transaction(&self.config, &mut data, spend_bundle) {
+            Ok(updates) => updates,
+            Err(error) => {
+                data.reject_transaction(transaction_id, error.to_string());
+
+                return Err(match error {
+                    SimulatorError::Validation(ValidationErr(
+                        _,
+                        ErrorCode::BadAggregateSignature,
+                    )) => SimulatorError::BadSignature,
+                    SimulatorError::Validation(ValidationErr(_, ErrorCode::DoubleSpend)) => {
+                        SimulatorError::DoubleSpend
+                    }
+                    error => error,
+                });
+            }
+        };
+
+        broadcast_updates(&self.peer_map, &data, &updates).await?;
+
+        let info = data.transaction_info(transaction_id).ok_or_else(|| {
+            SimulatorError::Inconsistent(format!("missing info for transaction {transaction_id}"))
+        })?;
+
+        Ok(BundleEffects {
+            created_coins: info.additions,
+            spent_coin_ids: info.removals.iter().map(Coin::coin_id).collect(),
+        })
+    }
+
+    /// Applies a list of previously captured spend bundles in order.
+    /// Before each bundle, the coins it spends which don't exist and weren't spent by an earlier bundle are minted.
+    /// The bundles are applied atomically, so if any of them is rejected the simulator is left unchanged.
+    /// Subscribed peers are notified of the resulting coin state updates.
+    pub async fn replay(&self, bundles: Vec<SpendBundle>) -> Result<(), SimulatorError> {
+        let mut data = self.data.lock().await;
+        let mut replayed = data.clone();
+
+        let mut spent_coin_ids = HashSet::new();
+        let mut updated_coin_ids: IndexMap<SocketAddr, IndexSet<Bytes32>> = IndexMap::new();
+
+        for (index, bundle) in bundles.into_iter().enumerate() {
+            let bundle_coin_ids: HashSet<Bytes32> = bundle
+                .coin_spends
+                .iter()
+                .map(|coin_spend| coin_spend.coin.coin_id())
+                .collect();
+
+            // Coins created by this bundle are ephemeral, and coins created by earlier bundles already exist.
+            for coin_spend in &bundle.coin_spends {
+                let parent_coin_id = coin_spend.coin.parent_coin_info;
+
+                if !bundle_coin_ids.contains(&parent_coin_id)
+                    && !spent_coin_ids.contains(&parent_coin_id)
+                    && replayed.coin_state(coin_spend.coin.coin_id()).is_none()
+                {
+                    replayed.create_coin(coin_spend.coin);
+                }
+            }
+
+            let updates =
+                new_transaction(&self.config, &mut replayed, bundle).map_err(|error| {
+                    SimulatorError::ReplayRejected {
+                        index,
+                        error: Box::new(error),
+                    }
+                })?;
+
+            spent_coin_ids.extend(bundle_coin_ids);
+
+            for (addr, coin_states) in updates {
+                updated_coin_ids
+                    .entry(addr)
+                    .or_default()
+                    .extend(coin_states.iter().map(|cs| cs.coin.coin_id()));
+            }
+        }
+
+        *data = replayed;
+
+        // Each peer is sent the final state of the coins it was notified about.
+        let updates = updated_coin_ids
+            .into_iter()
+            .map(|(addr, coin_ids)| (addr, data.lookup_coin_ids(&coin_ids).into_iter().collect()))
+            .collect();
+
+        broadcast_updates(&self.peer_map, &data, &updates).await
+    }
+
     pub async fn add_hint(&self, coin_id: Bytes32, hint: Bytes32) {
         let mut data = self.data.lock().await;
         data.add_hint(coin_id, hint);
@@ -115,16 +411,123 @@ impl Simulator {
         data.coin_state(coin_id)
     }
 
+    /// The coin states of the coins created by the given parent coin.
+    pub async fn children(&self, coin_id: Bytes32) -> Vec<CoinState> {
+        let data = self.data.lock().await;
+        data.children(coin_id)
+    }
+
+    pub async fn all_coin_states(&self) -> Vec<CoinState> {
+        let data = self.data.lock().await;
+        data.all_coin_states()
+    }
+
+    /// Returns the coin states for a puzzle hash that match the filters and changed at or after `min_height`.
+    /// This is the same filtering that's applied to puzzle state requests from peers.
+    pub async fn filtered_coin_states(
+        &self,
+        puzzle_hash: Bytes32,
+        filters: CoinStateFilters,
+        min_height: u32,
+    ) -> Vec<CoinState> {
+        let data = self.data.lock().await;
+        data.filtered_coin_states(IndexSet::from([puzzle_hash]), &filters, min_height)
+    }
+
+    /// Returns the puzzles and solutions of spent coins in a single batch, in the order requested.
+    /// Coins that haven't been spent are omitted. At most [`SimulatorConfig::puzzle_solution_batch_size`]
+    /// coin ids are looked up, and if any remain, the index of the next one is returned as a cursor.
+    pub async fn puzzles_and_solutions(
+        &self,
+        coin_ids: &[Bytes32],
+    ) -> (Vec<PuzzleSolutionResponse>, Option<usize>) {
+        let data = self.data.lock().await;
+        let batch_size = self.config.puzzle_solution_batch_size;
+
+        let responses = coin_ids
+            .iter()
+            .take(batch_size)
+            .filter_map(|coin_id| data.puzzle_and_solution(*coin_id))
+            .collect();
+
+        let cursor = (coin_ids.len() > batch_size).then_some(batch_size);
+
+        (responses, cursor)
+    }
+
+    /// Takes a snapshot of the current coin set, hints, and height.
+    pub async fn snapshot(&self) -> SimulatorSnapshot {
+        let data = self.data.lock().await;
+        SimulatorSnapshot { data: data.clone() }
+    }
+
+    /// Restores the state from a snapshot taken earlier.
+    pub async fn restore(&self, snapshot: SimulatorSnapshot) {
+        let mut data = self.data.lock().await;
+        data.restore(snapshot.data);
+    }
+
     pub async fn height(&self) -> u32 {
         let data = self.data.lock().await;
         data.height()
     }
 
+    /// The timestamp of the next block, which is used to validate time locks.
+    pub async fn timestamp(&self) -> u64 {
+        let data = self.data.lock().await;
+        data.timestamp()
+    }
+
+    /// Advances the timestamp of the next block by the given number of seconds.
+    pub async fn pass_time(&self, seconds: u64) {
+        let mut data = self.data.lock().await;
+        data.pass_time(seconds);
+    }
+
+    /// Checks that the internal state of the simulator is consistent.
+    pub async fn validate(&self) -> Result<(), SimulatorError> {
+        let data = self.data.lock().await;
+        data.validate()
+    }
+
+    pub async fn transaction_status(&self, transaction_id: Bytes32) -> TransactionStatus {
+        let data = self.data.lock().await;
+        data.transaction_status(transaction_id)
+    }
+
+    /// The cost, fee, additions, and removals of a transaction, if it was accepted.
+    pub async fn transaction_info(&self, transaction_id: Bytes32) -> Option<TransactionInfo> {
+        let data = self.data.lock().await;
+        data.transaction_info(transaction_id)
+    }
+
+    /// Rejects the next request of the given type with the reason, regardless of its contents.
+    /// Only [`ProtocolMessageTypes::RequestCoinState`] and [`ProtocolMessageTypes::RequestPuzzleState`] can be rejected.
+    pub async fn set_reject(&self, request_type: ProtocolMessageTypes, reason: RejectStateReason) {
+        let mut data = self.data.lock().await;
+        data.force_rejection(request_type, reason);
+    }
+
+    /// Returns the reason the transaction was rejected, if it was sent by a peer and failed validation.
+    /// The [`TransactionAck`](chia_protocol::TransactionAck) only has a generic error status.
+    pub async fn last_rejection(&self, transaction_id: Bytes32) -> Option<String> {
+        let data = self.data.lock().await;
+        data.rejection(transaction_id)
+    }
+
     pub async fn header_hash(&self, height: u32) -> Bytes32 {
         let data = self.data.lock().await;
         data.header_hash(height)
     }
 
+    /// The header hashes of every block from genesis to the peak, in height order.
+    pub async fn header_hashes(&self) -> Vec<Bytes32> {
+        let data = self.data.lock().await;
+        (0..=data.height())
+            .map(|height| data.header_hash(height))
+            .collect()
+    }
+
     pub async fn peak_hash(&self) -> Bytes32 {
         let data = self.data.lock().await;
         data.header_hash(data.height())
@@ -140,13 +543,29 @@ impl Drop for Simulator {
 #[cfg(test)]
 mod tests {
     use chia_bls::{DerivableKey, PublicKey, Signature};
+    use chia_client::PeerEvent;
     use chia_protocol::{
-        Bytes, CoinSpend, CoinStateFilters, CoinStateUpdate, RejectCoinState, RejectPuzzleState,
-        RequestCoinState, RequestPuzzleState, RespondCoinState, RespondPuzzleState, SpendBundle,
+        Bytes, CoinSpend, CoinStateFilters, CoinStateUpdate, Program, RejectCoinState,
+        RejectPuzzleState, RequestCoinState, RequestPuzzleState, RequestTransaction,
+        RespondCoinState, RespondPuzzleState, SpendBundle,
+    };
+    use chia_puzzles::{
+        singleton::{
+            SingletonArgs, SingletonSolution, SINGLETON_LAUNCHER_PUZZLE_HASH,
+            SINGLETON_TOP_LAYER_PUZZLE,
+        },
+        EveProof, Proof,
     };
-    use chia_sdk_types::conditions::{AggSigMe, CreateCoin, Remark};
+    use chia_sdk_types::conditions::{
+        AggSigMe, AssertHeightAbsolute, AssertSecondsRelative, CreateCoin, Remark,
+    };
+    use clvm_traits::{FromNodePtr, ToClvm};
+    use clvm_utils::{tree_hash, CurriedProgram};
+    use clvmr::{serde::node_from_bytes, Allocator};
 
-    use crate::{coin_state_updates, secret_key, test_transaction, to_program, to_puzzle};
+    use crate::{
+        coin_state_updates, secret_key, sign_transaction, test_transaction, to_program, to_puzzle,
+    };
 
     use super::*;
 
@@ -168,164 +587,977 @@ mod tests {
     }
 
     #[tokio::test]
-    async fn test_empty_transaction() -> anyhow::Result<()> {
+    async fn test_snapshot() -> anyhow::Result<()> {
         let sim = Simulator::new().await?;
         let peer = sim.connect().await?;
 
-        let empty_bundle = SpendBundle::new(Vec::new(), Signature::default());
-        let transaction_id = empty_bundle.name();
+        let (puzzle_hash, puzzle_reveal) = to_puzzle(1)?;
 
-        let ack = peer.send_transaction(empty_bundle).await?;
-        assert_eq!(ack.status, 3);
-        assert_eq!(ack.txid, transaction_id);
+        let coin = sim.mint_coin(puzzle_hash, 0).await;
+        sim.add_hint(coin.coin_id(), Bytes32::default()).await;
+
+        let snapshot = sim.snapshot().await;
+        assert_eq!(sim.all_coin_states().await.len(), 1);
+
+        let spend_bundle = SpendBundle::new(
+            vec![CoinSpend::new(coin, puzzle_reveal, to_program(())?)],
+            Signature::default(),
+        );
+
+        let ack = peer.send_transaction(spend_bundle.clone()).await?;
+        assert_eq!(ack.status, 1);
+
+        let other_coin = sim.mint_coin(puzzle_hash, 0).await;
+        assert_eq!(sim.all_coin_states().await.len(), 2);
+        assert_eq!(sim.height().await, 1);
+
+        sim.restore(snapshot.clone()).await;
+        sim.validate().await?;
+
+        assert_eq!(sim.height().await, 0);
+        assert_eq!(
+            sim.all_coin_states().await,
+            vec![CoinState::new(coin, None, Some(0))]
+        );
+        assert!(sim.coin_state(other_coin.coin_id()).await.is_none());
+
+        // The same spend can be made again after restoring.
+        let ack = peer.send_transaction(spend_bundle).await?;
+        assert_eq!(ack.status, 1);
+
+        sim.restore(snapshot).await;
+        assert_eq!(sim.height().await, 0);
 
         Ok(())
     }
 
     #[tokio::test]
-    async fn test_simple_transaction() -> anyhow::Result<()> {
+    async fn test_connect_peer_url() -> anyhow::Result<()> {
+        let sim = Simulator::new().await?;
+        let peer = connect_peer_url(&format!("ws://{}", sim.addr)).await?;
+
+        let (puzzle_hash, puzzle_reveal) = to_puzzle(1)?;
+        let coin = sim.mint_coin(puzzle_hash, 0).await;
+
+        let ack = peer
+            .send_transaction(SpendBundle::new(
+                vec![CoinSpend::new(coin, puzzle_reveal, to_program(())?)],
+                Signature::default(),
+            ))
+            .await?;
+        assert_eq!(ack.status, 1);
+
+        let coin_state = sim.coin_state(coin.coin_id()).await.expect("missing coin");
+        assert!(coin_state.spent_height.is_some());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_shutdown() -> anyhow::Result<()> {
+        let sim = Simulator::new().await?;
+        let peer = sim.connect().await?;
+        let mut receiver = peer.receiver().resubscribe();
+        let url = format!("ws://{}", sim.addr);
+
+        sim.shutdown().await;
+
+        // The peer's connection was closed, and new connections are refused.
+        assert!(receiver.recv().await.is_err());
+        assert!(connect_peer_url(&url).await.is_err());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_save_and_load() -> anyhow::Result<()> {
         let sim = Simulator::new().await?;
         let peer = sim.connect().await?;
 
         let (puzzle_hash, puzzle_reveal) = to_puzzle(1)?;
 
-        let coin = sim.mint_coin(puzzle_hash, 0).await;
+        let coin = sim.mint_coin(puzzle_hash, 1).await;
+        sim.add_hint(coin.coin_id(), Bytes32::default()).await;
 
         let spend_bundle = SpendBundle::new(
-            vec![CoinSpend::new(coin, puzzle_reveal, to_program(())?)],
+            vec![CoinSpend::new(
+                coin,
+                puzzle_reveal,
+                to_program([CreateCoin::new(puzzle_hash, 1)])?,
+            )],
             Signature::default(),
         );
-
+        let transaction_id = spend_bundle.name();
         let ack = peer.send_transaction(spend_bundle).await?;
         assert_eq!(ack.status, 1);
 
+        let path = std::env::temp_dir().join(format!("simulator-{}.bin", std::process::id()));
+        sim.save(&path).await?;
+        let loaded = Simulator::load(&path, SimulatorConfig::default()).await?;
+        std::fs::remove_file(&path)?;
+
+        loaded.validate().await?;
+        assert_eq!(loaded.height().await, sim.height().await);
+        assert_eq!(loaded.header_hashes().await, sim.header_hashes().await);
+        assert_eq!(loaded.all_coin_states().await, sim.all_coin_states().await);
+        assert_eq!(
+            loaded.transaction_status(transaction_id).await,
+            sim.transaction_status(transaction_id).await
+        );
+        assert_eq!(
+            loaded.transaction_info(transaction_id).await,
+            sim.transaction_info(transaction_id).await
+        );
+        assert!(loaded.transaction_info(transaction_id).await.is_some());
+
+        let loaded_peer = loaded.connect().await?;
+        let response = loaded_peer
+            .request_puzzle_and_solution(coin.coin_id(), 0)
+            .await?;
+        assert_eq!(response.coin_name, coin.coin_id());
+
+        let hinted = loaded_peer
+            .register_for_ph_updates(vec![Bytes32::default()], 0)
+            .await?;
+        assert_eq!(hinted.len(), 1);
+
+        // Minting resumes from the same point of the RNG.
+        assert_eq!(
+            loaded.mint_coin(puzzle_hash, 1).await,
+            sim.mint_coin(puzzle_hash, 1).await
+        );
+
         Ok(())
     }
 
     #[tokio::test]
-    async fn test_unknown_coin() -> anyhow::Result<()> {
+    async fn test_genesis_coin() -> anyhow::Result<()> {
+        let puzzle_hash = Bytes32::new([1; 32]);
+
         let sim = Simulator::new().await?;
-        let peer = sim.connect().await?;
+        let coin = sim.genesis_coin(puzzle_hash, 1000).await;
+        assert_eq!(
+            &coin.parent_coin_info[..16],
+            &sim.config().genesis_challenge[..16]
+        );
+        assert_eq!(
+            sim.coin_state(coin.coin_id()).await.map(|cs| cs.coin),
+            Some(coin)
+        );
 
-        let (puzzle_hash, puzzle_reveal) = to_puzzle(1)?;
+        let other_sim = Simulator::new().await?;
+        other_sim.mint_coin(puzzle_hash, 1000).await;
+        let other_coin = other_sim.genesis_coin(puzzle_hash, 1000).await;
+        assert_eq!(other_coin.coin_id(), coin.coin_id());
 
-        let coin = Coin::new(Bytes32::default(), puzzle_hash, 0);
+        // Minting the same genesis coin again doesn't recreate it after it has been spent.
+        let peer = sim.connect().await?;
+        let (puzzle_hash, puzzle_reveal) = to_puzzle(1)?;
+        let coin = sim.genesis_coin(puzzle_hash, 0).await;
 
         let spend_bundle = SpendBundle::new(
             vec![CoinSpend::new(coin, puzzle_reveal, to_program(())?)],
             Signature::default(),
         );
+        assert_eq!(peer.send_transaction(spend_bundle).await?.status, 1);
 
-        let ack = peer.send_transaction(spend_bundle).await?;
+        let spent_state = sim.coin_state(coin.coin_id()).await;
+        assert!(spent_state.is_some_and(|cs| cs.spent_height.is_some()));
+
+        assert_eq!(sim.genesis_coin(puzzle_hash, 0).await, coin);
+        assert_eq!(sim.coin_state(coin.coin_id()).await, spent_state);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_mint_coins() -> anyhow::Result<()> {
+        let sim = Simulator::new().await?;
+
+        let coins = sim.mint_coins(vec![(Bytes32::default(), 1); 5]).await?;
+        assert_eq!(coins.len(), 5);
+        assert_eq!(sim.height().await, 0);
+
+        for coin in coins {
+            let coin_state = sim.coin_state(coin.coin_id()).await.expect("missing coin");
+            assert_eq!(coin_state.created_height, Some(0));
+        }
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_mint_coins_sequential_heights() -> anyhow::Result<()> {
+        let sim = Simulator::with_config(SimulatorConfig {
+            sequential_mint_heights: true,
+            ..Default::default()
+        })
+        .await?;
+
+        let coins = sim.mint_coins(vec![(Bytes32::default(), 1); 5]).await?;
+        assert_eq!(sim.height().await, 5);
+
+        for (height, coin) in (0..).zip(coins) {
+            let coin_state = sim.coin_state(coin.coin_id()).await.expect("missing coin");
+            assert_eq!(coin_state.created_height, Some(height));
+        }
+
+        sim.validate().await?;
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_empty_transaction() -> anyhow::Result<()> {
+        let sim = Simulator::new().await?;
+        let peer = sim.connect().await?;
+
+        let empty_bundle = SpendBundle::new(Vec::new(), Signature::default());
+        let transaction_id = empty_bundle.name();
+
+        let ack = peer.send_transaction(empty_bundle).await?;
         assert_eq!(ack.status, 3);
+        assert_eq!(ack.txid, transaction_id);
 
         Ok(())
     }
 
     #[tokio::test]
-    async fn test_bad_signature() -> anyhow::Result<()> {
+    async fn test_simple_transaction() -> anyhow::Result<()> {
         let sim = Simulator::new().await?;
         let peer = sim.connect().await?;
-        let public_key = secret_key()?.public_key();
 
-        let (puzzle_hash, puzzle_reveal) = to_puzzle(1)?;
+        let (puzzle_hash, puzzle_reveal) = to_puzzle(1)?;
+
+        let coin = sim.mint_coin(puzzle_hash, 0).await;
+
+        let spend_bundle = SpendBundle::new(
+            vec![CoinSpend::new(coin, puzzle_reveal, to_program(())?)],
+            Signature::default(),
+        );
+
+        let ack = peer.send_transaction(spend_bundle).await?;
+        assert_eq!(ack.status, 1);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_max_block_cost() -> anyhow::Result<()> {
+        let sim = Simulator::with_config(SimulatorConfig {
+            max_block_cost: 1,
+            ..Default::default()
+        })
+        .await?;
+        let peer = sim.connect().await?;
+
+        let (puzzle_hash, puzzle_reveal) = to_puzzle(1)?;
+
+        let coin = sim.mint_coin(puzzle_hash, 0).await;
+
+        let spend_bundle = SpendBundle::new(
+            vec![CoinSpend::new(coin, puzzle_reveal, to_program(())?)],
+            Signature::default(),
+        );
+
+        let ack = peer.send_transaction(spend_bundle).await?;
+        assert_eq!(ack.status, 3);
+        assert!(sim
+            .coin_state(coin.coin_id())
+            .await
+            .expect("missing coin")
+            .spent_height
+            .is_none());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_transaction_info() -> anyhow::Result<()> {
+        let sim = Simulator::new().await?;
+        let peer = sim.connect().await?;
+
+        let (puzzle_hash, puzzle_reveal) = to_puzzle(1)?;
+
+        let coin = sim.mint_coin(puzzle_hash, 10).await;
+        let child = Coin::new(coin.coin_id(), puzzle_hash, 7);
+
+        let spend_bundle = SpendBundle::new(
+            vec![CoinSpend::new(
+                coin,
+                puzzle_reveal,
+                to_program([CreateCoin::new(puzzle_hash, 7)])?,
+            )],
+            Signature::default(),
+        );
+        let transaction_id = spend_bundle.name();
+
+        assert_eq!(sim.transaction_info(transaction_id).await, None);
+
+        let ack = peer.send_transaction(spend_bundle).await?;
+        assert_eq!(ack.status, 1);
+
+        let info = sim
+            .transaction_info(transaction_id)
+            .await
+            .expect("missing transaction info");
+        assert!(info.cost > 0);
+        assert_eq!(info.fee, 3);
+        assert_eq!(info.additions, vec![child]);
+        assert_eq!(info.removals, vec![coin]);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_spend_child_before_parent() -> anyhow::Result<()> {
+        let sim = Simulator::new().await?;
+        let peer = sim.connect().await?;
+
+        let (puzzle_hash, puzzle_reveal) = to_puzzle(1)?;
+
+        let coin = sim.mint_coin(puzzle_hash, 1).await;
+        let child = Coin::new(coin.coin_id(), puzzle_hash, 1);
+
+        // The child is created and spent in the same bundle, but its spend is listed first.
+        let spend_bundle = SpendBundle::new(
+            vec![
+                CoinSpend::new(child, puzzle_reveal.clone(), to_program(())?),
+                CoinSpend::new(
+                    coin,
+                    puzzle_reveal,
+                    to_program([CreateCoin::new(puzzle_hash, 1)])?,
+                ),
+            ],
+            Signature::default(),
+        );
+
+        let ack = peer.send_transaction(spend_bundle).await?;
+        assert_eq!(ack.status, 1);
+
+        let coin_state = sim
+            .coin_state(child.coin_id())
+            .await
+            .expect("missing child");
+        assert!(coin_state.spent_height.is_some());
+
+        sim.validate().await?;
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_header_hashes() -> anyhow::Result<()> {
+        let sim = Simulator::new().await?;
+        let peer = sim.connect().await?;
+
+        let (puzzle_hash, puzzle_reveal) = to_puzzle(1)?;
+
+        for _ in 0..3 {
+            let coin = sim.mint_coin(puzzle_hash, 0).await;
+
+            let spend_bundle = SpendBundle::new(
+                vec![CoinSpend::new(coin, puzzle_reveal.clone(), to_program(())?)],
+                Signature::default(),
+            );
+
+            let ack = peer.send_transaction(spend_bundle).await?;
+            assert_eq!(ack.status, 1);
+        }
+
+        let peak = sim.height().await;
+        assert_eq!(peak, 3);
+
+        let header_hashes = sim.header_hashes().await;
+        assert_eq!(header_hashes.len(), peak as usize + 1);
+
+        for (height, header_hash) in (0..=peak).zip(header_hashes) {
+            assert_eq!(header_hash, sim.header_hash(height).await);
+        }
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_transaction_status() -> anyhow::Result<()> {
+        let sim = Simulator::new().await?;
+        let peer = sim.connect().await?;
+
+        let (puzzle_hash, puzzle_reveal) = to_puzzle(1)?;
+
+        let coin = sim.mint_coin(puzzle_hash, 0).await;
+
+        let spend_bundle = SpendBundle::new(
+            vec![CoinSpend::new(coin, puzzle_reveal, to_program(())?)],
+            Signature::default(),
+        );
+        let transaction_id = spend_bundle.name();
+
+        // Transactions the simulator has never seen are unknown, rather than an error.
+        let response: RespondTransactionStatus = peer
+            .request(RequestTransaction::new(transaction_id))
+            .await?;
+        assert_eq!(response.status, TransactionStatus::Unknown);
+        assert_eq!(
+            sim.transaction_status(transaction_id).await,
+            TransactionStatus::Unknown
+        );
+
+        let ack = peer.send_transaction(spend_bundle).await?;
+        assert_eq!(ack.status, 1);
+
+        let response: RespondTransactionStatus = peer
+            .request(RequestTransaction::new(transaction_id))
+            .await?;
+        assert_eq!(response.transaction_id, transaction_id);
+        assert_eq!(response.status, TransactionStatus::Confirmed { height: 0 });
+        assert_eq!(
+            sim.transaction_status(transaction_id).await,
+            TransactionStatus::Confirmed { height: 0 }
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_unknown_coin() -> anyhow::Result<()> {
+        let sim = Simulator::new().await?;
+        let peer = sim.connect().await?;
+
+        let (puzzle_hash, puzzle_reveal) = to_puzzle(1)?;
+
+        let coin = Coin::new(Bytes32::default(), puzzle_hash, 0);
+
+        let spend_bundle = SpendBundle::new(
+            vec![CoinSpend::new(coin, puzzle_reveal, to_program(())?)],
+            Signature::default(),
+        );
+
+        let ack = peer.send_transaction(spend_bundle).await?;
+        assert_eq!(ack.status, 3);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_last_rejection() -> anyhow::Result<()> {
+        let sim = Simulator::new().await?;
+        let peer = sim.connect().await?;
+        let public_key = secret_key()?.public_key();
+
+        let (puzzle_hash, puzzle_reveal) = to_puzzle(1)?;
+
+        let unknown_coin = Coin::new(Bytes32::default(), puzzle_hash, 0);
+        let unknown_bundle = SpendBundle::new(
+            vec![CoinSpend::new(
+                unknown_coin,
+                puzzle_reveal.clone(),
+                to_program(())?,
+            )],
+            Signature::default(),
+        );
+
+        let coin = sim.mint_coin(puzzle_hash, 0).await;
+        let unsigned_bundle = SpendBundle::new(
+            vec![CoinSpend::new(
+                coin,
+                puzzle_reveal,
+                to_program([AggSigMe::new(public_key, Bytes::default())])?,
+            )],
+            Signature::default(),
+        );
+
+        assert_eq!(sim.last_rejection(unknown_bundle.name()).await, None);
+
+        let ack = peer.send_transaction(unknown_bundle.clone()).await?;
+        assert_eq!(ack.status, 3);
+        let ack = peer.send_transaction(unsigned_bundle.clone()).await?;
+        assert_eq!(ack.status, 3);
+
+        assert_eq!(
+            sim.last_rejection(unknown_bundle.name()).await.as_deref(),
+            Some("validation error: UnknownUnspent")
+        );
+        assert_eq!(
+            sim.last_rejection(unsigned_bundle.name()).await.as_deref(),
+            Some("validation error: BadAggregateSignature")
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_bad_signature() -> anyhow::Result<()> {
+        let sim = Simulator::new().await?;
+        let peer = sim.connect().await?;
+        let public_key = secret_key()?.public_key();
+
+        let (puzzle_hash, puzzle_reveal) = to_puzzle(1)?;
+
+        let coin = sim.mint_coin(puzzle_hash, 0).await;
+
+        let spend_bundle = SpendBundle::new(
+            vec![CoinSpend::new(
+                coin,
+                puzzle_reveal,
+                to_program([AggSigMe::new(public_key, Bytes::default())])?,
+            )],
+            Signature::default(),
+        );
+
+        let ack = peer.send_transaction(spend_bundle).await?;
+        assert_eq!(ack.status, 3);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_apply_bundle() -> anyhow::Result<()> {
+        let sim = Simulator::new().await?;
+        let public_key = secret_key()?.public_key();
+
+        let (puzzle_hash, puzzle_reveal) = to_puzzle(1)?;
+
+        let coin = sim.mint_coin(puzzle_hash, 3).await;
+        let spend_bundle = SpendBundle::new(
+            vec![CoinSpend::new(
+                coin,
+                puzzle_reveal.clone(),
+                to_program([
+                    CreateCoin::new(puzzle_hash, 1),
+                    CreateCoin::new(puzzle_hash, 2),
+                ])?,
+            )],
+            Signature::default(),
+        );
+
+        let effects = sim.apply_bundle(spend_bundle.clone()).await?;
+        assert_eq!(effects.spent_coin_ids, vec![coin.coin_id()]);
+
+        // The order of the created coins isn't significant.
+        assert_eq!(effects.created_coins.len(), 2);
+        for amount in [1, 2] {
+            assert!(effects.created_coins.contains(&Coin::new(
+                coin.coin_id(),
+                puzzle_hash,
+                amount
+            )));
+        }
+
+        assert!(matches!(
+            sim.apply_bundle(spend_bundle).await,
+            Err(SimulatorError::DoubleSpend)
+        ));
+
+        let unsigned = sim.mint_coin(puzzle_hash, 0).await;
+        let unsigned_bundle = SpendBundle::new(
+            vec![CoinSpend::new(
+                unsigned,
+                puzzle_reveal.clone(),
+                to_program([AggSigMe::new(public_key, Bytes::default())])?,
+            )],
+            Signature::default(),
+        );
+        assert!(matches!(
+            sim.apply_bundle(unsigned_bundle).await,
+            Err(SimulatorError::BadSignature)
+        ));
+
+        let locked = sim.mint_coin(puzzle_hash, 0).await;
+        let locked_bundle = SpendBundle::new(
+            vec![CoinSpend::new(
+                locked,
+                puzzle_reveal,
+                to_program([AssertHeightAbsolute::new(100)])?,
+            )],
+            Signature::default(),
+        );
+        assert!(matches!(
+            sim.apply_bundle(locked_bundle).await,
+            Err(SimulatorError::Validation(ValidationErr(
+                _,
+                ErrorCode::AssertHeightAbsoluteFailed
+            )))
+        ));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_agg_sig_me_replay_across_networks() -> anyhow::Result<()> {
+        let mainnet = Simulator::new().await?;
+        let testnet = Simulator::with_config(SimulatorConfig {
+            genesis_challenge: Bytes32::new([7; 32]),
+            ..Default::default()
+        })
+        .await?;
+        let peer = testnet.connect().await?;
+
+        let sk = secret_key()?;
+        let (puzzle_hash, puzzle_reveal) = to_puzzle(1)?;
+        let coin = testnet.mint_coin(puzzle_hash, 0).await;
+
+        let coin_spends = vec![CoinSpend::new(
+            coin,
+            puzzle_reveal,
+            to_program([AggSigMe::new(sk.public_key(), Bytes::default())])?,
+        )];
+
+        // A bundle signed for another network is rejected.
+        let signature = sign_transaction(
+            &coin_spends,
+            &[sk.clone()],
+            mainnet.config().genesis_challenge,
+        )?;
+        let ack = peer
+            .send_transaction(SpendBundle::new(coin_spends.clone(), signature))
+            .await?;
+        assert_eq!(ack.status, 3);
+
+        let signature = sign_transaction(&coin_spends, &[sk], testnet.config().genesis_challenge)?;
+        let ack = peer
+            .send_transaction(SpendBundle::new(coin_spends, signature))
+            .await?;
+        assert_eq!(ack.status, 1);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_signatures_not_required() -> anyhow::Result<()> {
+        let sim = Simulator::with_config(SimulatorConfig {
+            require_signatures: false,
+            ..Default::default()
+        })
+        .await?;
+        let peer = sim.connect().await?;
+        let public_key = secret_key()?.public_key();
+
+        let (puzzle_hash, puzzle_reveal) = to_puzzle(1)?;
+
+        let coin = sim.mint_coin(puzzle_hash, 0).await;
+
+        let spend_bundle = SpendBundle::new(
+            vec![CoinSpend::new(
+                coin,
+                puzzle_reveal,
+                to_program([AggSigMe::new(public_key, Bytes::default())])?,
+            )],
+            Signature::default(),
+        );
+
+        let ack = peer.send_transaction(spend_bundle).await?;
+        assert_eq!(ack.error, None);
+        assert_eq!(ack.status, 1);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_infinity_signature() -> anyhow::Result<()> {
+        let sim = Simulator::new().await?;
+        let peer = sim.connect().await?;
+
+        let (puzzle_hash, puzzle_reveal) = to_puzzle(1)?;
+
+        let coin = sim.mint_coin(puzzle_hash, 0).await;
+
+        let spend_bundle = SpendBundle::new(
+            vec![CoinSpend::new(
+                coin,
+                puzzle_reveal,
+                to_program([AggSigMe::new(PublicKey::default(), Bytes::default())])?,
+            )],
+            Signature::default(),
+        );
+
+        let ack = peer.send_transaction(spend_bundle).await?;
+        assert_eq!(ack.status, 3);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_valid_signature() -> anyhow::Result<()> {
+        let sim = Simulator::new().await?;
+        let peer = sim.connect().await?;
+        let sk = secret_key()?;
+        let pk = sk.public_key();
+
+        let (puzzle_hash, puzzle_reveal) = to_puzzle(1)?;
+
+        let coin = sim.mint_coin(puzzle_hash, 0).await;
+
+        test_transaction(
+            &peer,
+            vec![CoinSpend::new(
+                coin,
+                puzzle_reveal,
+                to_program([AggSigMe::new(pk, b"Hello, world!".to_vec().into())])?,
+            )],
+            &[sk],
+            sim.config.genesis_challenge,
+        )
+        .await;
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_aggregated_signature() -> anyhow::Result<()> {
+        let sim = Simulator::new().await?;
+        let peer = sim.connect().await?;
+
+        let sk1 = secret_key()?.derive_unhardened(0);
+        let pk1 = sk1.public_key();
+
+        let sk2 = secret_key()?.derive_unhardened(1);
+        let pk2 = sk2.public_key();
+
+        let (puzzle_hash, puzzle_reveal) = to_puzzle(1)?;
+
+        let coin = sim.mint_coin(puzzle_hash, 0).await;
+
+        test_transaction(
+            &peer,
+            vec![CoinSpend::new(
+                coin,
+                puzzle_reveal,
+                to_program([
+                    AggSigMe::new(pk1, b"Hello, world!".to_vec().into()),
+                    AggSigMe::new(pk2, b"Goodbye, world!".to_vec().into()),
+                ])?,
+            )],
+            &[sk1, sk2],
+            sim.config.genesis_challenge,
+        )
+        .await;
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_seconds_relative() -> anyhow::Result<()> {
+        let sim = Simulator::new().await?;
+        let peer = sim.connect().await?;
+
+        let (puzzle_hash, puzzle_reveal) = to_puzzle(1)?;
+
+        let coin = sim.mint_coin(puzzle_hash, 0).await;
+
+        let spend_bundle = SpendBundle::new(
+            vec![CoinSpend::new(
+                coin,
+                puzzle_reveal,
+                to_program([AssertSecondsRelative::new(100)])?,
+            )],
+            Signature::default(),
+        );
+
+        let ack = peer.send_transaction(spend_bundle.clone()).await?;
+        assert_eq!(ack.status, 3);
+
+        sim.pass_time(100).await;
+        assert_eq!(sim.timestamp().await, 100);
+
+        let ack = peer.send_transaction(spend_bundle).await?;
+        assert_eq!(ack.status, 1);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_replay() -> anyhow::Result<()> {
+        let sim = Simulator::new().await?;
+        let peer = sim.connect().await?;
+
+        let (puzzle_hash, puzzle_reveal) = to_puzzle(1)?;
+
+        let coin = sim.mint_coin(puzzle_hash, 1).await;
+        let child = Coin::new(coin.coin_id(), puzzle_hash, 1);
+
+        let bundles = vec![
+            SpendBundle::new(
+                vec![CoinSpend::new(
+                    coin,
+                    puzzle_reveal.clone(),
+                    to_program([CreateCoin::new(puzzle_hash, 1)])?,
+                )],
+                Signature::default(),
+            ),
+            SpendBundle::new(
+                vec![CoinSpend::new(child, puzzle_reveal, to_program(())?)],
+                Signature::default(),
+            ),
+        ];
+
+        for bundle in bundles.clone() {
+            let ack = peer.send_transaction(bundle).await?;
+            assert_eq!(ack.status, 1);
+        }
+
+        sim.reset().await?;
+        assert_eq!(sim.coin_state(coin.coin_id()).await, None);
 
-        let coin = sim.mint_coin(puzzle_hash, 0).await;
+        sim.replay(bundles.clone()).await?;
 
-        let spend_bundle = SpendBundle::new(
-            vec![CoinSpend::new(
-                coin,
-                puzzle_reveal,
-                to_program([AggSigMe::new(public_key, Bytes::default())])?,
-            )],
-            Signature::default(),
-        );
+        let coin_state = sim.coin_state(child.coin_id()).await.expect("missing coin");
+        assert!(coin_state.spent_height.is_some());
 
-        let ack = peer.send_transaction(spend_bundle).await?;
-        assert_eq!(ack.status, 3);
+        // Replaying the same bundles again should fail on the first double spend.
+        let error = sim.replay(bundles).await.unwrap_err();
+        assert!(matches!(
+            error,
+            SimulatorError::ReplayRejected { index: 0, .. }
+        ));
 
         Ok(())
     }
 
     #[tokio::test]
-    async fn test_infinity_signature() -> anyhow::Result<()> {
+    async fn test_replay_is_atomic() -> anyhow::Result<()> {
         let sim = Simulator::new().await?;
         let peer = sim.connect().await?;
+        let mut receiver = peer.receiver().resubscribe();
 
         let (puzzle_hash, puzzle_reveal) = to_puzzle(1)?;
 
-        let coin = sim.mint_coin(puzzle_hash, 0).await;
+        let coin = Coin::new(Bytes32::new([1; 32]), puzzle_hash, 1);
+        let child = Coin::new(coin.coin_id(), puzzle_hash, 1);
+        let unrelated = Coin::new(Bytes32::new([2; 32]), puzzle_hash, 1);
 
-        let spend_bundle = SpendBundle::new(
+        peer.register_for_ph_updates(vec![puzzle_hash], 0).await?;
+
+        let create_child = SpendBundle::new(
             vec![CoinSpend::new(
                 coin,
-                puzzle_reveal,
+                puzzle_reveal.clone(),
+                to_program([CreateCoin::new(puzzle_hash, 1)])?,
+            )],
+            Signature::default(),
+        );
+        let spend_child = SpendBundle::new(
+            vec![CoinSpend::new(
+                child,
+                puzzle_reveal.clone(),
+                to_program(())?,
+            )],
+            Signature::default(),
+        );
+        let invalid = SpendBundle::new(
+            vec![CoinSpend::new(
+                unrelated,
+                puzzle_reveal.clone(),
                 to_program([AggSigMe::new(PublicKey::default(), Bytes::default())])?,
             )],
             Signature::default(),
         );
 
-        let ack = peer.send_transaction(spend_bundle).await?;
-        assert_eq!(ack.status, 3);
-
-        Ok(())
-    }
-
-    #[tokio::test]
-    async fn test_valid_signature() -> anyhow::Result<()> {
-        let sim = Simulator::new().await?;
-        let peer = sim.connect().await?;
-        let sk = secret_key()?;
-        let pk = sk.public_key();
+        // A rejected bundle rolls back the ones before it, including the coins minted for them.
+        let error = sim
+            .replay(vec![create_child.clone(), invalid])
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            error,
+            SimulatorError::ReplayRejected { index: 1, .. }
+        ));
+        assert_eq!(sim.coin_state(coin.coin_id()).await, None);
+        assert_eq!(sim.coin_state(unrelated.coin_id()).await, None);
+        assert_eq!(sim.height().await, 0);
+
+        let spend_unrelated = SpendBundle::new(
+            vec![CoinSpend::new(unrelated, puzzle_reveal, to_program(())?)],
+            Signature::default(),
+        );
 
-        let (puzzle_hash, puzzle_reveal) = to_puzzle(1)?;
+        sim.replay(vec![create_child, spend_child, spend_unrelated])
+            .await?;
 
-        let coin = sim.mint_coin(puzzle_hash, 0).await;
+        // Missing coins are minted when their bundle is reached, rather than all up front.
+        let unrelated_state = sim.coin_state(unrelated.coin_id()).await.unwrap();
+        assert_eq!(unrelated_state.created_height, Some(2));
 
-        test_transaction(
-            &peer,
-            vec![CoinSpend::new(
-                coin,
-                puzzle_reveal,
-                to_program([AggSigMe::new(pk, b"Hello, world!".to_vec().into())])?,
-            )],
-            &[sk],
-            sim.config.genesis_challenge,
-        )
-        .await;
+        // Subscribed peers are notified of the final state of each coin.
+        peer.register_for_ph_updates(vec![puzzle_hash], 0).await?;
+        let updates = coin_state_updates(&mut receiver);
+        assert_eq!(updates.len(), 1);
+        assert_eq!(updates[0].items.len(), 3);
+
+        for coin_state in [
+            sim.coin_state(coin.coin_id()).await.unwrap(),
+            sim.coin_state(child.coin_id()).await.unwrap(),
+            unrelated_state,
+        ] {
+            assert!(updates[0].items.contains(&coin_state));
+        }
 
         Ok(())
     }
 
     #[tokio::test]
-    async fn test_aggregated_signature() -> anyhow::Result<()> {
-        let sim = Simulator::new().await?;
-        let peer = sim.connect().await?;
-
-        let sk1 = secret_key()?.derive_unhardened(0);
-        let pk1 = sk1.public_key();
-
-        let sk2 = secret_key()?.derive_unhardened(1);
-        let pk2 = sk2.public_key();
+    async fn test_singleton_rules() -> anyhow::Result<()> {
+        let mut allocator = Allocator::new();
 
-        let (puzzle_hash, puzzle_reveal) = to_puzzle(1)?;
+        let launcher_coin = Coin::new(
+            Bytes32::new([1; 32]),
+            SINGLETON_LAUNCHER_PUZZLE_HASH.into(),
+            1,
+        );
+        let launcher_id = launcher_coin.coin_id();
 
-        let coin = sim.mint_coin(puzzle_hash, 0).await;
+        let singleton_mod = node_from_bytes(&mut allocator, &SINGLETON_TOP_LAYER_PUZZLE)?;
+        let inner_puzzle = 1.to_clvm(&mut allocator)?;
+        let puzzle = CurriedProgram {
+            program: singleton_mod,
+            args: SingletonArgs::new(launcher_id, inner_puzzle),
+        }
+        .to_clvm(&mut allocator)?;
+        let puzzle_reveal = Program::from_node_ptr(&allocator, puzzle)?;
+
+        let coin = Coin::new(launcher_id, tree_hash(&allocator, puzzle).into(), 3);
+
+        let spend = |amount: u64| -> anyhow::Result<SpendBundle> {
+            let solution = SingletonSolution {
+                lineage_proof: Proof::Eve(EveProof {
+                    parent_coin_info: launcher_coin.parent_coin_info,
+                    amount: launcher_coin.amount,
+                }),
+                amount: coin.amount,
+                inner_solution: [CreateCoin::new(Bytes32::default(), amount)],
+            };
+            Ok(SpendBundle::new(
+                vec![CoinSpend::new(
+                    coin,
+                    puzzle_reveal.clone(),
+                    to_program(solution)?,
+                )],
+                Signature::default(),
+            ))
+        };
 
-        test_transaction(
-            &peer,
-            vec![CoinSpend::new(
-                coin,
-                puzzle_reveal,
-                to_program([
-                    AggSigMe::new(pk1, b"Hello, world!".to_vec().into()),
-                    AggSigMe::new(pk2, b"Goodbye, world!".to_vec().into()),
-                ])?,
-            )],
-            &[sk1, sk2],
-            sim.config.genesis_challenge,
-        )
-        .await;
+        // Recreating the singleton with an even amount is caught before running the spend.
+        let sim = Simulator::new().await?;
+        let error = sim.replay(vec![spend(2)?]).await.unwrap_err();
+        assert!(matches!(
+            error,
+            SimulatorError::ReplayRejected { error, .. }
+                if matches!(*error, SimulatorError::InvalidSingletonSpend(coin_id) if coin_id == coin.coin_id())
+        ));
+
+        sim.replay(vec![spend(3)?]).await?;
+
+        let sim = Simulator::with_config(SimulatorConfig {
+            enforce_singleton_rules: false,
+            ..Default::default()
+        })
+        .await?;
+        let error = sim.replay(vec![spend(2)?]).await.unwrap_err();
+        assert!(matches!(
+            error,
+            SimulatorError::ReplayRejected { error, .. }
+                if matches!(*error, SimulatorError::Validation(_))
+        ));
 
         Ok(())
     }
@@ -650,6 +1882,103 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn test_resync_after_reconnect() -> anyhow::Result<()> {
+        let sim = Simulator::with_config(SimulatorConfig {
+            sequential_mint_heights: true,
+            ..Default::default()
+        })
+        .await?;
+
+        let puzzle_hash = Bytes32::new([1; 32]);
+        let mut coins = sim.mint_coins(vec![(puzzle_hash, 1); 2]).await?;
+
+        let peer = sim.connect().await?;
+        let coin_states = peer.register_for_ph_updates(vec![puzzle_hash], 0).await?;
+        assert_eq!(coin_states.len(), 2);
+
+        // Subscriptions are lost when the peer disconnects.
+        let last_height = sim.height().await;
+        drop(peer);
+        sim.disconnect_all().await;
+
+        coins.extend(sim.mint_coins(vec![(puzzle_hash, 2)]).await?);
+
+        // Only coins changed at or after the last known height are returned after reconnecting.
+        let peer = sim.connect().await?;
+
+        let coin_states = peer
+            .register_for_ph_updates(vec![puzzle_hash], last_height)
+            .await?;
+        assert_eq!(coin_states.len(), 1);
+        assert_eq!(coin_states[0].coin, coins[2]);
+
+        let coin_states = peer
+            .register_for_coin_updates(coins.iter().map(Coin::coin_id).collect(), last_height)
+            .await?;
+        assert_eq!(coin_states.len(), 1);
+        assert_eq!(coin_states[0].coin, coins[2]);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_filtered_coin_states() -> anyhow::Result<()> {
+        let sim = Simulator::new().await?;
+        let peer = sim.connect().await?;
+
+        let (puzzle_hash, puzzle_reveal) = to_puzzle(1)?;
+
+        let unspent = sim.mint_coin(puzzle_hash, 1).await;
+        let spent = sim.mint_coin(puzzle_hash, 2).await;
+        let hinted = sim.mint_coin(Bytes32::default(), 3).await;
+        sim.add_hint(hinted.coin_id(), puzzle_hash).await;
+
+        let spend_bundle = SpendBundle::new(
+            vec![CoinSpend::new(spent, puzzle_reveal, to_program(())?)],
+            Signature::default(),
+        );
+        let ack = peer.send_transaction(spend_bundle).await?;
+        assert_eq!(ack.status, 1);
+
+        let recent = sim.mint_coin(puzzle_hash, 4).await;
+
+        let coins = |coin_states: Vec<CoinState>| -> Vec<Coin> {
+            coin_states.into_iter().map(|cs| cs.coin).collect()
+        };
+
+        let all = CoinStateFilters::new(true, true, true, 0);
+        assert_eq!(
+            coins(sim.filtered_coin_states(puzzle_hash, all.clone(), 0).await),
+            vec![unspent, spent, recent, hinted]
+        );
+
+        let unspent_only = CoinStateFilters::new(false, true, false, 0);
+        assert_eq!(
+            coins(sim.filtered_coin_states(puzzle_hash, unspent_only, 0).await),
+            vec![unspent, recent]
+        );
+
+        let spent_only = CoinStateFilters::new(true, false, false, 0);
+        assert_eq!(
+            coins(sim.filtered_coin_states(puzzle_hash, spent_only, 0).await),
+            vec![spent]
+        );
+
+        let min_amount = CoinStateFilters::new(true, true, true, 3);
+        assert_eq!(
+            coins(sim.filtered_coin_states(puzzle_hash, min_amount, 0).await),
+            vec![recent, hinted]
+        );
+
+        assert_eq!(
+            coins(sim.filtered_coin_states(puzzle_hash, all, 1).await),
+            vec![recent]
+        );
+
+        Ok(())
+    }
+
     #[tokio::test]
     async fn test_spent_hint_subscription() -> anyhow::Result<()> {
         let sim = Simulator::new().await?;
@@ -738,6 +2067,51 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn test_hint_with_extra_memos() -> anyhow::Result<()> {
+        let sim = Simulator::new().await?;
+        let peer = sim.connect().await?;
+        let mut receiver = peer.receiver().resubscribe();
+
+        let hint = Bytes32::new([42; 32]);
+        let (puzzle_hash, puzzle_reveal) = to_puzzle(1)?;
+
+        let coin = sim.mint_coin(puzzle_hash, 0).await;
+
+        let coin_states = peer.register_for_ph_updates(vec![hint], 0).await?;
+        assert_eq!(coin_states.len(), 0);
+
+        let create_coin = CreateCoin::with_memos(
+            puzzle_hash,
+            0,
+            vec![hint.into(), Bytes::new(b"extra memo".to_vec())],
+        );
+        assert_eq!(create_coin.hint(), Some(hint));
+
+        let spend_bundle = SpendBundle::new(
+            vec![CoinSpend::new(
+                coin,
+                puzzle_reveal,
+                to_program([create_coin])?,
+            )],
+            Signature::default(),
+        );
+
+        let ack = peer.send_transaction(spend_bundle).await?;
+        assert_eq!(ack.status, 1);
+
+        let child = Coin::new(coin.coin_id(), puzzle_hash, 0);
+
+        let updates = coin_state_updates(&mut receiver);
+        assert_eq!(updates.len(), 1);
+        assert_eq!(updates[0].items, vec![CoinState::new(child, None, Some(0))]);
+
+        let coin_states = peer.register_for_ph_updates(vec![hint], 0).await?;
+        assert_eq!(coin_states, vec![CoinState::new(child, None, Some(0))]);
+
+        Ok(())
+    }
+
     #[tokio::test]
     async fn test_request_coin_state() -> anyhow::Result<()> {
         let sim = Simulator::new().await?;
@@ -855,4 +2229,236 @@ mod tests {
 
         Ok(())
     }
+
+    #[tokio::test]
+    async fn test_set_reject() -> anyhow::Result<()> {
+        let sim = Simulator::new().await?;
+        let peer = sim.connect().await?;
+
+        let request = RequestPuzzleState::new(
+            vec![Bytes32::default()],
+            None,
+            sim.config().genesis_challenge,
+            CoinStateFilters::new(true, true, true, 0),
+            false,
+        );
+
+        sim.set_reject(
+            ProtocolMessageTypes::RequestPuzzleState,
+            RejectStateReason::ExceededSubscriptionLimit,
+        )
+        .await;
+
+        let error = peer
+            .request_or_reject::<RespondPuzzleState, RejectPuzzleState, _>(request.clone())
+            .await
+            .expect_err("expected rejection");
+        assert!(matches!(
+            error,
+            chia_client::Error::Rejection(rejection)
+                if rejection.reason == RejectStateReason::ExceededSubscriptionLimit
+        ));
+
+        // Only the next request is rejected.
+        peer.request_or_reject::<RespondPuzzleState, RejectPuzzleState, _>(request)
+            .await?;
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_request_puzzle_state_pages() -> anyhow::Result<()> {
+        // Each coin is minted at its own height, so every page is filled up to the limit.
+        let cases = [
+            (
+                SimulatorConfig {
+                    puzzle_state_batch_size: 2,
+                    ..Default::default()
+                },
+                5,
+                vec![2, 2, 1],
+            ),
+            (
+                SimulatorConfig {
+                    max_response_coins: 7,
+                    ..Default::default()
+                },
+                50,
+                vec![7, 7, 7, 7, 7, 7, 7, 1],
+            ),
+        ];
+
+        for (config, coin_count, expected_page_sizes) in cases {
+            let sim = Simulator::with_config(SimulatorConfig {
+                sequential_mint_heights: true,
+                ..config
+            })
+            .await?;
+            let peer = sim.connect().await?;
+
+            let puzzle_hash = Bytes32::new([1; 32]);
+            let coins = sim.mint_coins(vec![(puzzle_hash, 1); coin_count]).await?;
+
+            let mut previous_height = None;
+            let mut header_hash = sim.config().genesis_challenge;
+            let mut pages = Vec::new();
+
+            loop {
+                let response = peer
+                    .request_or_reject::<RespondPuzzleState, RejectPuzzleState, _>(
+                        RequestPuzzleState::new(
+                            vec![puzzle_hash],
+                            previous_height,
+                            header_hash,
+                            CoinStateFilters::new(true, true, true, 0),
+                            false,
+                        ),
+                    )
+                    .await?;
+
+                pages.push(response.coin_states);
+
+                if response.is_finished {
+                    assert_eq!(response.height, sim.height().await);
+                    break;
+                }
+
+                previous_height = Some(response.height);
+                header_hash = response.header_hash;
+            }
+
+            assert_eq!(
+                pages.iter().map(Vec::len).collect::<Vec<usize>>(),
+                expected_page_sizes
+            );
+            assert_eq!(
+                pages
+                    .into_iter()
+                    .flatten()
+                    .map(|cs| cs.coin)
+                    .collect::<Vec<Coin>>(),
+                coins
+            );
+        }
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_puzzles_and_solutions_batch() -> anyhow::Result<()> {
+        let sim = Simulator::with_config(SimulatorConfig {
+            puzzle_solution_batch_size: 3,
+            ..Default::default()
+        })
+        .await?;
+        let peer = sim.connect().await?;
+
+        let (puzzle_hash, puzzle_reveal) = to_puzzle(1)?;
+        let coins = sim.mint_coins(vec![(puzzle_hash, 1); 5]).await?;
+
+        let coin_spends: Vec<CoinSpend> = coins
+            .iter()
+            .map(|coin| CoinSpend::new(*coin, puzzle_reveal.clone(), Program::default()))
+            .collect();
+        let spend_bundle = SpendBundle::new(coin_spends.clone(), Signature::default());
+        let ack = peer.send_transaction(spend_bundle).await?;
+        assert_eq!(ack.status, 1);
+
+        let unspent = Bytes32::new([9; 32]);
+        let mut coin_ids: Vec<Bytes32> = coins.iter().map(Coin::coin_id).collect();
+        coin_ids.insert(1, unspent);
+
+        let (first, cursor) = sim.puzzles_and_solutions(&coin_ids).await;
+        assert_eq!(cursor, Some(3));
+        assert_eq!(
+            first.iter().map(|r| r.coin_name).collect::<Vec<_>>(),
+            vec![coin_ids[0], coin_ids[2]]
+        );
+
+        let (rest, cursor) = sim.puzzles_and_solutions(&coin_ids[3..]).await;
+        assert_eq!(cursor, None);
+
+        let responses: Vec<PuzzleSolutionResponse> = first.into_iter().chain(rest).collect();
+        assert_eq!(responses.len(), coins.len());
+
+        for (response, coin_spend) in responses.iter().zip(&coin_spends) {
+            assert_eq!(response.coin_name, coin_spend.coin.coin_id());
+            assert_eq!(response.puzzle, coin_spend.puzzle_reveal);
+            assert_eq!(response.solution, coin_spend.solution);
+            let spent_height = sim
+                .coin_state(coin_spend.coin.coin_id())
+                .await
+                .and_then(|coin_state| coin_state.spent_height);
+            assert_eq!(Some(response.height), spent_height);
+        }
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_farm_block_to() -> anyhow::Result<()> {
+        let sim = Simulator::new().await?;
+        let peer = sim.connect().await?;
+        let mut receiver = peer.receiver().resubscribe();
+
+        let reward_puzzle_hash = Bytes32::new([7; 32]);
+        peer.register_for_ph_updates(vec![reward_puzzle_hash], 0)
+            .await?;
+
+        // Confirm a transaction with a fee of 100 mojos.
+        let (puzzle_hash, puzzle_reveal) = to_puzzle(1)?;
+        let coin = sim.mint_coin(puzzle_hash, 1000).await;
+        let ack = peer
+            .send_transaction(SpendBundle::new(
+                vec![CoinSpend::new(
+                    coin,
+                    puzzle_reveal,
+                    to_program([CreateCoin::new(puzzle_hash, 900)])?,
+                )],
+                Signature::default(),
+            ))
+            .await?;
+        assert_eq!(ack.status, 1);
+        let _ = coin_state_updates(&mut receiver);
+
+        // Fees confirmed before saving are still paid after loading.
+        let path = std::env::temp_dir().join(format!("simulator-fees-{}.bin", std::process::id()));
+        sim.save(&path).await?;
+        let loaded = Simulator::load(&path, SimulatorConfig::default()).await?;
+        std::fs::remove_file(&path)?;
+        let (loaded_farmer_coin, _) = loaded.farm_block_to(reward_puzzle_hash).await?;
+        assert_eq!(loaded_farmer_coin.amount, 250_000_000_000 + 100);
+
+        let height = sim.height().await;
+        let (farmer_coin, pool_coin) = sim.farm_block_to(reward_puzzle_hash).await?;
+        assert_eq!(sim.height().await, height + 1);
+
+        let genesis_challenge = sim.config().genesis_challenge;
+        let height_bytes = u128::from(height).to_be_bytes();
+        assert_eq!(farmer_coin.parent_coin_info[..16], genesis_challenge[16..]);
+        assert_eq!(farmer_coin.parent_coin_info[16..], height_bytes);
+        assert_eq!(pool_coin.parent_coin_info[..16], genesis_challenge[..16]);
+        assert_eq!(pool_coin.parent_coin_info[16..], height_bytes);
+        assert_eq!(farmer_coin.amount, 250_000_000_000 + 100);
+        assert_eq!(pool_coin.amount, 1_750_000_000_000);
+
+        let update = timeout(Duration::from_secs(5), async {
+            loop {
+                if let Ok(PeerEvent::CoinStateUpdate(update)) = receiver.recv().await {
+                    return update;
+                }
+            }
+        })
+        .await?;
+        let coins: Vec<Coin> = update.items.iter().map(|cs| cs.coin).collect();
+        assert_eq!(coins, vec![farmer_coin, pool_coin]);
+
+        // Fees are only credited once.
+        let (farmer_coin, _) = sim.farm_block_to(reward_puzzle_hash).await?;
+        assert_eq!(farmer_coin.amount, 250_000_000_000);
+
+        sim.validate().await?;
+
+        Ok(())
+    }
 }
@@ -1,7 +1,7 @@
 use std::io;
 
 use chia_consensus::gen::validation_error::ValidationErr;
-use chia_protocol::ProtocolMessageTypes;
+use chia_protocol::{Bytes32, ProtocolMessageTypes};
 use chia_sdk_signer::SignerError;
 use futures_channel::mpsc::SendError;
 use thiserror::Error;
@@ -24,12 +24,31 @@ pub enum SimulatorError {
     #[error("signer error: {0}")]
     Signer(#[from] SignerError),
 
-    #[error("validation error: {0}")]
+    /// The spend bundle failed validation, for example because one of its conditions wasn't met.
+    #[error("{0}")]
     Validation(#[from] ValidationErr),
 
+    #[error("the aggregated signature of the spend bundle is invalid")]
+    BadSignature,
+
+    #[error("the spend bundle spends a coin which has already been spent")]
+    DoubleSpend,
+
     #[error("send message error: {0}")]
     SendMessage(#[from] SendError),
 
     #[error("unsupported protocol message type: {0:?}")]
     UnsupportedMessage(ProtocolMessageTypes),
+
+    #[error("singleton spend of {0} must create exactly one odd child unless melted")]
+    InvalidSingletonSpend(Bytes32),
+
+    #[error("inconsistent simulator data: {0}")]
+    Inconsistent(String),
+
+    #[error("spend bundle {index} was rejected during replay: {error}")]
+    ReplayRejected {
+        index: usize,
+        error: Box<SimulatorError>,
+    },
 }
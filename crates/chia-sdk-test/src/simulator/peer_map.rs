@@ -7,7 +7,7 @@ use tokio_tungstenite::tungstenite::Message;
 pub(crate) type Ws = UnboundedSender<Message>;
 type Peers = HashMap<SocketAddr, Ws>;
 
-#[derive(Default, Clone)]
+#[derive(Debug, Default, Clone)]
 pub(crate) struct PeerMap(Arc<Mutex<Peers>>);
 
 impl PeerMap {
@@ -19,6 +19,10 @@ impl PeerMap {
         self.0.lock().await.remove(&addr);
     }
 
+    pub(crate) async fn is_empty(&self) -> bool {
+        self.0.lock().await.is_empty()
+    }
+
     pub(crate) async fn peers(&self) -> Vec<(SocketAddr, Ws)> {
         self.0
             .lock()
@@ -1,4 +1,5 @@
 use chia_protocol::Bytes32;
+use chia_sdk_types::MAX_BLOCK_COST;
 use hex_literal::hex;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -6,7 +7,22 @@ pub struct SimulatorConfig {
     pub genesis_challenge: Bytes32,
     pub max_subscriptions: usize,
     pub max_response_coins: usize,
+    /// The maximum number of coin states returned in each page of a puzzle state request.
+    /// Pages end on a height boundary, so they can be smaller than this.
     pub puzzle_state_batch_size: usize,
+    /// The maximum number of coins returned by each call to [`Simulator::puzzles_and_solutions`](crate::Simulator::puzzles_and_solutions).
+    pub puzzle_solution_batch_size: usize,
+    /// The maximum cost of a spend bundle. Bundles that exceed it are rejected.
+    pub max_block_cost: u64,
+    /// Whether the aggregated signature of spend bundles is verified.
+    /// Disabling this is useful for tests that only exercise puzzle logic.
+    pub require_signatures: bool,
+    /// Whether each coin minted by [`Simulator::mint_coins`](crate::Simulator::mint_coins) is confirmed in its own block.
+    /// By default, they are all created at the current height.
+    pub sequential_mint_heights: bool,
+    /// Whether singleton spends are checked to create exactly one odd child unless melted, before running them.
+    /// This reports the offending coin, rather than a generic failure from the singleton puzzle.
+    pub enforce_singleton_rules: bool,
 }
 
 impl Default for SimulatorConfig {
@@ -18,6 +34,12 @@ impl Default for SimulatorConfig {
             max_subscriptions: 200_000,
             max_response_coins: 100_000,
             puzzle_state_batch_size: 30_000,
+            puzzle_solution_batch_size: 1_000,
+            // The mempool only fills blocks up to 60% of the maximum cost.
+            max_block_cost: MAX_BLOCK_COST / 5 * 3,
+            require_signatures: true,
+            sequential_mint_heights: false,
+            enforce_singleton_rules: true,
         }
     }
 }
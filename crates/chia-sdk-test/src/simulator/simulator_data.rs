@@ -1,4 +1,4 @@
-use std::{collections::HashSet, net::SocketAddr};
+use std::{collections::HashSet, io::Cursor, net::SocketAddr};
 
 use chia_bls::{aggregate_verify, PublicKey};
 use chia_consensus::gen::{
@@ -9,29 +9,55 @@ use chia_consensus::gen::{
     solution_generator::solution_generator,
     validation_error::{ErrorCode, ValidationErr},
 };
-use chia_protocol::{Bytes32, Coin, CoinState, PuzzleSolutionResponse, SpendBundle};
+use chia_protocol::{
+    Bytes32, Coin, CoinSpend, CoinState, CoinStateFilters, ProtocolMessageTypes,
+    PuzzleSolutionResponse, RejectStateReason, SpendBundle,
+};
 use chia_sdk_signer::RequiredSignature;
+use chia_sdk_types::conditions::singleton_spend_output;
+use chia_traits::Streamable;
 use clvmr::{
     sha2::{Digest, Sha256},
     Allocator, NodePtr,
 };
 use indexmap::{IndexMap, IndexSet};
-use tokio::sync::MutexGuard;
 
-use super::{error::SimulatorError, simulator_config::SimulatorConfig};
+use super::{
+    error::SimulatorError, simulator_config::SimulatorConfig, TransactionInfo, TransactionStatus,
+};
+use time_locks::validate_time_locks;
+
+mod time_locks;
 
 #[derive(Debug, Default, Clone)]
 pub(crate) struct SimulatorData {
     height: u32,
+    timestamp: u64,
+    coin_timestamps: IndexMap<Bytes32, u64>,
     coin_states: IndexMap<Bytes32, CoinState>,
     hinted_coins: IndexMap<Bytes32, IndexSet<Bytes32>>,
     puzzle_subscriptions: IndexMap<SocketAddr, IndexSet<Bytes32>>,
     coin_subscriptions: IndexMap<SocketAddr, IndexSet<Bytes32>>,
     puzzle_and_solutions: IndexMap<Bytes32, PuzzleSolutionResponse>,
+    transactions: IndexMap<Bytes32, u32>,
+    transaction_infos: IndexMap<Bytes32, TransactionInfo>,
+    rejections: IndexMap<Bytes32, String>,
+    forced_rejections: IndexMap<ProtocolMessageTypes, RejectStateReason>,
+    pending_fees: u64,
 }
 
+/// The transaction id, cost and fee, additions, and removals of a saved [`TransactionInfo`].
+type SavedTransactionInfo = (Bytes32, (u64, u64), Vec<Coin>, Vec<Coin>);
+
+/// The block reward paid to the pool, in mojos.
+const POOL_REWARD: u64 = 1_750_000_000_000;
+
+/// The block reward paid to the farmer, in mojos, excluding transaction fees.
+const FARMER_REWARD: u64 = 250_000_000_000;
+
 impl SimulatorData {
     pub(crate) fn create_coin(&mut self, coin: Coin) {
+        self.coin_timestamps.insert(coin.coin_id(), self.timestamp);
         self.coin_states.insert(
             coin.coin_id(),
             CoinState::new(coin, None, Some(self.height)),
@@ -42,6 +68,98 @@ impl SimulatorData {
         self.hinted_coins.entry(hint).or_default().insert(coin_id);
     }
 
+    /// Checks that the internal state is consistent.
+    /// Header hashes are derived from the height rather than stored, so there's nothing to check for them.
+    pub(crate) fn validate(&self) -> Result<(), SimulatorError> {
+        for coin_id in self.coin_states.keys() {
+            self.validate_coin(*coin_id)?;
+        }
+
+        for (hint, coin_ids) in &self.hinted_coins {
+            for coin_id in coin_ids {
+                self.validate_hint(*hint, *coin_id)?;
+            }
+        }
+
+        for coin_id in self.puzzle_and_solutions.keys() {
+            self.validate_puzzle_and_solution(*coin_id)?;
+        }
+
+        for transaction_id in self.transaction_infos.keys() {
+            if !self.transactions.contains_key(transaction_id) {
+                return Err(SimulatorError::Inconsistent(format!(
+                    "transaction {transaction_id} has info but was never confirmed"
+                )));
+            }
+        }
+
+        Ok(())
+    }
+
+    fn validate_coin(&self, coin_id: Bytes32) -> Result<(), SimulatorError> {
+        let inconsistent = |message: String| Err(SimulatorError::Inconsistent(message));
+
+        let Some(coin_state) = self.coin_states.get(&coin_id) else {
+            return inconsistent(format!("unknown coin {coin_id}"));
+        };
+
+        if coin_id != coin_state.coin.coin_id() {
+            return inconsistent(format!("coin state {coin_id} has the wrong coin id"));
+        }
+
+        let Some(created_height) = coin_state.created_height else {
+            return inconsistent(format!("coin {coin_id} has no created height"));
+        };
+
+        if created_height > self.height {
+            return inconsistent(format!("coin {coin_id} was created after the peak"));
+        }
+
+        if let Some(spent_height) = coin_state.spent_height {
+            if spent_height < created_height {
+                return inconsistent(format!("coin {coin_id} was spent before it was created"));
+            }
+
+            if spent_height > self.height {
+                return inconsistent(format!("coin {coin_id} was spent after the peak"));
+            }
+
+            if !self.puzzle_and_solutions.contains_key(&coin_id) {
+                return inconsistent(format!("spent coin {coin_id} has no puzzle and solution"));
+            }
+        }
+
+        if !self.coin_timestamps.contains_key(&coin_id) {
+            return inconsistent(format!("coin {coin_id} has no timestamp"));
+        }
+
+        Ok(())
+    }
+
+    fn validate_hint(&self, hint: Bytes32, coin_id: Bytes32) -> Result<(), SimulatorError> {
+        if !self.coin_states.contains_key(&coin_id) {
+            return Err(SimulatorError::Inconsistent(format!(
+                "hint {hint} points to unknown coin {coin_id}"
+            )));
+        }
+
+        Ok(())
+    }
+
+    fn validate_puzzle_and_solution(&self, coin_id: Bytes32) -> Result<(), SimulatorError> {
+        if self
+            .coin_states
+            .get(&coin_id)
+            .map_or(true, |coin_state| coin_state.spent_height.is_none())
+        {
+            return Err(SimulatorError::Inconsistent(format!(
+                "puzzle and solution for unspent coin {coin_id}"
+            )));
+        }
+
+        Ok(())
+    }
+
     #[allow(clippy::unused_self)]
     pub(crate) fn header_hash(&self, height: u32) -> Bytes32 {
         let mut hasher = Sha256::new();
@@ -49,10 +167,116 @@ impl SimulatorData {
         Bytes32::new(hasher.finalize().into())
     }
 
+    /// Advances to the next block without any transactions.
+    pub(crate) fn new_block(&mut self) {
+        self.height += 1;
+    }
+
+    /// Creates the pool and farmer reward coins for the current height and advances to the next block.
+    /// The farmer reward includes the fees of transactions confirmed since the last reward.
+    pub(crate) fn farm_block(
+        &mut self,
+        puzzle_hash: Bytes32,
+        genesis_challenge: Bytes32,
+    ) -> (Coin, Coin, IndexMap<SocketAddr, IndexSet<CoinState>>) {
+        let height = u128::from(self.height).to_be_bytes();
+
+        let mut pool_parent = [0; 32];
+        pool_parent[..16].copy_from_slice(&genesis_challenge[..16]);
+        pool_parent[16..].copy_from_slice(&height);
+
+        let mut farmer_parent = [0; 32];
+        farmer_parent[..16].copy_from_slice(&genesis_challenge[16..]);
+        farmer_parent[16..].copy_from_slice(&height);
+
+        let fees = std::mem::take(&mut self.pending_fees);
+        let farmer_coin = Coin::new(farmer_parent.into(), puzzle_hash, FARMER_REWARD + fees);
+        let pool_coin = Coin::new(pool_parent.into(), puzzle_hash, POOL_REWARD);
+
+        self.create_coin(farmer_coin);
+        self.create_coin(pool_coin);
+
+        let updates = [farmer_coin, pool_coin]
+            .into_iter()
+            .map(|coin| (coin.coin_id(), self.coin_states[&coin.coin_id()]))
+            .collect();
+        let peer_updates = self.peer_updates(&updates);
+
+        self.new_block();
+
+        (farmer_coin, pool_coin, peer_updates)
+    }
+
+    /// Collects the coin states each subscribed peer should be notified of after the given updates.
+    fn peer_updates(
+        &self,
+        updates: &IndexMap<Bytes32, CoinState>,
+    ) -> IndexMap<SocketAddr, IndexSet<CoinState>> {
+        let peers: Vec<SocketAddr> = self
+            .puzzle_subscriptions
+            .keys()
+            .chain(self.coin_subscriptions.keys())
+            .copied()
+            .collect();
+
+        let mut peer_updates = IndexMap::new();
+
+        for peer in peers {
+            let mut coin_states = IndexSet::new();
+
+            let coin_subscriptions = self
+                .coin_subscriptions
+                .get(&peer)
+                .cloned()
+                .unwrap_or_default();
+
+            let puzzle_subscriptions = self
+                .puzzle_subscriptions
+                .get(&peer)
+                .cloned()
+                .unwrap_or_default();
+
+            for (hint, coins) in &self.hinted_coins {
+                let Ok(hint) = hint.to_vec().try_into() else {
+                    continue;
+                };
+                let hint = Bytes32::new(hint);
+
+                if puzzle_subscriptions.contains(&hint) {
+                    coin_states.extend(coins.iter().map(|coin_id| self.coin_states[coin_id]));
+                }
+            }
+
+            for coin_id in updates.keys() {
+                if coin_subscriptions.contains(coin_id)
+                    || puzzle_subscriptions.contains(&self.coin_states[coin_id].coin.puzzle_hash)
+                {
+                    coin_states.insert(self.coin_states[coin_id]);
+                }
+            }
+
+            if coin_states.is_empty() {
+                continue;
+            };
+
+            peer_updates.insert(peer, coin_states);
+        }
+
+        peer_updates
+    }
+
     pub(crate) fn height(&self) -> u32 {
         self.height
     }
 
+    pub(crate) fn timestamp(&self) -> u64 {
+        self.timestamp
+    }
+
+    pub(crate) fn pass_time(&mut self, seconds: u64) {
+        self.timestamp += seconds;
+    }
+
     pub(crate) fn lookup_coin_ids(&self, coin_ids: &IndexSet<Bytes32>) -> Vec<CoinState> {
         coin_ids
             .iter()
@@ -86,6 +310,36 @@ impl SimulatorData {
         coin_states.into_values().collect()
     }
 
+    /// Looks up coin states by puzzle hash, applying the filters of a puzzle state request.
+    pub(crate) fn filtered_coin_states(
+        &self,
+        puzzle_hashes: IndexSet<Bytes32>,
+        filters: &CoinStateFilters,
+        min_height: u32,
+    ) -> Vec<CoinState> {
+        self.lookup_puzzle_hashes(puzzle_hashes, filters.include_hinted)
+            .into_iter()
+            .filter(|cs| {
+                if cs.spent_height.is_none() && !filters.include_unspent {
+                    return false;
+                }
+
+                if cs.spent_height.is_some() && !filters.include_spent {
+                    return false;
+                }
+
+                if cs.coin.amount < filters.min_amount {
+                    return false;
+                }
+
+                let created_height = cs.created_height.unwrap_or(0);
+                let spent_height = cs.spent_height.unwrap_or(0);
+                let height = u32::max(created_height, spent_height);
+                height >= min_height
+            })
+            .collect()
+    }
+
     pub(crate) fn add_coin_subscriptions(&mut self, peer: SocketAddr, coin_ids: IndexSet<Bytes32>) {
         self.coin_subscriptions
             .entry(peer)
@@ -124,16 +378,186 @@ impl SimulatorData {
             .collect()
     }
 
+    pub(crate) fn transaction_status(&self, transaction_id: Bytes32) -> TransactionStatus {
+        self.transactions
+            .get(&transaction_id)
+            .map_or(TransactionStatus::Unknown, |height| {
+                TransactionStatus::Confirmed { height: *height }
+            })
+    }
+
+    pub(crate) fn transaction_info(&self, transaction_id: Bytes32) -> Option<TransactionInfo> {
+        self.transaction_infos.get(&transaction_id).cloned()
+    }
+
+    pub(crate) fn reject_transaction(&mut self, transaction_id: Bytes32, reason: String) {
+        self.rejections.insert(transaction_id, reason);
+    }
+
+    pub(crate) fn rejection(&self, transaction_id: Bytes32) -> Option<String> {
+        self.rejections.get(&transaction_id).cloned()
+    }
+
+    pub(crate) fn force_rejection(
+        &mut self,
+        request_type: ProtocolMessageTypes,
+        reason: RejectStateReason,
+    ) {
+        self.forced_rejections.insert(request_type, reason);
+    }
+
+    pub(crate) fn take_forced_rejection(
+        &mut self,
+        request_type: ProtocolMessageTypes,
+    ) -> Option<RejectStateReason> {
+        self.forced_rejections.shift_remove(&request_type)
+    }
+
     pub(crate) fn coin_state(&self, coin_id: Bytes32) -> Option<CoinState> {
         self.coin_states.get(&coin_id).copied()
     }
+
+    pub(crate) fn all_coin_states(&self) -> Vec<CoinState> {
+        self.coin_states.values().copied().collect()
+    }
+
+    /// Serializes the blockchain state, excluding subscriptions.
+    pub(crate) fn stream(&self, out: &mut Vec<u8>) -> Result<(), SimulatorError> {
+        self.height.stream(out)?;
+        self.timestamp.stream(out)?;
+
+        let coin_timestamps: Vec<(Bytes32, u64)> = self
+            .coin_timestamps
+            .iter()
+            .map(|(coin_id, timestamp)| (*coin_id, *timestamp))
+            .collect();
+        coin_timestamps.stream(out)?;
+
+        self.all_coin_states().stream(out)?;
+
+        let hinted_coins: Vec<(Bytes32, Vec<Bytes32>)> = self
+            .hinted_coins
+            .iter()
+            .map(|(hint, coin_ids)| (*hint, coin_ids.iter().copied().collect()))
+            .collect();
+        hinted_coins.stream(out)?;
+
+        let puzzle_and_solutions: Vec<PuzzleSolutionResponse> =
+            self.puzzle_and_solutions.values().cloned().collect();
+        puzzle_and_solutions.stream(out)?;
+
+        let transactions: Vec<(Bytes32, u32)> = self
+            .transactions
+            .iter()
+            .map(|(transaction_id, height)| (*transaction_id, *height))
+            .collect();
+        transactions.stream(out)?;
+
+        let transaction_infos: Vec<SavedTransactionInfo> = self
+            .transaction_infos
+            .iter()
+            .map(|(transaction_id, info)| {
+                (
+                    *transaction_id,
+                    (info.cost, info.fee),
+                    info.additions.clone(),
+                    info.removals.clone(),
+                )
+            })
+            .collect();
+        transaction_infos.stream(out)?;
+
+        self.pending_fees.stream(out)?;
+
+        Ok(())
+    }
+
+    /// Parses the blockchain state serialized with [`SimulatorData::stream`], and validates it.
+    pub(crate) fn parse(input: &mut Cursor<&[u8]>) -> Result<Self, SimulatorError> {
+        let height = u32::parse::<false>(input)?;
+        let timestamp = u64::parse::<false>(input)?;
+        let coin_timestamps = Vec::<(Bytes32, u64)>::parse::<false>(input)?;
+        let coin_states = Vec::<CoinState>::parse::<false>(input)?;
+        let hinted_coins = Vec::<(Bytes32, Vec<Bytes32>)>::parse::<false>(input)?;
+        let puzzle_and_solutions = Vec::<PuzzleSolutionResponse>::parse::<false>(input)?;
+        let transactions = Vec::<(Bytes32, u32)>::parse::<false>(input)?;
+        let transaction_infos = Vec::<SavedTransactionInfo>::parse::<false>(input)?;
+        let pending_fees = u64::parse::<false>(input)?;
+
+        let data = Self {
+            height,
+            timestamp,
+            coin_timestamps: coin_timestamps.into_iter().collect(),
+            coin_states: coin_states
+                .into_iter()
+                .map(|coin_state| (coin_state.coin.coin_id(), coin_state))
+                .collect(),
+            hinted_coins: hinted_coins
+                .into_iter()
+                .map(|(hint, coin_ids)| (hint, coin_ids.into_iter().collect()))
+                .collect(),
+            puzzle_subscriptions: IndexMap::new(),
+            coin_subscriptions: IndexMap::new(),
+            puzzle_and_solutions: puzzle_and_solutions
+                .into_iter()
+                .map(|response| (response.coin_name, response))
+                .collect(),
+            transactions: transactions.into_iter().collect(),
+            transaction_infos: transaction_infos
+                .into_iter()
+                .map(|(transaction_id, (cost, fee), additions, removals)| {
+                    (
+                        transaction_id,
+                        TransactionInfo {
+                            cost,
+                            fee,
+                            additions,
+                            removals,
+                        },
+                    )
+                })
+                .collect(),
+            rejections: IndexMap::new(),
+            forced_rejections: IndexMap::new(),
+            pending_fees,
+        };
+
+        data.validate()?;
+        Ok(data)
+    }
+
+    /// Replaces the blockchain state with that of the snapshot, keeping the current subscriptions.
+    pub(crate) fn restore(&mut self, snapshot: SimulatorData) {
+        let puzzle_subscriptions = std::mem::take(&mut self.puzzle_subscriptions);
+        let coin_subscriptions = std::mem::take(&mut self.coin_subscriptions);
+
+        *self = Self {
+            puzzle_subscriptions,
+            coin_subscriptions,
+            ..snapshot
+        };
+    }
+}
+
+/// Checks that a singleton spend creates exactly one odd child, unless it's melted.
+/// Spends of coins which aren't singletons are ignored.
+fn validate_singleton_spend(
+    allocator: &mut Allocator,
+    coin_spend: &CoinSpend,
+) -> Result<(), SimulatorError> {
+    match singleton_spend_output(allocator, coin_spend) {
+        Ok(None) => Ok(()),
+        Ok(Some(output)) if output.is_valid() => Ok(()),
+        _ => Err(SimulatorError::InvalidSingletonSpend(
+            coin_spend.coin.coin_id(),
+        )),
+    }
 }
 
 pub(crate) fn new_transaction(
     config: &SimulatorConfig,
-    data: &mut MutexGuard<'_, SimulatorData>,
+    data: &mut SimulatorData,
     spend_bundle: SpendBundle,
-    max_cost: u64,
 ) -> Result<IndexMap<SocketAddr, IndexSet<CoinState>>, SimulatorError> {
     if spend_bundle.coin_spends.is_empty() {
         return Err(SimulatorError::Validation(ValidationErr(
@@ -142,8 +566,16 @@ pub(crate) fn new_transaction(
         )));
     }
 
+    let transaction_id = spend_bundle.name();
     let mut allocator = Allocator::new();
 
+    // Invalid singleton spends fail in the top layer anyway, but checking first reports which coin is at fault.
+    if config.enforce_singleton_rules {
+        for coin_spend in &spend_bundle.coin_spends {
+            validate_singleton_spend(&mut allocator, coin_spend)?;
+        }
+    }
+
     let generator = solution_generator(
         spend_bundle
             .coin_spends
@@ -156,7 +588,7 @@ pub(crate) fn new_transaction(
         &mut allocator,
         &generator,
         &[],
-        max_cost,
+        config.max_block_cost,
         MEMPOOL_MODE,
     )?;
 
@@ -178,23 +610,25 @@ pub(crate) fn new_transaction(
         )));
     }
 
-    let required_signatures = RequiredSignature::from_coin_spends(
-        &mut allocator,
-        &spend_bundle.coin_spends,
-        config.genesis_challenge,
-    )?;
-
-    if !aggregate_verify(
-        &spend_bundle.aggregated_signature,
-        required_signatures
-            .into_iter()
-            .map(|required| (required.public_key(), required.final_message()))
-            .collect::<Vec<(PublicKey, Vec<u8>)>>(),
-    ) {
-        return Err(SimulatorError::Validation(ValidationErr(
-            NodePtr::NIL,
-            ErrorCode::BadAggregateSignature,
-        )));
+    if config.require_signatures {
+        let required_signatures = RequiredSignature::from_coin_spends(
+            &mut allocator,
+            &spend_bundle.coin_spends,
+            config.genesis_challenge,
+        )?;
+
+        if !aggregate_verify(
+            &spend_bundle.aggregated_signature,
+            required_signatures
+                .into_iter()
+                .map(|required| (required.public_key(), required.final_message()))
+                .collect::<Vec<(PublicKey, Vec<u8>)>>(),
+        ) {
+            return Err(SimulatorError::Validation(ValidationErr(
+                NodePtr::NIL,
+                ErrorCode::BadAggregateSignature,
+            )));
+        }
     }
 
     let mut removed_coins = IndexMap::new();
@@ -249,6 +683,8 @@ pub(crate) fn new_transaction(
         removed_coins.insert(spend.coin_id, coin_state);
     }
 
+    validate_time_locks(data, &conds)?;
+
     // Validate removals.
     for (coin_id, coin_state) in &mut removed_coins {
         let height = data.height;
@@ -270,64 +706,83 @@ pub(crate) fn new_transaction(
         coin_state.spent_height = Some(height);
     }
 
+    let additions = added_coins
+        .values()
+        .map(|coin_state| coin_state.coin)
+        .collect();
+    let removals = removed_coins
+        .values()
+        .map(|coin_state| coin_state.coin)
+        .collect();
+
     // Update the coin data.
     let mut updates = added_coins.clone();
     updates.extend(removed_coins);
+    let timestamp = data.timestamp;
+    let height = data.height;
+    let fee =
+        u64::try_from(conds.removal_amount.saturating_sub(conds.addition_amount)).unwrap_or(0);
+    data.transactions.insert(transaction_id, height);
+    data.transaction_infos.insert(
+        transaction_id,
+        TransactionInfo {
+            cost: conds.cost,
+            fee,
+            additions,
+            removals,
+        },
+    );
+    data.pending_fees = data.pending_fees.saturating_add(fee);
     data.height += 1;
+    data.coin_timestamps
+        .extend(added_coins.keys().map(|coin_id| (*coin_id, timestamp)));
     data.coin_states.extend(updates.clone());
     data.hinted_coins.extend(added_hints.clone());
     data.puzzle_and_solutions.extend(puzzle_solutions);
 
-    let peers: Vec<SocketAddr> = data
-        .puzzle_subscriptions
-        .keys()
-        .chain(data.coin_subscriptions.keys())
-        .copied()
-        .collect();
-
-    let mut peer_updates = IndexMap::new();
-
-    // Send updates to peers.
-    for peer in peers {
-        let mut coin_states = IndexSet::new();
-
-        let coin_subscriptions = data
-            .coin_subscriptions
-            .get(&peer)
-            .cloned()
-            .unwrap_or_default();
-
-        let puzzle_subscriptions = data
-            .puzzle_subscriptions
-            .get(&peer)
-            .cloned()
-            .unwrap_or_default();
-
-        for (hint, coins) in &data.hinted_coins {
-            let Ok(hint) = hint.to_vec().try_into() else {
-                continue;
-            };
-            let hint = Bytes32::new(hint);
+    // Check the coins and hints touched by the transaction, so that bugs are reported where they happen.
+    for coin_id in updates.keys() {
+        data.validate_coin(*coin_id)?;
+    }
 
-            if puzzle_subscriptions.contains(&hint) {
-                coin_states.extend(coins.iter().map(|coin_id| data.coin_states[coin_id]));
-            }
-        }
-
-        for coin_id in updates.keys() {
-            if coin_subscriptions.contains(coin_id)
-                || puzzle_subscriptions.contains(&data.coin_states[coin_id].coin.puzzle_hash)
-            {
-                coin_states.insert(data.coin_states[coin_id]);
-            }
+    for (hint, coin_ids) in &added_hints {
+        for coin_id in coin_ids {
+            data.validate_hint(*hint, *coin_id)?;
         }
+    }
 
-        if coin_states.is_empty() {
-            continue;
-        };
+    Ok(data.peer_updates(&updates))
+}
 
-        peer_updates.insert(peer, coin_states);
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate() {
+        let mut data = SimulatorData::default();
+
+        let coin = Coin::new(Bytes32::default(), Bytes32::default(), 1);
+        data.create_coin(coin);
+        data.add_hint(coin.coin_id(), Bytes32::default());
+        assert!(data.validate().is_ok());
+
+        // A coin can't be spent before it was created.
+        let mut snapshot = data.clone();
+        snapshot.height = 2;
+        snapshot.coin_states[&coin.coin_id()] = CoinState::new(coin, Some(1), Some(2));
+        assert!(matches!(
+            snapshot.validate(),
+            Err(SimulatorError::Inconsistent(_))
+        ));
+
+        // Hints must point to known coins.
+        let mut snapshot = data.clone();
+        snapshot.coin_states.clear();
+        snapshot.coin_timestamps.clear();
+        assert!(matches!(
+            snapshot.validate(),
+            Err(SimulatorError::Inconsistent(_))
+        ));
     }
-
-    Ok(peer_updates)
 }
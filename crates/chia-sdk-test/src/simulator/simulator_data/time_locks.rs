@@ -0,0 +1,106 @@
+use chia_consensus::gen::{
+    owned_conditions::OwnedSpendBundleConditions,
+    validation_error::{ErrorCode, ValidationErr},
+};
+use clvmr::NodePtr;
+
+use crate::SimulatorError;
+
+use super::SimulatorData;
+
+/// Checks the height and time lock conditions of a spend bundle against the next block.
+pub(super) fn validate_time_locks(
+    data: &SimulatorData,
+    conds: &OwnedSpendBundleConditions,
+) -> Result<(), SimulatorError> {
+    let height = data.height;
+    let timestamp = data.timestamp;
+
+    let fail = |code| {
+        Err(SimulatorError::Validation(ValidationErr(
+            NodePtr::NIL,
+            code,
+        )))
+    };
+
+    if height < conds.height_absolute {
+        return fail(ErrorCode::AssertHeightAbsoluteFailed);
+    }
+
+    if timestamp < conds.seconds_absolute {
+        return fail(ErrorCode::AssertSecondsAbsoluteFailed);
+    }
+
+    if conds
+        .before_height_absolute
+        .is_some_and(|before| height >= before)
+    {
+        return fail(ErrorCode::AssertBeforeHeightAbsoluteFailed);
+    }
+
+    if conds
+        .before_seconds_absolute
+        .is_some_and(|before| timestamp >= before)
+    {
+        return fail(ErrorCode::AssertBeforeSecondsAbsoluteFailed);
+    }
+
+    for spend in &conds.spends {
+        // Coins created in this transaction are ephemeral, so they share its height and timestamp.
+        let created_height = data
+            .coin_states
+            .get(&spend.coin_id)
+            .and_then(|coin_state| coin_state.created_height)
+            .unwrap_or(height);
+
+        let created_timestamp = data
+            .coin_timestamps
+            .get(&spend.coin_id)
+            .copied()
+            .unwrap_or(timestamp);
+
+        if spend
+            .height_relative
+            .is_some_and(|relative| height < created_height.saturating_add(relative))
+        {
+            return fail(ErrorCode::AssertHeightRelativeFailed);
+        }
+
+        if spend
+            .seconds_relative
+            .is_some_and(|relative| timestamp < created_timestamp.saturating_add(relative))
+        {
+            return fail(ErrorCode::AssertSecondsRelativeFailed);
+        }
+
+        if spend
+            .before_height_relative
+            .is_some_and(|relative| height >= created_height.saturating_add(relative))
+        {
+            return fail(ErrorCode::AssertBeforeHeightRelativeFailed);
+        }
+
+        if spend
+            .before_seconds_relative
+            .is_some_and(|relative| timestamp >= created_timestamp.saturating_add(relative))
+        {
+            return fail(ErrorCode::AssertBeforeSecondsRelativeFailed);
+        }
+
+        if spend
+            .birth_height
+            .is_some_and(|birth_height| birth_height != created_height)
+        {
+            return fail(ErrorCode::AssertMyBirthHeightFailed);
+        }
+
+        if spend
+            .birth_seconds
+            .is_some_and(|birth_seconds| birth_seconds != created_timestamp)
+        {
+            return fail(ErrorCode::AssertMyBirthSecondsFailed);
+        }
+    }
+
+    Ok(())
+}
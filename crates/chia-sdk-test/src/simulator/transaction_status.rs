@@ -0,0 +1,120 @@
+use std::io::Cursor;
+
+use chia_protocol::{Bytes32, ChiaProtocolMessage, ProtocolMessageTypes};
+use chia_traits::{chia_error, Streamable};
+use clvmr::sha2::Sha256;
+
+/// Whether a transaction has been included by the simulator.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TransactionStatus {
+    /// The transaction was included at the given height.
+    Confirmed { height: u32 },
+    /// The transaction was received but hasn't been included yet.
+    /// The simulator applies spend bundles immediately, so it doesn't report this yet.
+    Pending,
+    /// The transaction has never been seen.
+    Unknown,
+}
+
+/// The simulator's response to a [`RequestTransaction`](chia_protocol::RequestTransaction),
+/// sent as a [`ProtocolMessageTypes::RespondTransaction`] message.
+///
+/// Unlike a full node, which responds with the spend bundle, the simulator responds with the status of the transaction.
+/// It's encoded as the transaction id, followed by a status byte which is `0` if the transaction is unknown,
+/// `1` followed by the big-endian `u32` height if it's confirmed, or `2` if it's pending.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RespondTransactionStatus {
+    pub transaction_id: Bytes32,
+    pub status: TransactionStatus,
+}
+
+impl RespondTransactionStatus {
+    pub fn new(transaction_id: Bytes32, status: TransactionStatus) -> Self {
+        Self {
+            transaction_id,
+            status,
+        }
+    }
+}
+
+impl ChiaProtocolMessage for RespondTransactionStatus {
+    fn msg_type() -> ProtocolMessageTypes {
+        ProtocolMessageTypes::RespondTransaction
+    }
+}
+
+impl Streamable for RespondTransactionStatus {
+    fn update_digest(&self, digest: &mut Sha256) {
+        self.transaction_id.update_digest(digest);
+
+        match self.status {
+            TransactionStatus::Unknown => 0_u8.update_digest(digest),
+            TransactionStatus::Confirmed { height } => {
+                1_u8.update_digest(digest);
+                height.update_digest(digest);
+            }
+            TransactionStatus::Pending => 2_u8.update_digest(digest),
+        }
+    }
+
+    fn stream(&self, out: &mut Vec<u8>) -> chia_error::Result<()> {
+        self.transaction_id.stream(out)?;
+
+        match self.status {
+            TransactionStatus::Unknown => 0_u8.stream(out),
+            TransactionStatus::Confirmed { height } => {
+                1_u8.stream(out)?;
+                height.stream(out)
+            }
+            TransactionStatus::Pending => 2_u8.stream(out),
+        }
+    }
+
+    fn parse<const TRUSTED: bool>(input: &mut Cursor<&[u8]>) -> chia_error::Result<Self> {
+        let transaction_id = Bytes32::parse::<TRUSTED>(input)?;
+
+        let status = match u8::parse::<TRUSTED>(input)? {
+            0 => TransactionStatus::Unknown,
+            1 => TransactionStatus::Confirmed {
+                height: u32::parse::<TRUSTED>(input)?,
+            },
+            2 => TransactionStatus::Pending,
+            _ => return Err(chia_error::Error::InvalidEnum),
+        };
+
+        Ok(Self::new(transaction_id, status))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_respond_transaction_status_encoding() -> anyhow::Result<()> {
+        let transaction_id = Bytes32::new([1; 32]);
+
+        let cases = [
+            (TransactionStatus::Unknown, vec![0]),
+            (
+                TransactionStatus::Confirmed { height: 5 },
+                vec![1, 0, 0, 0, 5],
+            ),
+            (TransactionStatus::Pending, vec![2]),
+        ];
+
+        for (status, encoded_status) in cases {
+            let response = RespondTransactionStatus::new(transaction_id, status);
+            let bytes = response.to_bytes()?;
+            assert_eq!(bytes[..32], transaction_id[..]);
+            assert_eq!(bytes[32..], encoded_status);
+            assert_eq!(RespondTransactionStatus::from_bytes(&bytes)?, response);
+        }
+
+        let mut bytes = transaction_id.to_vec();
+        bytes.push(3);
+        assert!(RespondTransactionStatus::from_bytes(&bytes).is_err());
+
+        Ok(())
+    }
+}
@@ -5,14 +5,15 @@ use chia_protocol::{
     Bytes, Bytes32, CoinState, CoinStateUpdate, Message, NewPeakWallet, ProtocolMessageTypes,
     RegisterForCoinUpdates, RegisterForPhUpdates, RejectCoinState, RejectPuzzleSolution,
     RejectPuzzleState, RejectStateReason, RequestChildren, RequestCoinState, RequestPuzzleSolution,
-    RequestPuzzleState, RespondChildren, RespondCoinState, RespondPuzzleSolution,
-    RespondPuzzleState, RespondToCoinUpdates, RespondToPhUpdates, SendTransaction, TransactionAck,
+    RequestPuzzleState, RequestTransaction, RespondChildren, RespondCoinState,
+    RespondPuzzleSolution, RespondPuzzleState, RespondToCoinUpdates, RespondToPhUpdates,
+    SendTransaction, TransactionAck,
 };
 use chia_traits::Streamable;
 use clvmr::NodePtr;
 use futures_channel::mpsc;
 use futures_util::{SinkExt, StreamExt};
-use indexmap::IndexSet;
+use indexmap::{IndexMap, IndexSet};
 use itertools::Itertools;
 use tokio::{
     net::TcpStream,
@@ -25,9 +26,11 @@ use super::{
     peer_map::Ws,
     simulator_config::SimulatorConfig,
     simulator_data::{new_transaction, SimulatorData},
-    PeerMap,
+    PeerMap, RespondTransactionStatus,
 };
 
+const SUBSCRIPTION_BATCH_SIZE: usize = 5000;
+
 pub(crate) async fn ws_connection(
     peer_map: PeerMap,
     ws: WebSocketStream<TcpStream>,
@@ -108,13 +111,16 @@ async fn handle_message(
         }
         ProtocolMessageTypes::RequestCoinState => {
             let request = RequestCoinState::from_bytes(&request.data)?;
-            let response = request_coin_state(addr, request, config, data)?;
-            (ProtocolMessageTypes::RespondCoinState, response)
+            request_coin_state(addr, request, config, data)?
         }
         ProtocolMessageTypes::RequestPuzzleState => {
             let request = RequestPuzzleState::from_bytes(&request.data)?;
-            let response = request_puzzle_state(addr, request, config, data)?;
-            (ProtocolMessageTypes::RespondPuzzleState, response)
+            request_puzzle_state(addr, request, config, data)?
+        }
+        ProtocolMessageTypes::RequestTransaction => {
+            let request = RequestTransaction::from_bytes(&request.data)?;
+            let response = request_transaction(&request, &data)?;
+            (ProtocolMessageTypes::RespondTransaction, response)
         }
         message_type => {
             return Err(SimulatorError::UnsupportedMessage(message_type));
@@ -141,10 +147,11 @@ async fn send_transaction(
 ) -> Result<Bytes, SimulatorError> {
     let transaction_id = request.transaction.name();
 
-    let updates = match new_transaction(config, &mut data, request.transaction, 6_600_000_000) {
+    let updates = match new_transaction(config, &mut data, request.transaction) {
         Ok(updates) => updates,
         Err(error) => {
             log::error!("error processing transaction: {:?}", &error);
+            data.reject_transaction(transaction_id, error.to_string());
 
             let validation_error = match error {
                 SimulatorError::Validation(validation_error) => validation_error,
@@ -161,21 +168,24 @@ async fn send_transaction(
         }
     };
 
-    let header_hash = data.header_hash(data.height());
+    broadcast_updates(&peer_map, &data, &updates).await?;
 
-    let new_peak = Message {
-        msg_type: ProtocolMessageTypes::NewPeakWallet,
-        id: None,
-        data: NewPeakWallet::new(header_hash, data.height(), 0, data.height())
-            .to_bytes()
-            .unwrap()
-            .into(),
-    }
-    .to_bytes()?;
+    Ok(TransactionAck::new(transaction_id, 1, None)
+        .to_bytes()?
+        .into())
+}
+
+/// Sends the new peak to every peer, followed by the coin state updates for that peer, if any.
+pub(crate) async fn broadcast_updates(
+    peer_map: &PeerMap,
+    data: &SimulatorData,
+    updates: &IndexMap<SocketAddr, IndexSet<CoinState>>,
+) -> Result<(), SimulatorError> {
+    let header_hash = data.header_hash(data.height());
+    let new_peak = new_peak_message(data)?;
 
-    // Send updates to peers.
     for (addr, mut peer) in peer_map.peers().await {
-        peer.send(new_peak.clone().into()).await.unwrap();
+        peer.send(new_peak.clone()).await.unwrap();
 
         let Some(peer_updates) = updates.get(&addr).cloned() else {
             continue;
@@ -199,9 +209,36 @@ async fn send_transaction(
         peer.send(update.into()).await?;
     }
 
-    Ok(TransactionAck::new(transaction_id, 1, None)
-        .to_bytes()?
-        .into())
+    Ok(())
+}
+
+/// Builds the [`NewPeakWallet`] message for the current peak.
+pub(crate) fn new_peak_message(data: &SimulatorData) -> Result<WsMessage, SimulatorError> {
+    let header_hash = data.header_hash(data.height());
+
+    Ok(Message {
+        msg_type: ProtocolMessageTypes::NewPeakWallet,
+        id: None,
+        data: NewPeakWallet::new(header_hash, data.height(), 0, data.height())
+            .to_bytes()?
+            .into(),
+    }
+    .to_bytes()?
+    .into())
+}
+
+/// Responds with the status of the transaction, encoded as described by [`RespondTransactionStatus`].
+fn request_transaction(
+    request: &RequestTransaction,
+    data: &SimulatorData,
+) -> Result<Bytes, SimulatorError> {
+    let status = data.transaction_status(request.transaction_id);
+
+    Ok(
+        RespondTransactionStatus::new(request.transaction_id, status)
+            .to_bytes()?
+            .into(),
+    )
 }
 
 fn register_for_coin_updates(
@@ -209,20 +246,21 @@ fn register_for_coin_updates(
     request: RegisterForCoinUpdates,
     mut data: MutexGuard<'_, SimulatorData>,
 ) -> Result<Bytes, SimulatorError> {
-    let coin_ids: IndexSet<Bytes32> = request.coin_ids.iter().copied().collect();
+    let mut coin_states = Vec::new();
 
-    let coin_states: Vec<CoinState> = data
-        .lookup_coin_ids(&coin_ids)
-        .into_iter()
-        .filter(|cs| {
+    // Arbitrarily large subscription lists are processed in batches.
+    for chunk in request.coin_ids.chunks(SUBSCRIPTION_BATCH_SIZE) {
+        let coin_ids: IndexSet<Bytes32> = chunk.iter().copied().collect();
+
+        coin_states.extend(data.lookup_coin_ids(&coin_ids).into_iter().filter(|cs| {
             let created_height = cs.created_height.unwrap_or(0);
             let spent_height = cs.spent_height.unwrap_or(0);
             let height = u32::max(created_height, spent_height);
             height >= request.min_height
-        })
-        .collect();
+        }));
 
-    data.add_coin_subscriptions(peer, coin_ids);
+        data.add_coin_subscriptions(peer, coin_ids);
+    }
 
     Ok(RespondToCoinUpdates {
         coin_ids: request.coin_ids,
@@ -300,14 +338,24 @@ fn request_coin_state(
     request: RequestCoinState,
     config: &SimulatorConfig,
     mut data: MutexGuard<'_, SimulatorData>,
-) -> Result<Bytes, SimulatorError> {
+) -> Result<(ProtocolMessageTypes, Bytes), SimulatorError> {
+    if let Some(reason) = data.take_forced_rejection(ProtocolMessageTypes::RequestCoinState) {
+        return Ok((
+            ProtocolMessageTypes::RejectCoinState,
+            RejectCoinState::new(reason).to_bytes()?.into(),
+        ));
+    }
+
     if (request.previous_height.is_some()
         && request.header_hash != data.header_hash(request.previous_height.unwrap()))
         || (request.previous_height.is_none() && request.header_hash != config.genesis_challenge)
     {
-        return Ok(RejectCoinState::new(RejectStateReason::Reorg)
-            .to_bytes()?
-            .into());
+        return Ok((
+            ProtocolMessageTypes::RejectCoinState,
+            RejectCoinState::new(RejectStateReason::Reorg)
+                .to_bytes()?
+                .into(),
+        ));
     }
 
     let coin_ids: IndexSet<Bytes32> = request.coin_ids.iter().copied().collect();
@@ -315,11 +363,12 @@ fn request_coin_state(
     let subscription_count = data.subscription_count(peer);
 
     if subscription_count + coin_ids.len() > config.max_subscriptions && request.subscribe {
-        return Ok(
+        return Ok((
+            ProtocolMessageTypes::RejectCoinState,
             RejectCoinState::new(RejectStateReason::ExceededSubscriptionLimit)
                 .to_bytes()?
                 .into(),
-        );
+        ));
     }
 
     let coin_states: Vec<CoinState> = data
@@ -337,12 +386,15 @@ fn request_coin_state(
         data.add_coin_subscriptions(peer, coin_ids);
     }
 
-    Ok(RespondCoinState {
-        coin_ids: request.coin_ids,
-        coin_states,
-    }
-    .to_bytes()?
-    .into())
+    Ok((
+        ProtocolMessageTypes::RespondCoinState,
+        RespondCoinState {
+            coin_ids: request.coin_ids,
+            coin_states,
+        }
+        .to_bytes()?
+        .into(),
+    ))
 }
 
 fn request_puzzle_state(
@@ -350,14 +402,24 @@ fn request_puzzle_state(
     request: RequestPuzzleState,
     config: &SimulatorConfig,
     mut data: MutexGuard<'_, SimulatorData>,
-) -> Result<Bytes, SimulatorError> {
+) -> Result<(ProtocolMessageTypes, Bytes), SimulatorError> {
+    if let Some(reason) = data.take_forced_rejection(ProtocolMessageTypes::RequestPuzzleState) {
+        return Ok((
+            ProtocolMessageTypes::RejectPuzzleState,
+            RejectPuzzleState::new(reason).to_bytes()?.into(),
+        ));
+    }
+
     if (request.previous_height.is_some()
         && request.header_hash != data.header_hash(request.previous_height.unwrap()))
         || (request.previous_height.is_none() && request.header_hash != config.genesis_challenge)
     {
-        return Ok(RejectPuzzleState::new(RejectStateReason::Reorg)
-            .to_bytes()?
-            .into());
+        return Ok((
+            ProtocolMessageTypes::RejectPuzzleState,
+            RejectPuzzleState::new(RejectStateReason::Reorg)
+                .to_bytes()?
+                .into(),
+        ));
     }
 
     let puzzle_hashes: IndexSet<Bytes32> = request.puzzle_hashes.iter().copied().collect();
@@ -367,49 +429,41 @@ fn request_puzzle_state(
     if subscription_count + puzzle_hashes.len() > config.max_subscriptions
         && request.subscribe_when_finished
     {
-        return Ok(
+        return Ok((
+            ProtocolMessageTypes::RejectPuzzleState,
             RejectPuzzleState::new(RejectStateReason::ExceededSubscriptionLimit)
                 .to_bytes()?
                 .into(),
-        );
+        ));
     }
 
     let puzzle_hashes: IndexSet<Bytes32> = request.puzzle_hashes.iter().copied().collect();
 
     let mut coin_states: Vec<CoinState> = data
-        .lookup_puzzle_hashes(puzzle_hashes.clone(), request.filters.include_hinted)
+        .filtered_coin_states(puzzle_hashes.clone(), &request.filters, min_height)
         .into_iter()
-        .filter(|cs| {
-            if cs.spent_height.is_none() && !request.filters.include_unspent {
-                return false;
-            }
+        .sorted_by_key(coin_state_height)
+        .collect();
 
-            if cs.spent_height.is_some() && !request.filters.include_spent {
-                return false;
-            }
+    let page_size = config
+        .puzzle_state_batch_size
+        .min(config.max_response_coins);
 
-            let created_height = cs.created_height.unwrap_or(0);
-            let spent_height = cs.spent_height.unwrap_or(0);
-            let height = u32::max(created_height, spent_height);
-            height >= min_height
-        })
-        .sorted_by_key(|cs| u32::max(cs.created_height.unwrap_or(0), cs.spent_height.unwrap_or(0)))
-        .take(config.max_response_coins + 1)
-        .collect();
+    // Pages end on a height boundary, so that the next request can resume from the following height.
+    let mut next_height = None;
 
-    let next_height = if coin_states.len() > config.puzzle_state_batch_size {
-        coin_states
-            .last()
-            .map(|cs| u32::max(cs.created_height.unwrap_or(0), cs.spent_height.unwrap_or(0)))
-    } else {
-        None
-    };
+    if coin_states.len() > page_size {
+        let cutoff = coin_state_height(&coin_states[page_size]);
+        let mut end = coin_states.partition_point(|cs| coin_state_height(cs) < cutoff);
+
+        // If a single height has more coins than fit in a page, it's returned in full.
+        if end == 0 {
+            end = coin_states.partition_point(|cs| coin_state_height(cs) <= cutoff);
+        }
 
-    if let Some(next_height) = next_height {
-        while coin_states.last().map_or(false, |cs| {
-            u32::max(cs.created_height.unwrap_or(0), cs.spent_height.unwrap_or(0)) == next_height
-        }) {
-            coin_states.pop();
+        if end < coin_states.len() {
+            next_height = Some(coin_state_height(&coin_states[end]));
+            coin_states.truncate(end);
         }
     }
 
@@ -417,15 +471,27 @@ fn request_puzzle_state(
         data.add_puzzle_subscriptions(peer, puzzle_hashes);
     }
 
-    let height = next_height.unwrap_or(data.height());
+    // The client passes this back as the previous height, so the next page starts at the next height.
+    let height = next_height.map_or(data.height(), |next_height| next_height - 1);
+
+    Ok((
+        ProtocolMessageTypes::RespondPuzzleState,
+        RespondPuzzleState {
+            height,
+            header_hash: data.header_hash(height),
+            puzzle_hashes: request.puzzle_hashes,
+            coin_states,
+            is_finished: next_height.is_none(),
+        }
+        .to_bytes()?
+        .into(),
+    ))
+}
 
-    Ok(RespondPuzzleState {
-        height,
-        header_hash: data.header_hash(height),
-        puzzle_hashes: request.puzzle_hashes,
-        coin_states,
-        is_finished: next_height.is_none(),
-    }
-    .to_bytes()?
-    .into())
+/// The height at which the coin state last changed.
+fn coin_state_height(coin_state: &CoinState) -> u32 {
+    u32::max(
+        coin_state.created_height.unwrap_or(0),
+        coin_state.spent_height.unwrap_or(0),
+    )
 }
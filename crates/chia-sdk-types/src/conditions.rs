@@ -1,6 +1,6 @@
 use std::collections::HashSet;
 
-use chia_protocol::{Coin, CoinSpend};
+use chia_protocol::{Bytes32, Coin, CoinSpend, SpendBundle};
 use clvm_traits::{FromClvm, FromClvmError, ToClvm, ToClvmError, ToNodePtr};
 use clvmr::{
     reduction::{EvalErr, Reduction},
@@ -8,20 +8,26 @@ use clvmr::{
 };
 use thiserror::Error;
 
+use crate::MAX_BLOCK_COST;
+
 mod agg_sig;
 mod announcements;
 mod coin_info;
 mod concurrent;
+mod custom;
 mod output;
 mod puzzles;
+mod singleton;
 mod time;
 
 pub use agg_sig::*;
 pub use announcements::*;
 pub use coin_info::*;
 pub use concurrent::*;
+pub use custom::*;
 pub use output::*;
 pub use puzzles::*;
+pub use singleton::*;
 pub use time::*;
 
 #[derive(Debug, Error)]
@@ -88,7 +94,7 @@ pub fn run_puzzle(
         &clvmr::ChiaDialect::new(0),
         puzzle,
         solution,
-        11_000_000_000,
+        MAX_BLOCK_COST,
     )?;
     Ok(output)
 }
@@ -102,6 +108,40 @@ pub fn puzzle_conditions(
     parse_conditions(allocator, output)
 }
 
+/// Runs the coin spend and returns the payload of each `REMARK` condition it outputs, in order.
+pub fn remark_payloads(
+    allocator: &mut Allocator,
+    coin_spend: &CoinSpend,
+) -> Result<Vec<NodePtr>, ConditionError> {
+    let puzzle = coin_spend.puzzle_reveal.to_node_ptr(allocator)?;
+    let solution = coin_spend.solution.to_node_ptr(allocator)?;
+
+    Ok(puzzle_conditions(allocator, puzzle, solution)?
+        .into_iter()
+        .filter_map(|condition| match condition {
+            Condition::Remark(remark) => Some(remark.rest),
+            _ => None,
+        })
+        .collect())
+}
+
+/// Runs each coin spend in the bundle, and returns its coin id along with the conditions it outputs.
+pub fn parse_bundle_conditions(
+    allocator: &mut Allocator,
+    bundle: &SpendBundle,
+) -> Result<Vec<(Bytes32, Vec<Condition>)>, ConditionError> {
+    bundle
+        .coin_spends
+        .iter()
+        .map(|coin_spend| {
+            let puzzle = coin_spend.puzzle_reveal.to_node_ptr(allocator)?;
+            let solution = coin_spend.solution.to_node_ptr(allocator)?;
+            let conditions = puzzle_conditions(allocator, puzzle, solution)?;
+            Ok((coin_spend.coin.coin_id(), conditions))
+        })
+        .collect()
+}
+
 pub fn non_ephemeral_coins(coin_spends: &[CoinSpend]) -> Result<Vec<Coin>, ConditionError> {
     let mut allocator = Allocator::new();
     let mut created_coins = HashSet::new();
@@ -135,7 +175,8 @@ pub fn non_ephemeral_coins(coin_spends: &[CoinSpend]) -> Result<Vec<Coin>, Condi
 mod tests {
     use super::*;
 
-    use chia_protocol::{Bytes32, Program};
+    use chia_bls::Signature;
+    use chia_protocol::Program;
     use clvm_traits::{FromNodePtr, ToClvm};
 
     #[test]
@@ -178,4 +219,57 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn test_parse_bundle_conditions() -> anyhow::Result<()> {
+        let mut allocator = Allocator::new();
+
+        let puzzle = 1.to_clvm(&mut allocator)?;
+        let puzzle_reveal = Program::from_node_ptr(&allocator, puzzle)?;
+
+        let first = Coin::new(Bytes32::new([1; 32]), Bytes32::default(), 3);
+        let second = Coin::new(Bytes32::new([2; 32]), Bytes32::default(), 0);
+
+        let create_coins = [
+            CreateCoin::new(Bytes32::new([3; 32]), 1),
+            CreateCoin::new(Bytes32::new([4; 32]), 2),
+        ];
+        let fee = ReserveFee::new(0);
+
+        let first_solution = create_coins.to_clvm(&mut allocator)?;
+        let second_solution = [&fee].to_clvm(&mut allocator)?;
+
+        let bundle = SpendBundle::new(
+            vec![
+                CoinSpend::new(
+                    first,
+                    puzzle_reveal.clone(),
+                    Program::from_node_ptr(&allocator, first_solution)?,
+                ),
+                CoinSpend::new(
+                    second,
+                    puzzle_reveal,
+                    Program::from_node_ptr(&allocator, second_solution)?,
+                ),
+            ],
+            Signature::default(),
+        );
+
+        let conditions = parse_bundle_conditions(&mut allocator, &bundle)?;
+        assert_eq!(
+            conditions,
+            vec![
+                (
+                    first.coin_id(),
+                    create_coins
+                        .into_iter()
+                        .map(Condition::CreateCoin)
+                        .collect()
+                ),
+                (second.coin_id(), vec![Condition::ReserveFee(fee)]),
+            ]
+        );
+
+        Ok(())
+    }
 }
@@ -11,6 +11,12 @@ pub struct AssertMyCoinId {
     pub coin_id: Bytes32,
 }
 
+impl AssertMyCoinId {
+    pub fn new(coin_id: Bytes32) -> Self {
+        Self { coin_id }
+    }
+}
+
 #[derive(ToClvm, FromClvm)]
 #[apply_constants]
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -21,6 +27,12 @@ pub struct AssertMyParentId {
     pub parent_id: Bytes32,
 }
 
+impl AssertMyParentId {
+    pub fn new(parent_id: Bytes32) -> Self {
+        Self { parent_id }
+    }
+}
+
 #[derive(ToClvm, FromClvm)]
 #[apply_constants]
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -31,6 +43,12 @@ pub struct AssertMyPuzzleHash {
     pub puzzle_hash: Bytes32,
 }
 
+impl AssertMyPuzzleHash {
+    pub fn new(puzzle_hash: Bytes32) -> Self {
+        Self { puzzle_hash }
+    }
+}
+
 #[derive(ToClvm, FromClvm)]
 #[apply_constants]
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -0,0 +1,144 @@
+use std::{any::Any, collections::HashMap, fmt::Debug};
+
+use clvm_traits::{FromClvm, FromClvmError};
+use clvmr::{Allocator, NodePtr};
+
+use super::{Condition, ConditionError};
+
+/// An application specific condition, parsed by a parser registered with [`ConditionParsers`].
+pub trait CustomCondition: Debug + Any + Send + Sync {
+    fn as_any(&self) -> &dyn Any;
+}
+
+impl<T> CustomCondition for T
+where
+    T: Debug + Any + Send + Sync,
+{
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+/// Parses a condition with a registered opcode into a custom condition.
+pub type ConditionParser =
+    fn(&Allocator, NodePtr) -> Result<Box<dyn CustomCondition>, FromClvmError>;
+
+/// A condition which is either one of the known [`Condition`] variants, or a custom condition.
+#[derive(Debug)]
+pub enum ParsedCondition {
+    Known(Condition),
+    Custom(Box<dyn CustomCondition>),
+}
+
+impl ParsedCondition {
+    /// Returns the custom condition if it is of the given type.
+    pub fn as_custom<T>(&self) -> Option<&T>
+    where
+        T: CustomCondition,
+    {
+        match self {
+            Self::Known(_) => None,
+            Self::Custom(custom) => (**custom).as_any().downcast_ref(),
+        }
+    }
+}
+
+/// A registry of parsers for condition opcodes that aren't covered by the [`Condition`] enum.
+#[derive(Debug, Default, Clone)]
+pub struct ConditionParsers {
+    parsers: HashMap<i64, ConditionParser>,
+}
+
+impl ConditionParsers {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers a parser for an opcode, replacing any parser that was previously registered.
+    pub fn register_condition_parser(&mut self, opcode: i64, parser: ConditionParser) {
+        self.parsers.insert(opcode, parser);
+    }
+
+    /// Parses a list of conditions, using the registered parsers before falling back to [`Condition`].
+    pub fn parse_conditions(
+        &self,
+        allocator: &Allocator,
+        conditions: NodePtr,
+    ) -> Result<Vec<ParsedCondition>, ConditionError> {
+        Vec::<NodePtr>::from_clvm(allocator, conditions)?
+            .into_iter()
+            .map(|condition| self.parse_condition(allocator, condition))
+            .collect()
+    }
+
+    /// Parses a single condition, using the registered parsers before falling back to [`Condition`].
+    pub fn parse_condition(
+        &self,
+        allocator: &Allocator,
+        condition: NodePtr,
+    ) -> Result<ParsedCondition, ConditionError> {
+        if let Ok((opcode, _)) = <(i64, NodePtr)>::from_clvm(allocator, condition) {
+            if let Some(parser) = self.parsers.get(&opcode) {
+                return Ok(ParsedCondition::Custom(parser(allocator, condition)?));
+            }
+        }
+
+        Ok(ParsedCondition::Known(Condition::from_clvm(
+            allocator, condition,
+        )?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chia_protocol::Bytes;
+    use clvm_traits::{apply_constants, ToClvm};
+
+    use crate::conditions::{run_puzzle, CreateCoinAnnouncement};
+
+    use super::*;
+
+    #[derive(ToClvm, FromClvm)]
+    #[apply_constants]
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    #[clvm(list)]
+    struct Greeting {
+        #[clvm(constant = 100)]
+        opcode: u8,
+        message: Bytes,
+    }
+
+    fn parse_greeting(
+        allocator: &Allocator,
+        condition: NodePtr,
+    ) -> Result<Box<dyn CustomCondition>, FromClvmError> {
+        Ok(Box::new(Greeting::from_clvm(allocator, condition)?))
+    }
+
+    #[test]
+    fn test_custom_condition_parser() -> anyhow::Result<()> {
+        let mut allocator = Allocator::new();
+
+        let greeting = Greeting {
+            message: b"hello".to_vec().into(),
+        };
+        let announcement = CreateCoinAnnouncement::new(b"world".to_vec().into());
+
+        let puzzle = 1.to_clvm(&mut allocator)?;
+        let solution = (&greeting, (&announcement, ())).to_clvm(&mut allocator)?;
+        let output = run_puzzle(&mut allocator, puzzle, solution)?;
+
+        let mut parsers = ConditionParsers::new();
+        parsers.register_condition_parser(100, parse_greeting);
+
+        let conditions = parsers.parse_conditions(&allocator, output)?;
+        assert_eq!(conditions.len(), 2);
+        assert_eq!(conditions[0].as_custom::<Greeting>(), Some(&greeting));
+        assert!(matches!(
+            &conditions[1],
+            ParsedCondition::Known(Condition::CreateCoinAnnouncement(condition)) if condition == &announcement
+        ));
+
+        Ok(())
+    }
+}
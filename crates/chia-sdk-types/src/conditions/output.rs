@@ -30,6 +30,18 @@ impl CreateCoin {
     pub fn with_hint(puzzle_hash: Bytes32, amount: u64, hint: Bytes32) -> Self {
         Self::with_memos(puzzle_hash, amount, vec![hint.into()])
     }
+
+    /// The memos of the created coin, the first of which is the hint if it's 32 bytes long.
+    pub fn memos(&self) -> &[Bytes] {
+        &self.memos
+    }
+
+    /// The hint of the created coin, which is the first memo if it's 32 bytes long.
+    /// Any memos after the hint are ignored.
+    pub fn hint(&self) -> Option<Bytes32> {
+        let memo = self.memos.first()?;
+        Bytes32::try_from(memo.as_ref()).ok()
+    }
 }
 
 #[derive(ToClvm, FromClvm)]
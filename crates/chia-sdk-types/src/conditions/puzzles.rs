@@ -101,17 +101,25 @@ impl NewNftOwner {
             did_inner_puzzle_hash,
         }
     }
+
+    /// Appends a trade price, which the royalty transfer program pays royalties on.
+    #[must_use]
+    pub fn with_trade_price(mut self, trade_price: u64, puzzle_hash: Bytes32) -> Self {
+        self.trade_prices
+            .push(NftTradePrice::new(trade_price, puzzle_hash));
+        self
+    }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, ToClvm, FromClvm)]
 #[clvm(list)]
 pub struct NftTradePrice {
-    pub trade_price: u16,
+    pub trade_price: u64,
     pub puzzle_hash: Bytes32,
 }
 
 impl NftTradePrice {
-    pub fn new(trade_price: u16, puzzle_hash: Bytes32) -> Self {
+    pub fn new(trade_price: u64, puzzle_hash: Bytes32) -> Self {
         Self {
             trade_price,
             puzzle_hash,
@@ -0,0 +1,123 @@
+use chia_protocol::CoinSpend;
+use chia_puzzles::singleton::{SingletonArgs, SingletonSolution, SINGLETON_TOP_LAYER_PUZZLE_HASH};
+use clvm_traits::{FromClvm, ToNodePtr};
+use clvm_utils::{tree_hash, CurriedProgram};
+use clvmr::{Allocator, NodePtr};
+
+use super::{run_puzzle, ConditionError, CreateCoin};
+
+/// What the inner puzzle of a singleton outputs, which decides whether the spend is valid.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SingletonOutput {
+    /// The singleton is melted, so it doesn't need to create a child.
+    Melt,
+    /// The number of coins created with an odd amount.
+    OddChildren(usize),
+}
+
+impl SingletonOutput {
+    /// A singleton spend must either melt the singleton or create exactly one odd child.
+    pub fn is_valid(self) -> bool {
+        matches!(self, Self::Melt | Self::OddChildren(1))
+    }
+}
+
+/// Runs the inner puzzle of a singleton and checks whether it melts or how many odd children it creates.
+pub fn singleton_output(
+    allocator: &mut Allocator,
+    inner_puzzle: NodePtr,
+    inner_solution: NodePtr,
+) -> Result<SingletonOutput, ConditionError> {
+    let output = run_puzzle(allocator, inner_puzzle, inner_solution)?;
+    let conditions = Vec::<NodePtr>::from_clvm(allocator, output)?;
+
+    let mut odd_children = 0;
+
+    for condition in conditions {
+        // The top layer only checks for the magic amount, since inner layers may wrap the puzzle hash.
+        if matches!(
+            <(u8, (NodePtr, (i64, NodePtr)))>::from_clvm(allocator, condition),
+            Ok((51, (_, (-113, _))))
+        ) {
+            return Ok(SingletonOutput::Melt);
+        }
+
+        if let Ok(create_coin) = CreateCoin::from_clvm(allocator, condition) {
+            if create_coin.amount % 2 == 1 {
+                odd_children += 1;
+            }
+        }
+    }
+
+    Ok(SingletonOutput::OddChildren(odd_children))
+}
+
+/// Checks the output of the inner puzzle of a singleton coin spend.
+/// Returns [`None`] if the coin spend isn't a singleton.
+pub fn singleton_spend_output(
+    allocator: &mut Allocator,
+    coin_spend: &CoinSpend,
+) -> Result<Option<SingletonOutput>, ConditionError> {
+    let puzzle = coin_spend.puzzle_reveal.to_node_ptr(allocator)?;
+
+    let Ok(puzzle) = CurriedProgram::<NodePtr, NodePtr>::from_clvm(allocator, puzzle) else {
+        return Ok(None);
+    };
+
+    if tree_hash(allocator, puzzle.program) != SINGLETON_TOP_LAYER_PUZZLE_HASH {
+        return Ok(None);
+    }
+
+    let solution = coin_spend.solution.to_node_ptr(allocator)?;
+    let args = SingletonArgs::<NodePtr>::from_clvm(allocator, puzzle.args)?;
+    let solution = SingletonSolution::<NodePtr>::from_clvm(allocator, solution)?;
+
+    singleton_output(allocator, args.inner_puzzle, solution.inner_solution).map(Some)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use chia_protocol::Bytes32;
+    use clvm_traits::ToClvm;
+
+    use crate::conditions::MeltSingleton;
+
+    #[test]
+    fn test_singleton_output() -> anyhow::Result<()> {
+        let mut allocator = Allocator::new();
+
+        // The identity puzzle outputs its solution as the conditions.
+        let puzzle = 1.to_clvm(&mut allocator)?;
+
+        let odd = CreateCoin::new(Bytes32::default(), 1);
+        let even = CreateCoin::new(Bytes32::default(), 2);
+
+        let cases = [
+            (vec![], SingletonOutput::OddChildren(0)),
+            (vec![odd.clone(), even], SingletonOutput::OddChildren(1)),
+            (
+                vec![odd.clone(), odd.clone()],
+                SingletonOutput::OddChildren(2),
+            ),
+        ];
+
+        for (conditions, expected) in cases {
+            let solution = conditions.to_clvm(&mut allocator)?;
+            let output = singleton_output(&mut allocator, puzzle, solution)?;
+            assert_eq!(output, expected);
+            assert_eq!(
+                output.is_valid(),
+                expected == SingletonOutput::OddChildren(1)
+            );
+        }
+
+        let solution = (MeltSingleton::new(), (odd.clone(), (odd, ()))).to_clvm(&mut allocator)?;
+        let output = singleton_output(&mut allocator, puzzle, solution)?;
+        assert_eq!(output, SingletonOutput::Melt);
+        assert!(output.is_valid());
+
+        Ok(())
+    }
+}
@@ -0,0 +1,2 @@
+/// The maximum CLVM cost of a block, which is also the most that a single puzzle is allowed to run.
+pub const MAX_BLOCK_COST: u64 = 11_000_000_000;
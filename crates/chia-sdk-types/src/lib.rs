@@ -1 +1,5 @@
 pub mod conditions;
+
+mod constants;
+
+pub use constants::*;
@@ -76,8 +76,7 @@ impl CustomExt for SpendContext {
         let spend = conditions.custom_spend(self, public_key)?;
         let puzzle_reveal = self.serialize(&spend.puzzle())?;
         let solution = self.serialize(&spend.solution())?;
-        self.insert_coin_spend(CoinSpend::new(coin, puzzle_reveal, solution));
-        Ok(())
+        self.insert_coin_spend(CoinSpend::new(coin, puzzle_reveal, solution))
     }
 }
 
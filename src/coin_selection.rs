@@ -22,26 +22,36 @@ pub enum CoinSelectionError {
     ExceededMaxCoins,
 }
 
+/// The maximum number of coins that can be selected.
+const MAX_COINS: usize = 500;
+
+/// The maximum number of branches explored by the branch and bound algorithm.
+const BNB_MAX_TRIES: usize = 100_000;
+
+/// The strategy used to select coins.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum SelectionStrategy {
+    /// Uses the knapsack algorithm, which is what [`select_coins`] does.
+    #[default]
+    Knapsack,
+
+    /// Selects the largest coins until the amount is reached.
+    LargestFirst,
+
+    /// Searches for a set of coins that matches the amount with at most `max_change` left over,
+    /// preferring the least change. Falls back to [`SelectionStrategy::LargestFirst`] if there is no such set.
+    BranchAndBound { max_change: u64 },
+}
+
 /// Uses the knapsack algorithm to select coins.
 pub fn select_coins(
     mut spendable_coins: Vec<Coin>,
     amount: u128,
 ) -> Result<Vec<Coin>, CoinSelectionError> {
-    let max_coins = 500;
+    // Coins with no value can't contribute to the amount.
+    spendable_coins.retain(|coin| coin.amount > 0);
 
-    // You cannot spend no coins.
-    if spendable_coins.is_empty() {
-        return Err(CoinSelectionError::NoSpendableCoins);
-    }
-
-    // Checks to ensure the balance is sufficient before continuing.
-    let spendable_amount = spendable_coins
-        .iter()
-        .fold(0u128, |acc, coin| acc + coin.amount as u128);
-
-    if spendable_amount < amount {
-        return Err(CoinSelectionError::InsufficientBalance(spendable_amount));
-    }
+    check_spendable_amount(&spendable_coins, amount)?;
 
     // Sorts by amount, descending.
     spendable_coins.sort_unstable_by_key(|coin| Reverse(coin.amount));
@@ -66,7 +76,7 @@ pub fn select_coins(
     }
 
     // Check for an exact match.
-    if smaller_sum == amount && smaller_coins.len() < max_coins && amount != 0 {
+    if smaller_sum == amount && smaller_coins.len() < MAX_COINS && amount != 0 {
         return Ok(smaller_coins.into_iter().collect());
     }
 
@@ -82,7 +92,7 @@ pub fn select_coins(
             &spendable_coins,
             amount,
             u128::MAX,
-            max_coins,
+            MAX_COINS,
         ) {
             return Ok(result.into_iter().collect());
         }
@@ -90,7 +100,7 @@ pub fn select_coins(
         // Knapsack failed to select coins, so try summing the largest coins.
         let summed_coins = sum_largest_coins(&spendable_coins, amount);
 
-        if summed_coins.len() <= max_coins {
+        if summed_coins.len() <= MAX_COINS {
             return Ok(summed_coins.into_iter().collect());
         } else {
             return Err(CoinSelectionError::ExceededMaxCoins);
@@ -106,6 +116,63 @@ pub fn select_coins(
     Err(CoinSelectionError::ExceededMaxCoins)
 }
 
+/// Selects coins using the given strategy.
+pub fn select_coins_with_strategy(
+    mut spendable_coins: Vec<Coin>,
+    amount: u128,
+    strategy: SelectionStrategy,
+) -> Result<Vec<Coin>, CoinSelectionError> {
+    let max_change = match strategy {
+        SelectionStrategy::Knapsack => return select_coins(spendable_coins, amount),
+        SelectionStrategy::LargestFirst => None,
+        SelectionStrategy::BranchAndBound { max_change } => Some(max_change),
+    };
+
+    spendable_coins.retain(|coin| coin.amount > 0);
+
+    check_spendable_amount(&spendable_coins, amount)?;
+
+    // Sorts by amount, descending.
+    spendable_coins.sort_unstable_by_key(|coin| Reverse(coin.amount));
+
+    if let Some(max_change) = max_change {
+        if let Some(result) =
+            branch_and_bound_coin_algorithm(&spendable_coins, amount, max_change as u128, MAX_COINS)
+        {
+            return Ok(result);
+        }
+    }
+
+    let summed_coins = sum_largest_coins(&spendable_coins, amount);
+
+    if summed_coins.len() <= MAX_COINS {
+        Ok(summed_coins.into_iter().collect())
+    } else {
+        Err(CoinSelectionError::ExceededMaxCoins)
+    }
+}
+
+fn check_spendable_amount(
+    spendable_coins: &[Coin],
+    amount: u128,
+) -> Result<(), CoinSelectionError> {
+    // You cannot spend no coins.
+    if spendable_coins.is_empty() {
+        return Err(CoinSelectionError::NoSpendableCoins);
+    }
+
+    // Checks to ensure the balance is sufficient before continuing.
+    let spendable_amount = spendable_coins
+        .iter()
+        .fold(0u128, |acc, coin| acc + coin.amount as u128);
+
+    if spendable_amount < amount {
+        return Err(CoinSelectionError::InsufficientBalance(spendable_amount));
+    }
+
+    Ok(())
+}
+
 fn sum_largest_coins(coins: &[Coin], amount: u128) -> IndexSet<Coin> {
     let mut selected_coins = IndexSet::new();
     let mut selected_sum = 0;
@@ -190,6 +257,104 @@ pub fn knapsack_coin_algorithm(
     best_coins
 }
 
+/// Runs a depth first branch and bound search over coins sorted by amount descending,
+/// looking for the set of coins with the least change that doesn't exceed `max_change`.
+pub fn branch_and_bound_coin_algorithm(
+    spendable_coins: &[Coin],
+    amount: u128,
+    max_change: u128,
+    max_coins: usize,
+) -> Option<Vec<Coin>> {
+    let mut remaining = vec![0; spendable_coins.len() + 1];
+
+    for (index, coin) in spendable_coins.iter().enumerate().rev() {
+        remaining[index] = remaining[index + 1] + coin.amount as u128;
+    }
+
+    let mut search = BranchAndBound {
+        coins: spendable_coins,
+        remaining,
+        amount,
+        max_amount: amount.saturating_add(max_change),
+        max_coins,
+        tries: 0,
+        selected: Vec::new(),
+        best: None,
+    };
+
+    search.explore(0, 0);
+
+    search.best.map(|(_, indices)| {
+        indices
+            .into_iter()
+            .map(|index| spendable_coins[index])
+            .collect()
+    })
+}
+
+struct BranchAndBound<'a> {
+    coins: &'a [Coin],
+    remaining: Vec<u128>,
+    amount: u128,
+    max_amount: u128,
+    max_coins: usize,
+    tries: usize,
+    selected: Vec<usize>,
+    best: Option<(u128, Vec<usize>)>,
+}
+
+impl BranchAndBound<'_> {
+    fn explore(&mut self, index: usize, sum: u128) {
+        if self.tries >= BNB_MAX_TRIES || sum > self.max_amount {
+            return;
+        }
+
+        self.tries += 1;
+
+        // Adding more coins would only increase the change.
+        if sum >= self.amount && !self.selected.is_empty() {
+            let change = sum - self.amount;
+
+            let is_better = self.best.as_ref().map_or(true, |(best_change, best)| {
+                change < *best_change
+                    || (change == *best_change && self.selected.len() < best.len())
+            });
+
+            if is_better {
+                self.best = Some((change, self.selected.clone()));
+            }
+
+            return;
+        }
+
+        if index == self.coins.len()
+            || sum + self.remaining[index] < self.amount
+            || self.selected.len() >= self.max_coins
+        {
+            return;
+        }
+
+        let coin_amount = self.coins[index].amount as u128;
+
+        self.selected.push(index);
+        self.explore(index + 1, sum + coin_amount);
+        self.selected.pop();
+
+        if matches!(self.best, Some((0, _))) {
+            return;
+        }
+
+        // Omitting this coin means omitting coins of the same amount is equivalent, so skip them.
+        let mut next = index + 1;
+
+        while next < self.coins.len() && self.coins[next].amount as u128 == coin_amount {
+            next += 1;
+        }
+
+        self.explore(next, sum);
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use chia_protocol::Bytes32;
@@ -228,6 +393,49 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_branch_and_bound() {
+        let coins = coin_list![1000, 600, 500, 300, 100];
+
+        // Largest first leaves 200 in change.
+        let selected =
+            select_coins_with_strategy(coins.clone(), 800, SelectionStrategy::LargestFirst)
+                .unwrap();
+        assert_eq!(selected, coin_list![1000]);
+
+        // Branch and bound finds an exact match instead.
+        let selected = select_coins_with_strategy(
+            coins,
+            800,
+            SelectionStrategy::BranchAndBound { max_change: 0 },
+        )
+        .unwrap();
+        assert_eq!(selected, coin_list![500, 300]);
+    }
+
+    #[test]
+    fn test_branch_and_bound_change() {
+        let coins = coin_list![1000, 600, 500, 300];
+
+        // There's no exact match, but 600 and 300 are within the allowed change.
+        let selected = select_coins_with_strategy(
+            coins.clone(),
+            850,
+            SelectionStrategy::BranchAndBound { max_change: 100 },
+        )
+        .unwrap();
+        assert_eq!(selected, coin_list![600, 300]);
+
+        // Without enough allowed change, it falls back to largest first.
+        let selected = select_coins_with_strategy(
+            coins,
+            850,
+            SelectionStrategy::BranchAndBound { max_change: 10 },
+        )
+        .unwrap();
+        assert_eq!(selected, coin_list![1000]);
+    }
+
     #[test]
     fn test_no_coins() {
         // There is no amount to select from.
@@ -238,4 +446,21 @@ mod tests {
         let selected = select_coins(Vec::new(), 0);
         assert_eq!(selected, Err(CoinSelectionError::NoSpendableCoins));
     }
+
+    #[test]
+    fn test_zero_amount_coins() {
+        let coins = coin_list![0, 100, 0, 50];
+
+        // Zero amount coins add nothing, so they aren't selected.
+        let selected = select_coins(coins.clone(), 150).unwrap();
+        assert!(selected.iter().all(|coin| coin.amount > 0));
+
+        let selected =
+            select_coins_with_strategy(coins, 150, SelectionStrategy::LargestFirst).unwrap();
+        assert_eq!(selected, coin_list![100, 50]);
+
+        // Zero amount coins alone aren't spendable.
+        let selected = select_coins(coin_list![0, 0], 0);
+        assert_eq!(selected, Err(CoinSelectionError::NoSpendableCoins));
+    }
 }